@@ -53,6 +53,12 @@ struct PosParams {
 };
 
 [[group(1), binding(2)]] var<uniform> params : SimParams;
+
+// matrix from the response the draw shaders weight the rays with to the output colour space
+struct ColourParams {
+  to_output: mat3x3<f32>;
+};
+[[group(1), binding(3)]] var<uniform> colour : ColourParams;
 [[group(2), binding(1)]] var<storage, read> elements : Elements;
 
 [[group(1), binding(0)]] var<uniform> posParams : PosParams;
@@ -74,7 +80,9 @@ var s_diffuse: sampler;
 [[stage(fragment)]]
 fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     let pos = vec2<f32>(in.clip_position.x / params.width, in.clip_position.y / params.height);
-    let sample = textureSample(t_diffuse, s_diffuse, pos);
+    let response = textureSample(t_diffuse, s_diffuse, pos);
+    // colours outside of the output colour space are clipped
+    let sample = vec4<f32>(max(colour.to_output * response.rgb, vec3<f32>(0.)), response.a);
     var bg = vec4<f32>(0.15,0.15,0.25,1.0);
 
     let screenAspect = normalize(vec2<f32>(params.height, params.width));
@@ -46,25 +46,33 @@ struct Monomial {
     b: f32;
     c: f32;
     d: f32;
+    // exponent of the wavelength
+    e: f32;
     coefficient: f32;
 };
 
 struct Polynomial {
-    monomials: [[stride(20)]] array<Monomial>;
+    monomials: [[stride(24)]] array<Monomial>;
 };
 
 [[group(1), binding(2)]] var<uniform> params : SimParams;
+
+// matrix from the response the draw shaders weight the rays with to the output colour space
+struct ColourParams {
+  to_output: mat3x3<f32>;
+};
+[[group(1), binding(3)]] var<uniform> colour : ColourParams;
 [[group(1), binding(0)]] var<uniform> posParams : PosParams;
 
 [[group(2), binding(0)]] var<storage, read> terms : Polynomial;
 [[group(2), binding(1)]] var<uniform> polyParams : PolyParams;
 
-fn eval(x: vec4<f32>, index: u32) -> f32 {
+fn eval(x: vec4<f32>, wavelength: f32, index: u32) -> f32 {
     var res = 0.;
     for (var i = u32(index * polyParams.num_terms); i < (index + u32(1)) * polyParams.num_terms; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient > 0. || term.coefficient < 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.a > 0.) {
                 if (i32(term.a) % 2 == 1 && x.x < 0.) {
                     term_res = -term_res * pow(-x.x, term.a);
@@ -99,7 +107,7 @@ fn eval(x: vec4<f32>, index: u32) -> f32 {
     return res;
 }
 
-fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
+fn eval_grad_zw(x: vec4<f32>, wavelength: f32, index: u32) -> f32 {
     var dz = vec2<f32>(0.);
     var dw = vec2<f32>(0.);
     let poyly_index = index * u32(2) * polyParams.num_terms;
@@ -107,7 +115,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index); i < polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.c == 0.) {
                 term_res = 0.;
             } else {
@@ -148,7 +156,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index) + polyParams.num_terms; i < u32(2) * polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.c == 0.) {
                 term_res = 0.;
             } else {
@@ -189,7 +197,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index); i < polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.d == 0.) {
                 term_res = 0.;
             } else {
@@ -230,7 +238,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index) + polyParams.num_terms; i < u32(2) * polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.d == 0.) {
                 term_res = 0.;
             } else {
@@ -297,21 +305,23 @@ fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
         pos.y = clamp(pos.y, 0., 1.);
         pos = pos * posParams.width - posParams.width/2.;
     }
-    let sample = textureSample(t_diffuse, s_diffuse, pos);
+    let response = textureSample(t_diffuse, s_diffuse, pos);
+    // colours outside of the output colour space are clipped
+    let sample = vec4<f32>(max(colour.to_output * response.rgb, vec3<f32>(0.)), response.a);
     var bg = vec4<f32>(0.0,0.0,0.0,1.0);
 
     var bright = 0.;
     let num_polys = arrayLength(&terms.monomials) / polyParams.num_terms;
     // for (var i = u32(0); i < num_polys; i = i + u32(1)) {
-    //     let poly_res = eval(vec4<f32>(0., 0., pos.x, pos.y), i);
+    //     let poly_res = eval(vec4<f32>(0., 0., pos.x, pos.y), posParams.init.wavelength, i);
     //     bright = bright + abs(poly_res);
     // }
     // let bright = 100. * pos.x * pos.y;
     if (debug) {
         let x = vec4<f32>(posParams.init.o.xy, pos.x, pos.y);
-        // bright = eval(x, u32(params.which_ghost));
-        bright = eval_grad_zw(x, u32(params.which_ghost)) * 1000.;
-        // bright = eval(x, u32(params.which_ghost * 2.) + u32(0));
+        // bright = eval(x, posParams.init.wavelength, u32(params.which_ghost));
+        bright = eval_grad_zw(x, posParams.init.wavelength, u32(params.which_ghost)) * 1000.;
+        // bright = eval(x, posParams.init.wavelength, u32(params.which_ghost * 2.) + u32(0));
     }
 
     if (debug) {
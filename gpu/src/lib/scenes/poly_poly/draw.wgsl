@@ -30,11 +30,31 @@ struct Element {
   c1_2: f32;
   c2_2: f32;
   c3_2: f32;
-  coating_ior: f32;
-  coating_thickness: f32;
+  coating_start: f32;// index of the first layer in Coatings
+  coating_num: f32;// number of coating layers
   position: f32;// num_blades if aperture
-  entry: f32;// 0: false, 1: true, 2: aperture
+  entry: f32;// 0: false, 1: true, 2: aperture, 3: sensor, 4/5: filter front/back
   spherical: f32;// 0: false, 1: true
+  k: f32;// conic constant
+  a4: f32;// even asphere coefficients
+  a6: f32;
+  a8: f32;
+  a10: f32;
+  a12: f32;
+  a14: f32;
+  a16: f32;
+  semi_diameter: f32;// radius of the clear aperture, 0: unlimited
+  absorption1: f32;// absorption coefficient of the glass every 50 nm from 380 nm
+  absorption2: f32;
+  absorption3: f32;
+  absorption4: f32;
+  absorption5: f32;
+  absorption6: f32;
+  absorption7: f32;
+  absorption8: f32;
+  absorption9: f32;
+  formula: f32;// dispersion formula of the glass, 0: Sellmeier, 1: Schott, 2: Herzberger, 3: Conrady
+  formula_2: f32;// dispersion formula of the outer ior
 };
 
 struct SimParams {
@@ -77,7 +97,7 @@ struct Sensor {
 
 /// all the Elements of the Lens under test
 struct Elements {
-  el: [[stride(72)]] array<Element>;
+  el: [[stride(152)]] array<Element>;
 };
 
 struct PolyParams {
@@ -89,11 +109,13 @@ struct Monomial {
     b: f32;
     c: f32;
     d: f32;
+    // exponent of the wavelength
+    e: f32;
     coefficient: f32;
 };
 
 struct Polynomial {
-    monomials: [[stride(20)]] array<Monomial>;
+    monomials: [[stride(24)]] array<Monomial>;
 };
 
 [[group(0), binding(0)]] var<storage, read> elements : Elements;
@@ -105,7 +127,7 @@ struct Polynomial {
 [[group(2), binding(0)]] var<storage, read> terms : Polynomial;
 [[group(2), binding(1)]] var<uniform> polyParams : PolyParams;
 
-fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
+fn eval_grad_zw(x: vec4<f32>, wavelength: f32, index: u32) -> f32 {
     var dz = vec2<f32>(0.);
     var dw = vec2<f32>(0.);
     let poyly_index = index * u32(2) * polyParams.num_terms;
@@ -113,7 +135,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index); i < polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.c == 0.) {
                 term_res = 0.;
             } else {
@@ -154,7 +176,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index) + polyParams.num_terms; i < u32(2) * polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.c == 0.) {
                 term_res = 0.;
             } else {
@@ -195,7 +217,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index); i < polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.d == 0.) {
                 term_res = 0.;
             } else {
@@ -236,7 +258,7 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     for (var i = u32(poyly_index) + polyParams.num_terms; i < u32(2) * polyParams.num_terms + poyly_index; i = i + u32(1)) {
         let term = terms.monomials[i];
         if (term.coefficient != 0.) {
-            var term_res = term.coefficient;
+            var term_res = term.coefficient * pow(wavelength, term.e);
             if (term.d == 0.) {
                 term_res = 0.;
             } else {
@@ -276,9 +298,11 @@ fn eval_grad_zw(x: vec4<f32>, index: u32) -> f32 {
     return 1. / abs(dz.x * dw.y - dz.y * dw.x);
 }
 
+/// response of the sensor at wavelength in µm, the LUT has a sample every 10nm
 fn lookup_rgb(wavelength: f32) -> vec3<f32> {
-    let lower_index = u32(clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 34.));
-    let factor = (wavelength % 0.1) * 10.;
+    let x = clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 40.);
+    let lower_index = min(u32(x), u32(39));
+    let factor = x - f32(lower_index);
     return sensor.measuremens[lower_index].rgb * (1. - factor)
      + sensor.measuremens[lower_index + u32(1)].rgb * (factor);
 }
@@ -326,7 +350,7 @@ fn isNan( val: f32 ) -> bool {
 fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
   for (var i = u32(0); i < arrayLength(&elements.el); i = i + u32(1)) {
       let element = elements.el[i];
-      if (element.entry > 1.) {
+      if (element.entry > 1. && element.entry < 3.) {
           if (clip_ray_poly(in.aperture_pos, u32(element.b1), element.radius)) {
               return vec4<f32>(0., 0., 0., 0.);
           }
@@ -337,7 +361,7 @@ fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
   if (posParams.entry_rad > 1.){
     strength = in.strength;
   } else {
-    strength = in.strength * pow(eval_grad_zw(vec4<f32>(posParams.init.o.xy, in.entry_pos), u32(2)), 0.5);
+    strength = in.strength * pow(eval_grad_zw(vec4<f32>(posParams.init.o.xy, in.entry_pos), posParams.init.wavelength, u32(2)), 0.5);
   }
 
   if (isNan(strength) || length(in.entry_pos) > posParams.entry_rad) {
@@ -10,8 +10,7 @@ use std::{
 };
 
 use directories::ProjectDirs;
-use itertools::iproduct;
-use polynomial_optics::{Monomial, Polynom4d, Polynomial};
+use polynomial_optics::{DrawRay, LeastSquares, Monomial, Polynomial};
 use wgpu::{
     util::DeviceExt, BindGroup, BindGroupLayout, Buffer, CommandEncoder, ComputePipeline, Device,
     Queue, RenderPipeline, SurfaceConfiguration, TextureFormat, TextureView,
@@ -19,9 +18,15 @@ use wgpu::{
 
 use crate::{lens_state::LensState, texture::Texture};
 
+/// range in µm the wavelengths of the rays the polynomials are fitted to are sampled from
+const VISIBLE_WAVELENGTHS: [f64; 2] = [0.38, 0.78];
+/// bumped when cached polynomials from before can't be used anymore,
+/// 1: x and y of a ghost share their terms
+const CACHE_VERSION: u32 = 1;
+
 #[allow(dead_code)]
 struct GpuPolynomials {
-    polynomials: Vec<Polynomial<f64, 4>>,
+    polynomials: Vec<Polynomial<f64, 5>>,
     pub polynomial_bind_group: BindGroup,
     pub polynomial_bind_group_layout: BindGroupLayout,
 }
@@ -33,9 +38,16 @@ impl GpuPolynomials {
         num_terms: usize,
         degree: usize,
         lens_state: &LensState,
-    ) -> Option<Vec<Polynomial<f64, 4>>> {
+    ) -> Option<Vec<Polynomial<f64, 5>>> {
         let mut hasher = DefaultHasher::new();
-        (num_dots, num_terms, degree, &lens_state.actual_lens).hash(&mut hasher);
+        (
+            CACHE_VERSION,
+            num_dots,
+            num_terms,
+            degree,
+            &lens_state.actual_lens,
+        )
+            .hash(&mut hasher);
         let hash = hasher.finish();
 
         let proj_dirs = ProjectDirs::from("de", "luksab", "polyflare").unwrap();
@@ -70,7 +82,7 @@ impl GpuPolynomials {
                     usize,
                     usize,
                     usize,
-                    Vec<Polynomial<f64, 4>>,
+                    Vec<Polynomial<f64, 5>>,
                 ) = match ron::de::from_str(str.as_str()) {
                     Ok(lens) => lens,
                     Err(_) => return None,
@@ -127,10 +139,17 @@ impl GpuPolynomials {
         num_terms: usize,
         degree: usize,
         lens_state: &LensState,
-        polynomials: &Vec<Polynomial<f64, 4>>,
+        polynomials: &Vec<Polynomial<f64, 5>>,
     ) {
         let mut hasher = DefaultHasher::new();
-        (num_dots, num_terms, degree, &lens_state.actual_lens).hash(&mut hasher);
+        (
+            CACHE_VERSION,
+            num_dots,
+            num_terms,
+            degree,
+            &lens_state.actual_lens,
+        )
+            .hash(&mut hasher);
         let hash = hasher.finish();
 
         let proj_dirs = ProjectDirs::from("de", "luksab", "polyflare").unwrap();
@@ -168,9 +187,8 @@ impl GpuPolynomials {
         num_terms: usize,
         degree: usize,
         lens_state: &LensState,
-    ) -> Vec<Polynomial<f64, 4>> {
+    ) -> Vec<Polynomial<f64, 5>> {
         let num_samples = 7000;
-        let num_iterations = 30000;
 
         let now = Instant::now();
         let plot_output = false;
@@ -197,40 +215,18 @@ impl GpuPolynomials {
             .build()
             .unwrap()
             .install(|| {
-                iproduct!(
-                    (1..lens_state.actual_lens.get_ghosts_indicies(1, 0).len()).into_iter(),
-                    0..=1
-                )
-                .par_bridge()
-                // uncomment for specific ghost
-                // vec![(1usize, 0usize)]
-                //     .into_iter()
-                .map(|(which_ghost, dir_xy)| {
-                    let mut stats = String::new();
-                    stats += format!("{},{}", which_ghost, dir_xy).as_str();
-                    // stats: which_ghost, dir_xy
-                    let width = 1.;
-                    // let dots = &mut lens.get_dots_grid(
-                    //     f64::powf(num_dots as f64, 0.28) as u32,
-                    let dots = &mut lens.get_dots(
-                        num_dots as u32,
-                        // let dots = &mut lens.get_dots(
-                        //     11673,
-                        cgmath::Vector3 {
-                            x: 0.,
-                            y: 0.,
-                            z: pos_params[2] as f64,
-                        },
-                        which_ghost as u32,
-                        pos_params[8] as f64,
-                        [width as f64, width as f64],
-                        true,
-                    );
-                    dbg!(dots.len());
-                    if plot_output && dir_xy == 0 {
-                        // let num_dots = 316;
-                        let dots = &mut lens.get_dots_2dgrid(
-                            (num_dots as f64).powf(0.5) as u32,
+                (1..lens_state.actual_lens.get_ghosts_indicies(1, 0).len())
+                    .into_par_iter()
+                    // uncomment for specific ghost
+                    // (1usize..2)
+                    //     .into_par_iter()
+                    .map(|which_ghost| {
+                        let mut stats = String::new();
+                        stats += format!("{}", which_ghost).as_str();
+                        // stats: which_ghost
+                        let width = 1.;
+                        let (dots, status_counts) = &mut lens.get_dots(
+                            num_dots as u32,
                             cgmath::Vector3 {
                                 x: 0.,
                                 y: 0.,
@@ -239,303 +235,190 @@ impl GpuPolynomials {
                             which_ghost as u32,
                             pos_params[8] as f64,
                             [width as f64, width as f64],
-                            false,
+                            VISIBLE_WAVELENGTHS,
+                            true,
                         );
-                        let mut file = std::fs::OpenOptions::new()
-                            .write(true)
-                            .create(true)
-                            .truncate(true)
-                            .open(format!("python/dots/dots,{}.csv", which_ghost))
-                            .unwrap();
-                        dbg!(dots.len());
-                        // writeln!(file, "x, y, z, w, xo, yo").unwrap();
-                        for dot in dots.iter() {
-                            writeln!(
-                                file,
-                                "{}, {}, {}, {}",
-                                dot.init_pos[2], dot.init_pos[3], dot.pos[0], dot.pos[1]
-                            )
-                            .unwrap();
+                        println!(
+                            "ghost {}: {} dots, ray status counts: {:?}",
+                            which_ghost,
+                            dots.len(),
+                            status_counts
+                        );
+                        if plot_output {
+                            let dots = &mut lens
+                                .get_dots_2dgrid(
+                                    (num_dots as f64).powf(0.5) as u32,
+                                    cgmath::Vector3 {
+                                        x: 0.,
+                                        y: 0.,
+                                        z: pos_params[2] as f64,
+                                    },
+                                    which_ghost as u32,
+                                    pos_params[8] as f64,
+                                    [width as f64, width as f64],
+                                    false,
+                                )
+                                .0;
+                            let mut file = std::fs::OpenOptions::new()
+                                .write(true)
+                                .create(true)
+                                .truncate(true)
+                                .open(format!("python/dots/dots,{}.csv", which_ghost))
+                                .unwrap();
+                            // writeln!(file, "x, y, z, w, xo, yo").unwrap();
+                            for dot in dots.iter() {
+                                writeln!(
+                                    file,
+                                    "{}, {}, {}, {}",
+                                    dot.init_pos[2], dot.init_pos[3], dot.pos[0], dot.pos[1]
+                                )
+                                .unwrap();
+                            }
                         }
-                    }
 
-                    let points = dots
-                        .iter()
-                        .map(|dot| {
+                        // x and y on the sensor of the ray with init_pos and wavelength
+                        let to_point = |dot: &DrawRay| {
                             (
-                                dot.init_pos[0],
-                                dot.init_pos[1],
-                                dot.init_pos[2],
-                                dot.init_pos[3],
-                                // dot.strength,
-                                dot.pos[dir_xy],
+                                [
+                                    dot.init_pos[0],
+                                    dot.init_pos[1],
+                                    dot.init_pos[2],
+                                    dot.init_pos[3],
+                                    dot.wavelength,
+                                ],
+                                dot.pos,
                             )
-                        })
-                        .collect::<Vec<_>>();
-                    dbg!(points.len());
-
-                    let now = Instant::now();
-
-                    let polynom = Polynom4d::<_>::fit(&points, degree);
-                    println!("Fitting took {:?}", now.elapsed());
-                    let now = Instant::now();
-                    stats += format!(",{}", now.elapsed().as_millis()).as_str();
-                    // stats: which_ghost, dir_xy, dense_fit_time
-
-                    let mut sum = 0.;
-                    for point in &points {
-                        let eval = polynom.eval(point.0, point.1, point.2, point.3);
-                        sum += (point.4 - eval).powi(2);
-                        // println!("p: {}, l: {}, q: {}", point.4, eval, point.4 / eval);
-                    }
-                    println!("de error: {}", (sum / points.len() as f64).sqrt());
-                    stats += format!(",{}", (sum / points.len() as f64).sqrt()).as_str();
-                    // stats: which_ghost, dir_xy, dense_fit_time, dense_fit_error
-
-                    let points_rand = &mut lens
-                        .get_dots(
-                            num_dots as u32,
-                            cgmath::Vector3 {
-                                x: 0.,
-                                y: 0.,
-                                z: pos_params[2] as f64,
-                            },
-                            which_ghost as u32,
-                            pos_params[8] as f64,
-                            [width as f64, width as f64],
-                            true,
-                        )
-                        .iter()
-                        .map(|dot| {
-                            (
-                                dot.init_pos[0],
-                                dot.init_pos[1],
-                                dot.init_pos[2],
-                                dot.init_pos[3],
-                                // dot.strength,
-                                dot.pos[dir_xy],
-                            )
-                        })
-                        .collect::<Vec<_>>();
-
-                    let points_grid = &mut lens
-                        .get_dots_grid(
-                            f64::powf(num_dots as f64, 0.3) as u32,
-                            cgmath::Vector3 {
-                                x: 0.,
-                                y: 0.,
-                                z: pos_params[2] as f64,
-                            },
-                            which_ghost as u32,
-                            pos_params[8] as f64,
-                            [width as f64, width as f64],
-                            true,
-                        )
-                        .iter()
-                        .map(|dot| {
-                            (
-                                dot.init_pos[0],
-                                dot.init_pos[1],
-                                dot.init_pos[2],
-                                dot.init_pos[3],
-                                // dot.strength,
-                                dot.pos[dir_xy],
+                        };
+                        let points = dots.iter().map(to_point).collect::<Vec<_>>();
+                        let points_rand = lens
+                            .get_dots(
+                                num_dots as u32,
+                                cgmath::Vector3 {
+                                    x: 0.,
+                                    y: 0.,
+                                    z: pos_params[2] as f64,
+                                },
+                                which_ghost as u32,
+                                pos_params[8] as f64,
+                                [width as f64, width as f64],
+                                VISIBLE_WAVELENGTHS,
+                                true,
                             )
-                        })
-                        .collect::<Vec<_>>();
-                    println!("number of points: {}", points_grid.len());
-
-                    let mut sum_rand = 0.;
-                    for point in points_rand.iter() {
-                        let eval = polynom.eval(point.0, point.1, point.2, point.3);
-                        sum_rand += (point.4 - eval).powi(2);
-                        // println!("p: {}, l: {}, q: {}", point.4, eval, point.4 / eval);
-                    }
-
-                    let mut sum_grid = 0.;
-                    for point in points_grid.iter() {
-                        let eval = polynom.eval(point.0, point.1, point.2, point.3);
-                        sum_grid += (point.4 - eval).powi(2);
-                        // println!("p: {}, l: {}, q: {}", point.4, eval, point.4 / eval);
-                    }
-
-                    println!(
-                        "rand error: {}, grid error: {}",
-                        (sum_rand / points_rand.len() as f64).sqrt(),
-                        (sum_grid / points_grid.len() as f64).sqrt()
-                    );
-                    // panic!("done");
-                    if plot_output {
-                        let mut file = std::fs::OpenOptions::new()
-                            .write(true)
-                            .create(true)
-                            .truncate(true)
-                            .open(format!("python/dots/depoly,{},{}.csv", which_ghost, dir_xy))
-                            .unwrap();
-                        // writeln!(file, "x, y, z, w, o").unwrap();
-                        lens.get_dots_2dgrid(
-                            (num_dots as f64).powf(0.5) as u32,
-                            cgmath::Vector3 {
-                                x: 0.,
-                                y: 0.,
-                                z: pos_params[2] as f64,
-                            },
-                            which_ghost as u32,
-                            pos_params[8] as f64,
-                            [width as f64, width as f64],
-                            false,
-                        )
-                        .iter()
-                        .map(|ray| (ray.init_pos[2], ray.init_pos[3], ray.pos[0].is_finite()))
-                        .for_each(|(z, w, is_finite)| {
-                            let (x, y) = (0., 0.);
-                            if is_finite {
-                                writeln!(file, "{}, {}, {}", z, w, polynom.eval(x, y, z, w))
-                                    .unwrap();
-                            } else {
-                                writeln!(file, "{}, {}, {}", z, w, f32::NAN).unwrap();
+                            .0
+                            .iter()
+                            .map(to_point)
+                            .collect::<Vec<_>>();
+
+                        // x and y share their terms, so the shaders evaluate every monomial once
+                        let now = Instant::now();
+                        let (sparse_polys, errors) = match Polynomial::<f64, 5>::dense(degree)
+                            .get_sparse_somp(
+                                &points[0..usize::min(num_samples, points.len())],
+                                num_terms,
+                                &LeastSquares::default(),
+                            ) {
+                            Ok(fit) => fit,
+                            Err(err) => {
+                                println!("ghost {}: skipped, {}", which_ghost, err);
+                                // polynomials without terms keep the following ghosts at their index
+                                return vec![Polynomial { terms: vec![] }; 2];
                             }
-                        });
-                    }
-
-                    println!("plotting took {:?}", now.elapsed());
-                    let now = Instant::now();
-
-                    if cfg!(debug_assertions) {
-                        println!("{}", polynom);
-                    }
-                    let now = Instant::now();
-                    println!("poly len: {}", polynom.coefficients.len());
-                    // let mut sparse_poly = polynom.get_sparse(
-                    //     &points[0..usize::min(num_samples, points.len())],
-                    //     num_terms,
-                    //     false,
-                    //     false,
-                    // );
-                    // let omp_time = now.elapsed().as_millis();
-                    // println!("sparse_poly took {:?}", now.elapsed());
-                    // let omp_error = sparse_poly.error(&points);
-                    // let now = Instant::now();
-
-                    // let mut sparse_poly = polynom.get_sparse_dumb(num_terms);
-
-                    // return sparse_poly;
-
-                    // println!("sa");
-                    // (0..10).par_bridge().for_each(|_| {
-                    //     let sparse_poly = polynom.simulated_annealing(
-                    //         &points,
-                    //         num_terms,
-                    //         usize::min(num_samples, points.len()), // make sure we don't sample more than we have
-                    //         num_iterations,
-                    //     );
-                    //     println!("{}", sparse_poly.error(&points_rand));
-                    // });
-
-                    println!("full omp");
-                    // (0..3).par_bridge().for_each(|_| {
-                        let sparse_poly = polynom.get_sparse(
-                            &points,
-                            num_terms,
-                            false,
-                            false,
-                        );
-                        println!("{}", sparse_poly.error(&points_rand));
-                    // });
-
-                    // println!("cheap omp");
-                    // (0..10).par_bridge().for_each(|_| {
-                    //     let sparse_poly = polynom.get_sparse(
-                    //         &points,
-                    //         num_terms,
-                    //         true,
-                    //         false,
-                    //     );
-                    //     println!("{}", sparse_poly.error(&points_rand));
-                    // });
-                    panic!("done");
-                    let sparse_poly = polynom.simulated_annealing(
-                        &points,
-                        num_terms,
-                        usize::min(num_samples, points.len()), // make sure we don't sample more than we have
-                        num_iterations,
-                    );
-                    let sa_time = now.elapsed().as_millis();
-                    println!("sa took {:?}", now.elapsed());
-                    // error on random and grid points
-                    let sa_error_grid = sparse_poly.error(&points_grid);
-                    let sa_error_rand = sparse_poly.error(&points_rand);
-                    println!("sa rand: {}, sa grid: {}", sa_error_rand, sa_error_grid);
-                    panic!("done");
-
-                    // println!("omp time: {}, sa time: {}", omp_time, sa_time);
-                    // let sa_error = sparse_poly.error(&points);
-                    // println!("omp error: {}, sa error: {}", omp_error, sa_error);
-                    // panic!("done!");
-
-                    dbg!(sparse_poly.terms.len());
-
-                    sum = 0.;
-                    for point in &points {
-                        let eval = sparse_poly.eval([point.0, point.1, point.2, point.3]);
-                        sum += (point.4 - eval).powi(2);
-                        // println!("p: {}, l: {}, q: {}", point.4, eval, point.4 / eval);
-                    }
-                    println!("sp error: {}", (sum / points.len() as f64).sqrt());
-                    stats += format!(",{}", (sum / points.len() as f64).sqrt()).as_str();
-                    // stats: which_ghost, dir_xy, dense_fit_time, dense_fit_error, sparse_fit_error
-
-                    // gradient descent is just worse than fit
-                    // sparse_poly.gradient_descent(&filtered_points, 100);
-                    println!("actual error: {}", sparse_poly.error(&points));
-                    stats += format!(",{}", sparse_poly.error(&points)).as_str();
-                    // stats: which_ghost, dir_xy, dense_fit_time, dense_fit_error, sparse_fit_error, sparse_fit_error_actual
-
-                    if plot_output {
-                        let mut file = std::fs::OpenOptions::new()
-                            .write(true)
-                            .create(true)
-                            .truncate(true)
-                            .open(format!("python/dots/poly,{},{}.csv", which_ghost, dir_xy))
-                            .unwrap();
-                        // writeln!(file, "x, y, z, w, o").unwrap();
-                        lens.get_dots_2dgrid(
-                            (num_dots as f64).powf(0.5) as u32,
-                            cgmath::Vector3 {
-                                x: 0.,
-                                y: 0.,
-                                z: pos_params[2] as f64,
-                            },
-                            which_ghost as u32,
-                            pos_params[8] as f64,
-                            [width as f64, width as f64],
-                            false,
-                        )
-                        .iter()
-                        .map(|ray| (ray.init_pos[2], ray.init_pos[3], ray.pos[0].is_finite()))
-                        .for_each(|(z, w, is_finite)| {
-                            let (x, y) = (0., 0.);
-                            if is_finite {
-                                writeln!(file, "{}, {}, {}", z, w, sparse_poly.eval([x, y, z, w]))
+                        };
+                        println!("somp took {:?}", now.elapsed());
+                        stats += format!(",{}", now.elapsed().as_millis()).as_str();
+                        // stats: which_ghost, fit_time
+
+                        // error after each added term, to pick num_terms
+                        println!("error per term count: {:?}", errors);
+                        for error in &errors {
+                            stats += format!(",{}", error).as_str();
+                        }
+                        // stats: which_ghost, fit_time, error with 1 term, error with 2 terms, ...
+
+                        for (dir_xy, sparse_poly) in sparse_polys.iter().enumerate() {
+                            let error = |points: &[([f64; 5], [f64; 2])]| {
+                                (points
+                                    .iter()
+                                    .map(|(input, output)| {
+                                        (output[dir_xy] - sparse_poly.eval(*input)).powi(2)
+                                    })
+                                    .sum::<f64>()
+                                    / points.len() as f64)
+                                    .sqrt()
+                            };
+                            println!(
+                                "ghost {}, dir {}: sp error: {}, sp rand error: {}",
+                                which_ghost,
+                                dir_xy,
+                                error(&points),
+                                error(&points_rand)
+                            );
+
+                            if plot_output {
+                                let mut file = std::fs::OpenOptions::new()
+                                    .write(true)
+                                    .create(true)
+                                    .truncate(true)
+                                    .open(format!(
+                                        "python/dots/poly,{},{}.csv",
+                                        which_ghost, dir_xy
+                                    ))
                                     .unwrap();
-                            } else {
-                                writeln!(file, "{}, {}, {}", z, w, f32::NAN).unwrap();
+                                // writeln!(file, "x, y, z, w, o").unwrap();
+                                lens.get_dots_2dgrid(
+                                    (num_dots as f64).powf(0.5) as u32,
+                                    cgmath::Vector3 {
+                                        x: 0.,
+                                        y: 0.,
+                                        z: pos_params[2] as f64,
+                                    },
+                                    which_ghost as u32,
+                                    pos_params[8] as f64,
+                                    [width as f64, width as f64],
+                                    false,
+                                )
+                                .0
+                                .iter()
+                                .map(|ray| {
+                                    (
+                                        ray.init_pos[2],
+                                        ray.init_pos[3],
+                                        ray.wavelength,
+                                        ray.pos[0].is_finite(),
+                                    )
+                                })
+                                .for_each(
+                                    |(z, w, wavelength, is_finite)| {
+                                        let (x, y) = (0., 0.);
+                                        if is_finite {
+                                            writeln!(
+                                                file,
+                                                "{}, {}, {}",
+                                                z,
+                                                w,
+                                                sparse_poly.eval([x, y, z, w, wavelength])
+                                            )
+                                            .unwrap();
+                                        } else {
+                                            writeln!(file, "{}, {}, {}", z, w, f32::NAN).unwrap();
+                                        }
+                                    },
+                                );
                             }
-                        });
-                    }
+                        }
 
-                    stats += "\n";
+                        stats += "\n";
 
-                    stats_file
-                        .lock()
-                        .unwrap()
-                        .write_all(stats.as_bytes())
-                        .unwrap();
+                        stats_file
+                            .lock()
+                            .unwrap()
+                            .write_all(stats.as_bytes())
+                            .unwrap();
 
-                    // panic!("{}", now.elapsed().as_millis());
-                    sparse_poly
-                })
-                .collect::<Vec<_>>()
+                        sparse_polys
+                    })
+                    .flatten()
+                    .collect::<Vec<_>>()
             });
 
         println!("Computing polynomials took {:?}", now.elapsed());
@@ -548,7 +431,7 @@ impl GpuPolynomials {
         num_terms: usize,
         degree: usize,
         lens_state: &LensState,
-    ) -> Vec<Polynomial<f64, 4>> {
+    ) -> Vec<Polynomial<f64, 5>> {
         match Self::check_cache(num_dots, num_terms, degree, lens_state) {
             Some(polynomials) => polynomials,
             None => {
@@ -598,7 +481,7 @@ impl GpuPolynomials {
 
         let poly_data: Vec<f32> = polynomials
             .iter()
-            .flat_map(|polynomial: &Polynomial<f64, 4>| polynomial.get_T_as_vec(num_terms))
+            .flat_map(|polynomial: &Polynomial<f64, 5>| polynomial.get_T_as_vec(num_terms))
             .collect();
 
         // println!("poly_data: {:?}", poly_data);
@@ -1517,6 +1400,7 @@ impl PolyPoly {
                     .into(),
             );
 
+            lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
             self.convert(device, &mut encoder, view, lens_state);
 
             queue.submit(Some(encoder.finish()));
@@ -1545,6 +1429,7 @@ impl PolyPoly {
                 panic!("Failed to copy ray buffer!")
             }
         } else {
+            lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
             self.convert(device, &mut encoder, view, lens_state);
             queue.submit(iter::once(encoder.finish()));
         }
@@ -1603,6 +1488,7 @@ impl PolyPoly {
             label: Some("Render Encoder"),
         });
 
+        lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
         self.convert(device, &mut encoder, view, lens_state);
         queue.submit(iter::once(encoder.finish()));
 
@@ -1624,6 +1510,7 @@ impl PolyPoly {
 
         self.render_dots(&self.high_color_tex.view, &mut encoder, lens_state, true, 1);
 
+        lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
         self.convert(device, &mut encoder, view, lens_state);
 
         queue.submit(iter::once(encoder.finish()));
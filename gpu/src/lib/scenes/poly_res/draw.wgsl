@@ -42,9 +42,11 @@ struct Sensor {
 [[group(0), binding(1)]] var<storage, read> sensor : Sensor;
 
 
+/// response of the sensor at wavelength in µm, the LUT has a sample every 10nm
 fn lookup_rgb(wavelength: f32) -> vec3<f32> {
-    let lower_index = u32(clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 34.));
-    let factor = (wavelength % 0.1) * 10.;
+    let x = clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 40.);
+    let lower_index = min(u32(x), u32(39));
+    let factor = x - f32(lower_index);
     return sensor.measuremens[lower_index].rgb * (1. - factor)
      + sensor.measuremens[lower_index + u32(1)].rgb * (factor);
 }
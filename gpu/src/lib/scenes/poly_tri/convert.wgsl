@@ -25,6 +25,12 @@ struct SimParams {
 
 [[group(1), binding(2)]] var<uniform> params : SimParams;
 
+// matrix from the response the draw shaders weight the rays with to the output colour space
+struct ColourParams {
+  to_output: mat3x3<f32>;
+};
+[[group(1), binding(3)]] var<uniform> colour : ColourParams;
+
 [[stage(vertex)]]
 fn mainv(
     in: VertexInput,
@@ -45,7 +51,9 @@ fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     let h = params.window_height_scaled;
     let ratio = max(w,h);
     let pos = vec2<f32>(in.clip_position.x - w/2., in.clip_position.y - h/2.) / ratio + 0.5;
-    let sample = textureSample(t_diffuse, s_diffuse, pos);
+    let response = textureSample(t_diffuse, s_diffuse, pos);
+    // colours outside of the output colour space are clipped
+    let sample = vec4<f32>(max(colour.to_output * response.rgb, vec3<f32>(0.)), response.a);
     var bg = vec4<f32>(0.0,0.0,0.0,1.0);
 
     let color = bg * (1.0 - sample.a) + sample;
@@ -0,0 +1,87 @@
+struct StarburstParams {
+  center: vec2<f32>;
+  half_size: f32;
+  intensity: f32;
+  size: f32;
+  reference: f32;
+  longest: f32;
+  padding: f32;
+};
+
+struct Pattern {
+  values: [[stride(4)]] array<f32>;
+};
+
+struct SensorDatapoint {
+    rgb: vec3<f32>;
+    wavelength: f32;
+};
+
+struct Sensor {
+    measuremens: [[stride(16)]] array<SensorDatapoint>;
+};
+
+struct LightSpectrum {
+  samples: [[stride(4)]] array<f32, 81>;
+};
+
+[[group(0), binding(0)]] var<storage, read> pattern : Pattern;
+[[group(0), binding(1)]] var output : texture_storage_2d<rgba16float, write>;
+[[group(0), binding(2)]] var<uniform> starburst : StarburstParams;
+[[group(1), binding(1)]] var<storage, read> sensor : Sensor;
+[[group(2), binding(5)]] var<storage, read> light : LightSpectrum;
+
+/// response of the sensor at wavelength in µm, the LUT has a sample every 10nm
+fn lookup_rgb(wavelength: f32) -> vec3<f32> {
+    let x = clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 40.);
+    let lower_index = min(u32(x), u32(39));
+    let factor = x - f32(lower_index);
+    return sensor.measuremens[lower_index].rgb * (1. - factor)
+     + sensor.measuremens[lower_index + u32(1)].rgb * (factor);
+}
+
+/// relative power of the light source at wavelength in µm, the LUT has a sample every 5nm
+fn light_power(wavelen: f32) -> f32 {
+    let x = clamp((wavelen - 0.38) / 0.005, 0., 80.);
+    let i = min(u32(x), u32(79));
+    let t = x - f32(i);
+    return light.samples[i] * (1. - t) + light.samples[i + u32(1)] * t;
+}
+
+/// bilinear lookup of the pattern at pos in bins, 0 outside of it
+fn pattern_at(pos: vec2<f32>) -> f32 {
+    let size = u32(starburst.size);
+    let max_pos = starburst.size - 1.;
+    if (pos.x < 0. || pos.y < 0. || pos.x > max_pos || pos.y > max_pos) {
+        return 0.;
+    }
+    let x = min(u32(pos.x), size - u32(2));
+    let y = min(u32(pos.y), size - u32(2));
+    let t = pos - vec2<f32>(f32(x), f32(y));
+    let top = pattern.values[y * size + x] * (1. - t.x)
+        + pattern.values[y * size + x + u32(1)] * t.x;
+    let bottom = pattern.values[(y + u32(1)) * size + x] * (1. - t.x)
+        + pattern.values[(y + u32(1)) * size + x + u32(1)] * t.x;
+    return top * (1. - t.y) + bottom * t.y;
+}
+
+/// one texel of the spectral starburst, a texel is one bin of the longest wavelength
+[[stage(compute), workgroup_size(8, 8)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    let size = u32(starburst.size);
+    if (id.x >= size || id.y >= size) {
+        return;
+    }
+    let half = starburst.size / 2.;
+    let offset = vec2<f32>(f32(id.x), f32(id.y)) + 0.5 - half;
+
+    var rgb = vec3<f32>(0.);
+    for (var i = u32(0); i < u32(41); i = i + u32(1)) {
+        let wavelength = 0.38 + f32(i) * 0.01;
+        // the pattern grows with the wavelength and keeps its energy
+        let scale = starburst.reference / wavelength;
+        let intensity = pattern_at(offset * starburst.longest / wavelength + half) * scale * scale;
+        rgb = rgb + intensity * light_power(wavelength) * lookup_rgb(wavelength);
+    }
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(rgb * starburst.intensity / 41., 1.));
+}
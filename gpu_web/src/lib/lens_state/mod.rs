@@ -4,13 +4,20 @@ use std::path::Path;
 use instant::Instant;
 
 use cgmath::{InnerSpace, Vector3};
-use polynomial_optics::{Element, Glass, Lens, Properties, QuarterWaveCoating, Sellmeier};
+use polynomial_optics::{
+    Asphere, CoatingStack, ColourPipeline, ColourSpace, Element, Filter, GhostCulling, GhostPath,
+    GhostRank, Glass, Lens, LightSource, Properties, QuarterWaveCoating, Sellmeier, SensorResponse,
+    SensorStack, Spectrum, CUSTOM_BLACKBODY, CUSTOM_DAYLIGHT,
+};
 use wgpu::util::DeviceExt;
 use wgpu::{Buffer, Device, Queue};
 
-mod sensor;
+use crate::starburst::GpuStarburst;
 
-use sensor::*;
+/// glass catalogs are in mm, the GUI works in cm
+const PRESCRIPTION_SCALE: f64 = 0.1;
+/// rays along each side of the grid traced per ghost by `Lens::rank_ghosts`
+const GHOST_RANK_SIDE_LEN: u32 = 8;
 
 /// The representation of a piece of glass in the GUI
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -28,6 +35,15 @@ pub struct GlassElement {
     sellmeier_index: usize,
     coating_optimal: f32,
     coating_enable: bool,
+    /// multi-layer coating used instead of the optimal quarter-wave one
+    #[serde(default)]
+    coating_stack: Option<CoatingStack>,
+    /// radius of the clear aperture, 0 for no limit
+    #[serde(default)]
+    semi_diameter: f32,
+    /// conic constant and aspheric coefficients of the surface with radius `r`
+    #[serde(default)]
+    asphere: Asphere,
 }
 
 /// The representation of an aperture in the GUI
@@ -41,11 +57,30 @@ pub struct Aperture {
     num_blades: u32,
 }
 
+/// The representation of a filter plate in the GUI
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct FilterElement {
+    /// distance in front of the filter
+    d: f32,
+    thickness: f32,
+    sellmeier: Sellmeier,
+    /// index into `LensState.all_glasses`
+    sellmeier_index: usize,
+    transmittance: Spectrum,
+    /// CSV file the transmittance was loaded from
+    #[serde(default)]
+    transmittance_file: String,
+    /// radius of the clear aperture, 0 for no limit
+    #[serde(default)]
+    semi_diameter: f32,
+}
+
 /// One Part of a Lens in the GUI
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub enum ElementState {
     Lens(GlassElement),
     Aperture(Aperture),
+    Filter(FilterElement),
 }
 
 /// Dense = 0, Sparse = 1 or Poly = 2
@@ -102,14 +137,47 @@ pub struct LensState {
     /// index into all_sensors
     sensor_index: usize,
     /// all sensor representations
-    all_sensors: Vec<(String, Sensor)>,
-    /// buffer of the current sensor
+    all_sensors: Vec<(String, SensorResponse)>,
+    /// weight the rays with the CIE 1931 observer instead of the current sensor
+    pub cie_observer: bool,
+    /// LUT of the response the rays are weighted with, see `ColourPipeline::get_lut`
     pub sensor_buffer: Buffer,
+    /// index into all_colour_spaces
+    colour_space_index: usize,
+    /// all colour spaces the flare can be output in
+    all_colour_spaces: Vec<(String, ColourSpace)>,
+    /// matrix from the response to the output colour space for the convert shaders
+    colour_buffer: Buffer,
+    /// spectrum of the light causing the flare
+    pub light_source: LightSource,
+    /// index into all_light_sources
+    light_index: usize,
+    /// all spectra of light sources
+    all_light_sources: Vec<(String, LightSource)>,
+    /// LUT of the current light source, see `LightSource::get_lut`
+    light_buffer: Buffer,
     /// positions of the rays and the sensor
     pub pos_params_buffer: wgpu::Buffer,
-    /// buffer for which ghost to draw
+    /// the ghosts to draw
+    pub ghost_paths: Vec<GhostPath>,
+    /// `[start, num]` of each ghost to draw in `ghost_surfaces_buffer`, see `GhostPath::get_buffers`
     pub ghost_indices: Vec<[u32; 2]>,
     pub ghost_indices_buffer: wgpu::Buffer,
+    /// the reflecting surfaces of all drawn ghosts after each other
+    pub ghost_surfaces_buffer: wgpu::Buffer,
+    /// most reflections along a ghost path
+    pub max_bounces: u32,
+    /// add the cover glass and the sensor as reflecting surfaces
+    pub sensor_reflections: bool,
+    pub sensor_stack: SensorStack,
+    /// index into `LensState.all_glasses` of the cover glass
+    #[allow(dead_code)]
+    cover_glass_index: usize,
+    /// ghosts of the current lens, the brightest first
+    pub ghost_ranking: Vec<GhostRank>,
+    /// only draw the ghosts kept by `ghost_culling` instead of all of them
+    pub cull_ghosts: bool,
+    pub ghost_culling: GhostCulling,
     /// positions of the rays and the sensor
     pub params_bind_group: wgpu::BindGroup,
     /// positions of the rays and the sensor
@@ -128,7 +196,7 @@ pub struct LensState {
     /// 8: sensor: f32;
     /// 9: width: f32;
     ///10: entry_radius: f32;
-    /// padding
+    ///11: s_polarized: f32;
     /// };
     /// ```
     pub pos_params: [f32; 12],
@@ -156,10 +224,14 @@ pub struct LensState {
     lens_buffer: Buffer,
     /// buffer for the currently selected lens for raytracing
     lens_rt_buffer: Buffer,
+    /// buffer for the coating layers of the currently selected lens
+    coatings_buffer: Buffer,
     /// bind group for both representations of the current lens
     pub lens_bind_group: wgpu::BindGroup,
     /// bind group layout for both representations of the current lens
     pub lens_bind_group_layout: wgpu::BindGroupLayout,
+    /// diffraction spikes of the aperture around the light
+    pub starburst: GpuStarburst,
 
     // pub polys: Vec<Box< dyn PolyStore<f32>>>,
     /// buffer containing the sparse polynomial
@@ -184,6 +256,9 @@ impl LensState {
                 sellmeier_index: 0,
                 coating_optimal: 0.5,
                 coating_enable: false,
+                coating_stack: None,
+                semi_diameter: 0.,
+                asphere: Asphere::sphere(),
             }),
             ElementState::Lens(GlassElement {
                 d: 1.5,
@@ -194,6 +269,9 @@ impl LensState {
                 sellmeier_index: 0,
                 coating_optimal: 0.5,
                 coating_enable: false,
+                coating_stack: None,
+                semi_diameter: 0.,
+                asphere: Asphere::sphere(),
             }),
             ElementState::Aperture(Aperture {
                 d: 1.5,
@@ -209,6 +287,9 @@ impl LensState {
                 sellmeier_index: 0,
                 coating_optimal: 0.5,
                 coating_enable: false,
+                coating_stack: None,
+                semi_diameter: 0.,
+                asphere: Asphere::sphere(),
             }),
             ElementState::Lens(GlassElement {
                 d: 1.5,
@@ -219,6 +300,9 @@ impl LensState {
                 sellmeier_index: 0,
                 coating_optimal: 0.5,
                 coating_enable: false,
+                coating_stack: None,
+                semi_diameter: 0.,
+                asphere: Asphere::sphere(),
             }),
         ];
         let sensor_dist = 3.;
@@ -233,6 +317,13 @@ impl LensState {
                 | wgpu::BufferUsages::STORAGE,
         });
 
+        let coatings_data = actual_lens.get_coatings_buffer();
+        let coatings_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lens coatings Buffer"),
+            contents: bytemuck::cast_slice(&coatings_data),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        });
+
         let lens_data = actual_lens.get_elements_buffer();
         let lens_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Lens drawing Buffer"),
@@ -242,9 +333,10 @@ impl LensState {
                 | wgpu::BufferUsages::STORAGE,
         });
 
-        let sensors = Sensor::get_all_sensors();
+        let sensors = SensorResponse::get_all_sensors();
         let sensor_index = 0;
-        let sensor_data = sensors[sensor_index].1.get_data();
+        let colour = ColourPipeline::default();
+        let sensor_data = colour.get_lut();
 
         let sensor_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("sensor info Buffer"),
@@ -254,7 +346,26 @@ impl LensState {
                 | wgpu::BufferUsages::STORAGE,
         });
 
-        let ghost_indices = vec![[0, 1]];
+        let all_colour_spaces = ColourSpace::get_all_colour_spaces();
+        let colour_space_index = 0;
+        let colour_data = colour.get_matrix_buffer();
+        let colour_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Colour Matrix Buffer"),
+            contents: bytemuck::cast_slice(&colour_data),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let all_light_sources = LightSource::get_all_light_sources();
+        let light_index = 0;
+        let light_source = all_light_sources[light_index].1.clone();
+        let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Spectrum Buffer"),
+            contents: bytemuck::cast_slice(&light_source.get_lut()),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        });
+
+        let ghost_paths = vec![GhostPath::two_bounce(0, 1).unwrap()];
+        let (ghost_indices, ghost_surfaces) = GhostPath::get_buffers(&ghost_paths);
         let ghost_indices_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Ghost Indices Buffer"),
             contents: bytemuck::cast_slice(&ghost_indices),
@@ -262,6 +373,11 @@ impl LensState {
                 | wgpu::BufferUsages::COPY_DST
                 | wgpu::BufferUsages::STORAGE,
         });
+        let ghost_surfaces_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ghost Surfaces Buffer"),
+            contents: bytemuck::cast_slice(&ghost_surfaces),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        });
 
         let lens_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -298,6 +414,36 @@ impl LensState {
                         },
                         count: None,
                     },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 5,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
                 label: None,
             });
@@ -317,10 +463,29 @@ impl LensState {
                     binding: 2,
                     resource: ghost_indices_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: coatings_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: ghost_surfaces_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: light_buffer.as_entire_binding(),
+                },
             ],
             label: None,
         });
 
+        let all_glasses = Self::gui_glasses(&Sellmeier::get_all_glasses());
+        let sensor_stack = SensorStack::default();
+        let cover_glass_index = all_glasses
+            .iter()
+            .position(|(_name, glass)| *glass == sensor_stack.cover_glass)
+            .unwrap_or(0);
+
         let center_pos = Vector3 {
             x: 0.0,
             y: 0.0,
@@ -344,7 +509,7 @@ impl LensState {
             7.,
             0.5,
             1.,
-            0., // Padding
+            0.5, // unpolarized
         ];
         let pos_params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Simulation Parameter Buffer"),
@@ -411,6 +576,18 @@ impl LensState {
                         },
                         count: None,
                     },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                (colour_data.len() * std::mem::size_of::<f32>()) as _,
+                            ),
+                        },
+                        count: None,
+                    },
                 ],
                 label: None,
             });
@@ -429,10 +606,17 @@ impl LensState {
                     binding: 2,
                     resource: sim_param_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: colour_buffer.as_entire_binding(),
+                },
             ],
             label: None,
         });
 
+        let starburst =
+            GpuStarburst::new(device, &params_bind_group_layout, &lens_bind_group_layout);
+
         Self {
             needs_update: false,
             ray_exponent: 5.,
@@ -451,11 +635,20 @@ impl LensState {
             actual_lens,
             selected_lens: 0,
             current_filename: String::new(),
-            all_glasses: Sellmeier::get_all_glasses(),
+            all_glasses,
             all_sensors: sensors,
             sensor_index,
+            cie_observer: true,
             sensor_buffer,
+            colour_space_index,
+            all_colour_spaces,
+            colour_buffer,
+            light_source,
+            light_index,
+            all_light_sources,
+            light_buffer,
             lens_rt_buffer,
+            coatings_buffer,
             lens_buffer,
             pos_params_buffer,
             params_bind_group,
@@ -463,8 +656,18 @@ impl LensState {
             params_bind_group_layout,
             lens_bind_group,
             lens_bind_group_layout,
+            starburst,
+            ghost_paths,
             ghost_indices,
             ghost_indices_buffer,
+            ghost_surfaces_buffer,
+            max_bounces: 2,
+            sensor_reflections: false,
+            sensor_stack,
+            cover_glass_index,
+            ghost_ranking: vec![],
+            cull_ghosts: false,
+            ghost_culling: GhostCulling::default(),
             first_frame: true,
             sim_param_buffer,
             sim_params,
@@ -489,6 +692,14 @@ impl LensState {
         self.needs_update = true;
     }
 
+    /// `glasses` with their lengths in the units of the GUI
+    fn gui_glasses(glasses: &[(String, Sellmeier)]) -> Vec<(String, Sellmeier)> {
+        glasses
+            .iter()
+            .map(|(name, glass)| (name.clone(), glass.scaled(PRESCRIPTION_SCALE)))
+            .collect()
+    }
+
     /// Convert from the GUI representation to the `polynomial_optics` representation
     fn get_lens_arr(lenses: &[ElementState]) -> Vec<polynomial_optics::Element> {
         let mut elements: Vec<Element> = vec![];
@@ -515,7 +726,7 @@ impl LensState {
                                     Sellmeier::air()
                                 }
                             }
-                            ElementState::Aperture(_) => Sellmeier::air(),
+                            ElementState::Aperture(_) | ElementState::Filter(_) => Sellmeier::air(),
                         }
                     } else {
                         Sellmeier::air()
@@ -524,18 +735,22 @@ impl LensState {
                         radius: lens.r as f64,
                         properties: Properties::Glass(Glass {
                             sellmeier: lens.sellmeier,
-                            coating: if lens.coating_enable {
-                                QuarterWaveCoating::optimal(
+                            coating: match (lens.coating_enable, lens.coating_stack) {
+                                (true, Some(stack)) => stack,
+                                (true, None) => QuarterWaveCoating::optimal(
                                     lens.sellmeier.ior(lens.coating_optimal as f64),
                                     1.0,
                                     lens.coating_optimal as f64,
                                 )
-                            } else {
-                                QuarterWaveCoating::none()
+                                .into(),
+                                (false, _) => CoatingStack::none(),
                             },
                             entry,
                             outer_ior,
                             spherical: lens.spherical,
+                            asphere: lens.asphere,
+                            semi_diameter: Some(lens.semi_diameter as f64)
+                                .filter(|semi_diameter| *semi_diameter > 0.),
                         }),
                         position: dst as f64,
                     });
@@ -549,13 +764,29 @@ impl LensState {
                         position: dst as f64,
                     });
                 }
+                ElementState::Filter(filter) => {
+                    dst += filter.d;
+                    elements.extend(Filter::plate(
+                        filter.sellmeier,
+                        filter.transmittance,
+                        dst as f64,
+                        filter.thickness as f64,
+                        Some(filter.semi_diameter as f64)
+                            .filter(|semi_diameter| *semi_diameter > 0.),
+                    ));
+                    dst += filter.thickness;
+                }
             }
         }
         elements
     }
     /// get the `polynomial_optics` representation of the Lens
     pub fn get_lens(&self) -> Lens {
-        Lens::new(Self::get_lens_arr(&self.lens), self.actual_lens.sensor_dist)
+        let mut lens = Lens::new(Self::get_lens_arr(&self.lens), self.actual_lens.sensor_dist);
+        lens.set_sensor_stack(Some(self.sensor_stack).filter(|_| self.sensor_reflections));
+        lens.light_source = self.light_source.clone();
+        lens.colour = self.get_colour();
+        lens
     }
 
     /// Convert from the `polynomial_optics` representation to the GUI representation
@@ -564,23 +795,37 @@ impl LensState {
         let mut elements = vec![];
         let mut last_pos = -5.;
         let mut expect_entry = true;
-        let mut enty = (0., 0.);
+        let mut enty = (0., 0., 0., Asphere::sphere());
+        let mut filter_front = 0.;
 
-        for element in &self.actual_lens.elements {
+        for element in self.actual_lens.lens_elements() {
             match element.properties {
                 Properties::Glass(glass) => {
                     if expect_entry && glass.entry {
-                        enty = (element.position as f32 - last_pos, element.radius as f32);
+                        enty = (
+                            element.position as f32 - last_pos,
+                            element.radius as f32,
+                            glass.semi_diameter.unwrap_or(0.) as f32,
+                            glass.asphere,
+                        );
                         expect_entry = false;
                     } else if !expect_entry && !glass.entry {
-                        let mut sellmeier_index = 0;
-                        for (index, (_name, other_glass)) in self.all_glasses.iter().enumerate() {
-                            if glass.sellmeier == *other_glass {
-                                sellmeier_index = index;
-                            }
-                        }
+                        let sellmeier_index = self
+                            .all_glasses
+                            .iter()
+                            .position(|(_name, other_glass)| glass.sellmeier == *other_glass)
+                            // glasses made up from nd and Vd show the closest catalog glass
+                            .or_else(|| {
+                                Sellmeier::nearest_glass(
+                                    glass.sellmeier.nd(),
+                                    glass.sellmeier.abbe_number(),
+                                    &self.all_glasses,
+                                )
+                            })
+                            .unwrap_or(0);
 
-                        let coating_enable = glass.coating.thickness > 0.;
+                        let coating_enable = !glass.coating.is_empty();
+                        let coating_stack = Some(glass.coating).filter(|_| coating_enable);
                         elements.push(ElementState::Lens(GlassElement {
                             d: enty.0,
                             r: enty.1,
@@ -590,6 +835,9 @@ impl LensState {
                             sellmeier_index,
                             coating_optimal: 0.5, // TODO: read from file somehow
                             coating_enable,
+                            coating_stack,
+                            semi_diameter: enty.2,
+                            asphere: enty.3,
                         }));
                         elements.push(ElementState::Lens(GlassElement {
                             d: element.position as f32 - last_pos,
@@ -600,6 +848,9 @@ impl LensState {
                             sellmeier_index,
                             coating_optimal: 0.5, // TODO: read from file somehow
                             coating_enable,
+                            coating_stack,
+                            semi_diameter: glass.semi_diameter.unwrap_or(0.) as f32,
+                            asphere: glass.asphere,
                         }));
                         expect_entry = true;
                     } else {
@@ -613,6 +864,27 @@ impl LensState {
                         num_blades,
                     }))
                 }
+                Properties::Filter(filter) => {
+                    if filter.entry {
+                        filter_front = element.position as f32 - last_pos;
+                    } else {
+                        let sellmeier_index = self
+                            .all_glasses
+                            .iter()
+                            .position(|(_name, other_glass)| filter.material == *other_glass)
+                            .unwrap_or(0);
+                        elements.push(ElementState::Filter(FilterElement {
+                            d: filter_front,
+                            thickness: element.position as f32 - last_pos,
+                            sellmeier: filter.material,
+                            sellmeier_index,
+                            transmittance: filter.transmittance,
+                            transmittance_file: String::new(),
+                            semi_diameter: filter.semi_diameter.unwrap_or(0.) as f32,
+                        }));
+                    }
+                }
+                Properties::Sensor(_) => (),
             };
             last_pos = element.position as f32;
         }
@@ -637,6 +909,13 @@ impl LensState {
                 | wgpu::BufferUsages::STORAGE,
         });
 
+        let coatings_data = self.actual_lens.get_coatings_buffer();
+        self.coatings_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lens coatings Buffer"),
+            contents: bytemuck::cast_slice(&coatings_data),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        });
+
         let lens_data = self.actual_lens.get_elements_buffer();
         self.lens_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Lens drawing Buffer"),
@@ -646,9 +925,24 @@ impl LensState {
                 | wgpu::BufferUsages::STORAGE,
         });
 
-        self.ghost_indices = self
-            .actual_lens
-            .get_ghosts_indicies(self.draw as _, self.which_ghost as _);
+        if self.cull_ghosts {
+            self.rank_ghosts();
+        }
+        self.ghost_paths = if self.cull_ghosts && self.which_ghost == 0 && self.draw & 1 > 0 {
+            let mut ghost_paths = self.ghost_culling.cull(&self.ghost_ranking);
+            if self.draw & 2 > 0 {
+                ghost_paths.push(GhostPath::direct());
+            }
+            ghost_paths
+        } else {
+            self.actual_lens.get_ghost_paths(
+                self.draw as _,
+                self.which_ghost as _,
+                self.max_bounces as _,
+            )
+        };
+        let (ghost_indices, ghost_surfaces) = GhostPath::get_buffers(&self.ghost_paths);
+        self.ghost_indices = ghost_indices;
 
         self.ghost_indices_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Ghost Indices Buffer"),
@@ -657,6 +951,11 @@ impl LensState {
                 | wgpu::BufferUsages::COPY_DST
                 | wgpu::BufferUsages::STORAGE,
         });
+        self.ghost_surfaces_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ghost Surfaces Buffer"),
+            contents: bytemuck::cast_slice(&ghost_surfaces),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        });
 
         self.lens_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &self.lens_bind_group_layout,
@@ -673,6 +972,18 @@ impl LensState {
                     binding: 2,
                     resource: self.ghost_indices_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.coatings_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: self.ghost_surfaces_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: self.light_buffer.as_entire_binding(),
+                },
             ],
             label: None,
         });
@@ -683,11 +994,39 @@ impl LensState {
             0,
             bytemuck::cast_slice(&self.pos_params),
         );
+        queue.write_buffer(
+            &self.light_buffer,
+            0,
+            bytemuck::cast_slice(&self.light_source.get_lut()),
+        );
         queue.write_buffer(
             &self.sim_param_buffer,
             0,
             bytemuck::cast_slice(&self.sim_params),
         );
+
+        let direction = Vector3::new(
+            self.pos_params[4] as f64,
+            self.pos_params[5] as f64,
+            self.pos_params[6] as f64,
+        );
+        self.starburst.update(
+            device,
+            queue,
+            &self.actual_lens,
+            direction,
+            self.sim_params[12],
+        );
+    }
+
+    /// add the starburst to the flare in `view` if it is enabled
+    pub fn render_starburst(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        self.starburst.render(
+            encoder,
+            view,
+            &self.params_bind_group,
+            &self.lens_bind_group,
+        );
     }
 
     /// read the available lens descriptions from ~/.config/polyflare/lenses/
@@ -788,27 +1127,57 @@ impl LensState {
                     update_lens = true;
                 }
 
+                let mut update_colour = ui.checkbox("CIE 1931 observer", &mut self.cie_observer);
+                if !self.cie_observer {
+                    update_colour |= ui.combo(
+                        "select sensor",
+                        &mut self.sensor_index,
+                        self.all_sensors.as_slice(),
+                        |(label, _sensor)| std::borrow::Cow::Borrowed(label),
+                    );
+                }
+                update_colour |= ui.combo(
+                    "colour space",
+                    &mut self.colour_space_index,
+                    self.all_colour_spaces.as_slice(),
+                    |(label, _colour_space)| std::borrow::Cow::Borrowed(label),
+                );
+                if update_colour {
+                    self.write_colour(queue);
+                }
+
                 if ui.combo(
-                    "select sensor",
-                    &mut self.sensor_index,
-                    self.all_sensors.as_slice(),
-                    |(label, _lens)| std::borrow::Cow::Borrowed(label),
+                    "select light",
+                    &mut self.light_index,
+                    self.all_light_sources.as_slice(),
+                    |(label, _light)| std::borrow::Cow::Borrowed(label),
                 ) {
-                    queue.write_buffer(
-                        &self.sensor_buffer,
-                        0,
-                        bytemuck::cast_slice(&self.all_sensors[self.sensor_index].1.get_data()),
-                    );
+                    self.light_source = self.all_light_sources[self.light_index].1.clone();
+                    update_lens = true;
+                }
+                // the CIE illuminants keep their temperature
+                let name = self.all_light_sources[self.light_index].0.as_str();
+                if let LightSource::Blackbody(temperature) | LightSource::Daylight(temperature) =
+                    &mut self.light_source
+                {
+                    if name == CUSTOM_BLACKBODY || name == CUSTOM_DAYLIGHT {
+                        update_lens |= Drag::new("temperature")
+                            .range(1000., 25000.)
+                            .speed(10.)
+                            .build(ui, temperature);
+                    }
                 }
 
                 let mut delete_glass = None;
                 let mut delete_aperture = None;
+                let mut delete_filter = None;
 
                 let mut next_sellmeier = None;
                 // how many elements to draw within the current header
                 let mut ui_draw_next = 0;
                 let mut element_counter = 0;
                 let mut aperture_counter = 0;
+                let mut filter_counter = 0;
                 for (i, element) in self.lens.iter_mut().enumerate() {
                     match element {
                         ElementState::Lens(lens) => {
@@ -833,6 +1202,32 @@ impl LensState {
                                         ));
                                         update_lens = true;
                                     }
+
+                                    // patents often only give nd and Vd of a glass
+                                    let mut nd = lens.sellmeier.nd() as f32;
+                                    let mut vd = lens.sellmeier.abbe_number() as f32;
+                                    let mut update_abbe = Drag::new(format!("nd##{}", i))
+                                        .range(1., 2.5)
+                                        .speed(0.001)
+                                        .build(ui, &mut nd);
+                                    ui.same_line();
+                                    update_abbe |= Drag::new(format!("Vd##{}", i))
+                                        .range(10., 100.)
+                                        .speed(0.1)
+                                        .build(ui, &mut vd);
+                                    if update_abbe {
+                                        lens.sellmeier =
+                                            Sellmeier::from_abbe(nd as f64, vd as f64, None);
+                                        lens.sellmeier_index = Sellmeier::nearest_glass(
+                                            nd as f64,
+                                            vd as f64,
+                                            &self.all_glasses,
+                                        )
+                                        .unwrap_or(0);
+                                        next_sellmeier =
+                                            Some((lens.sellmeier, lens.sellmeier_index));
+                                        update_lens = true;
+                                    }
                                 }
                             } else if let Some(sellmeier) = next_sellmeier {
                                 lens.sellmeier = sellmeier.0;
@@ -852,6 +1247,17 @@ impl LensState {
                                 update_lens |= Drag::new(format!("r##{}", i))
                                     .speed(0.01)
                                     .build(ui, &mut lens.r);
+                                update_lens |= Drag::new(format!("semi-diameter##{}", i))
+                                    .range(0., 500.)
+                                    .speed(0.01)
+                                    .build(ui, &mut lens.semi_diameter);
+                                update_lens |= Drag::new(format!("conic##{}", i))
+                                    .speed(0.01)
+                                    .build(ui, &mut lens.asphere.k);
+                                update_lens |= Drag::new(format!("a4..a16##{}", i))
+                                    .speed(1e-7)
+                                    .display_format("%.3e")
+                                    .build_array(ui, &mut lens.asphere.a);
                                 // update_lens |=
                                 //     Slider::new(format!("d2##{}", i), -3., 6.).build(&ui, &mut lens.d2);
                                 // ui.same_line();
@@ -864,6 +1270,16 @@ impl LensState {
                                 ui.same_line();
                                 update_lens |= ui
                                     .checkbox(format!("coating##{}", i), &mut lens.coating_enable);
+                                ui.same_line();
+                                let mut multi_layer = lens.coating_stack.is_some();
+                                if ui.checkbox(format!("multi-layer##{}", i), &mut multi_layer) {
+                                    lens.coating_stack = if multi_layer {
+                                        Some(CoatingStack::broadband(lens.coating_optimal as f64))
+                                    } else {
+                                        None
+                                    };
+                                    update_lens = true;
+                                }
 
                                 if ui.button(format!("delete##{}", i)) {
                                     delete_glass = Some(i - 1);
@@ -899,6 +1315,60 @@ impl LensState {
                                     delete_aperture = Some(i);
                                 }
 
+                                ui.separator();
+                            }
+                        }
+                        ElementState::Filter(filter) => {
+                            filter_counter += 1;
+                            if CollapsingHeader::new(format!("Filter {:?}", filter_counter))
+                                .build(ui)
+                            {
+                                if ui.combo(
+                                    format!("select glass##{}", i),
+                                    &mut filter.sellmeier_index,
+                                    self.all_glasses.as_slice(),
+                                    |(label, _lens)| std::borrow::Cow::Borrowed(label),
+                                ) {
+                                    filter.sellmeier = self.all_glasses[filter.sellmeier_index].1;
+                                    update_lens = true;
+                                }
+                                update_lens |= Drag::new(format!("d##{}", i))
+                                    .range(0., 500.)
+                                    .speed(0.1)
+                                    .build(ui, &mut filter.d);
+                                update_lens |= Drag::new(format!("thickness##{}", i))
+                                    .range(0.01, 10.)
+                                    .speed(0.01)
+                                    .build(ui, &mut filter.thickness);
+                                update_lens |= Drag::new(format!("semi-diameter##{}", i))
+                                    .range(0., 500.)
+                                    .speed(0.01)
+                                    .build(ui, &mut filter.semi_diameter);
+
+                                ui.input_text(
+                                    format!("transmittance##{}", i),
+                                    &mut filter.transmittance_file,
+                                )
+                                .build();
+                                ui.same_line();
+                                if ui.button(format!("load##{}", i)) {
+                                    match Spectrum::read_csv(Path::new(&filter.transmittance_file))
+                                    {
+                                        Ok(transmittance) => {
+                                            filter.transmittance = transmittance;
+                                            update_lens = true;
+                                        }
+                                        Err(err) => println!(
+                                            "Could not read {:?}:\n\t {}",
+                                            filter.transmittance_file, err
+                                        ),
+                                    }
+                                }
+
+                                if ui.button(format!("delete##{}", i)) {
+                                    delete_filter = Some(i);
+                                }
+
                                 ui.separator();
                             }
                         }
@@ -916,6 +1386,11 @@ impl LensState {
                     update_lens = true;
                 }
 
+                if let Some(delete_filter) = delete_filter {
+                    self.lens.remove(delete_filter);
+                    update_lens = true;
+                }
+
                 if ui.button("add aperture") {
                     self.lens.push(ElementState::Aperture(Aperture {
                         d: 1.5,
@@ -935,6 +1410,9 @@ impl LensState {
                         sellmeier_index: 0,
                         coating_optimal: 0.5,
                         coating_enable: false,
+                        coating_stack: None,
+                        semi_diameter: 0.,
+                        asphere: Asphere::sphere(),
                     }));
                     self.lens.push(ElementState::Lens(GlassElement {
                         d: 1.5,
@@ -945,6 +1423,28 @@ impl LensState {
                         sellmeier_index: 0,
                         coating_optimal: 0.5,
                         coating_enable: false,
+                        coating_stack: None,
+                        semi_diameter: 0.,
+                        asphere: Asphere::sphere(),
+                    }));
+                    update_lens = true;
+                }
+                ui.same_line();
+                if ui.button("add filter") {
+                    // BK7, selected in the glass combo like the cover glass
+                    let sellmeier_index = self
+                        .all_glasses
+                        .iter()
+                        .position(|(_name, glass)| *glass == Sellmeier::bk7())
+                        .unwrap_or(0);
+                    self.lens.push(ElementState::Filter(FilterElement {
+                        d: 0.5,
+                        thickness: 0.2,
+                        sellmeier: self.all_glasses[sellmeier_index].1,
+                        sellmeier_index,
+                        transmittance: Spectrum::constant(1.),
+                        transmittance_file: String::new(),
+                        semi_diameter: 0.,
                     }));
                     update_lens = true;
                 }
@@ -972,9 +1472,41 @@ impl LensState {
                     .range(0., 469245.)
                     .speed(0.01)
                     .build(ui, &mut self.actual_lens.sensor_dist);
+                if CollapsingHeader::new("sensor stack").build(ui) {
+                    update_lens |= ui.checkbox("sensor reflections", &mut self.sensor_reflections);
+                    if ui.combo(
+                        "cover glass",
+                        &mut self.cover_glass_index,
+                        self.all_glasses.as_slice(),
+                        |(label, _glass)| std::borrow::Cow::Borrowed(label),
+                    ) {
+                        self.sensor_stack.cover_glass = self.all_glasses[self.cover_glass_index].1;
+                        update_lens = true;
+                    }
+                    update_lens |= Drag::new("cover glass thickness (0: none)")
+                        .range(0., 10.)
+                        .speed(0.01)
+                        .build(ui, &mut self.sensor_stack.cover_thickness);
+                    update_lens |= Drag::new("cover glass gap")
+                        .range(0., 10.)
+                        .speed(0.01)
+                        .build(ui, &mut self.sensor_stack.cover_gap);
+                    let mut reflectance = self.sensor_stack.reflectance.at(0.55);
+                    if Slider::new("sensor reflectance", 0., 1.).build(ui, &mut reflectance) {
+                        self.sensor_stack.reflectance = Spectrum::constant(reflectance);
+                        update_lens = true;
+                    }
+                }
                 update_lens |= Slider::new("zoom", 0., 10.).build(ui, &mut self.sim_params[12]);
                 update_lens |=
                     Slider::new("num_wavelengths", 1, 20).build(ui, &mut self.num_wavelengths);
+                if CollapsingHeader::new("starburst").build(ui) {
+                    update_lens |= ui.checkbox("diffraction spikes", &mut self.starburst.enabled);
+                    update_lens |= Slider::new("starburst intensity", 0., 10.)
+                        .build(ui, &mut self.starburst.intensity);
+                    update_lens |= Slider::new("starburst scale", 1., 1000.)
+                        .build(ui, &mut self.starburst.scale);
+                }
                 update_lens |= update_sensor
             });
 
@@ -993,11 +1525,48 @@ impl LensState {
             .build(ui, || {
                 let num_ghosts = (self
                     .actual_lens
-                    .get_ghosts_indicies(self.draw as usize, 0)
+                    .get_ghost_paths(self.draw as usize, 0, self.max_bounces as usize)
                     .len()) as u32;
                 if Slider::new("which ghost", 0, num_ghosts).build(ui, &mut self.which_ghost) {
                     update_lens = true;
                 }
+                update_lens |= Slider::new("max bounces", 2, 4).build(ui, &mut self.max_bounces);
+                update_lens |= ui.checkbox("cull ghosts", &mut self.cull_ghosts);
+                if self.cull_ghosts {
+                    update_lens |= Slider::new("min ghost intensity", 0., 0.1)
+                        .build(ui, &mut self.ghost_culling.min_intensity);
+                    let mut max_ghosts = self.ghost_culling.max_ghosts as u32;
+                    if Slider::new("ghost budget (0: all)", 0, num_ghosts)
+                        .build(ui, &mut max_ghosts)
+                    {
+                        self.ghost_culling.max_ghosts = max_ghosts as usize;
+                        update_lens = true;
+                    }
+                }
+                if CollapsingHeader::new("ghost ranking").build(ui) {
+                    if ui.button("rank ghosts") {
+                        self.rank_ghosts();
+                    }
+                    let kept = self.ghost_culling.cull(&self.ghost_ranking);
+                    ui.columns(5, "ghost ranking", true);
+                    for title in ["surfaces", "energy", "footprint", "intensity", "culled"] {
+                        ui.text(title);
+                        ui.next_column();
+                    }
+                    for rank in &self.ghost_ranking {
+                        ui.text(format!("{:?}", rank.path.reflections()));
+                        ui.next_column();
+                        ui.text(format!("{:.2e}", rank.energy));
+                        ui.next_column();
+                        ui.text(format!("{:.2e}", rank.footprint));
+                        ui.next_column();
+                        ui.text(format!("{:.2e}", rank.intensity()));
+                        ui.next_column();
+                        ui.text(if kept.contains(&rank.path) { "" } else { "x" });
+                        ui.next_column();
+                    }
+                    ui.columns(1, "ghost ranking", false);
+                }
                 ui.text(format!("Framerate: {:.0}", self.fps));
                 update_rays |=
                     Slider::new("rays_exponent", 0., 6.5).build(ui, &mut self.ray_exponent);
@@ -1065,6 +1634,9 @@ impl LensState {
                     .speed(0.01)
                     .build(ui, &mut self.pos_params[10]);
 
+                update_lens |=
+                    Slider::new("s polarized light", 0., 1.).build(ui, &mut self.pos_params[11]);
+
                 render = ui.button("hi-res render");
                 ui.same_line();
                 compute = ui.button("compute");
@@ -1092,6 +1664,28 @@ impl LensState {
         )
     }*/
 
+    /// rank the ghosts of the current lens for the current ray origin, direction and width
+    pub fn rank_ghosts(&mut self) {
+        self.ghost_ranking = self.actual_lens.rank_ghosts(
+            Vector3::new(
+                self.pos_params[0] as f64,
+                self.pos_params[1] as f64,
+                self.pos_params[2] as f64,
+            ),
+            Vector3::new(
+                self.pos_params[4] as f64,
+                self.pos_params[5] as f64,
+                self.pos_params[6] as f64,
+            ),
+            self.pos_params[9] as f64,
+            // the middle of the visible spectrum
+            0.55,
+            self.actual_lens.sensor_dist,
+            GHOST_RANK_SIDE_LEN,
+            self.max_bounces as usize,
+        );
+    }
+
     pub fn dir_to_lens(&mut self) {
         let dir = self.actual_lens.get_center_dir(Vector3::new(
             self.pos_params[0] as f64,
@@ -1111,11 +1705,7 @@ impl LensState {
         self.actual_lens = self.get_lens();
         self.current_filename = lenses[self.selected_lens].0.clone();
 
-        queue.write_buffer(
-            &self.sensor_buffer,
-            0,
-            bytemuck::cast_slice(&self.all_sensors[self.sensor_index].1.get_data()),
-        );
+        self.write_colour(queue);
 
         let sample = 1. / (Instant::now() - self.last_frame_time).as_secs_f64();
         let alpha = 0.98;
@@ -1137,4 +1727,28 @@ impl LensState {
         self.actual_lens = self.get_lens();
         self.current_filename = lenses[self.selected_lens].0.clone();
     }
+
+    /// how the rays become colours, see `ColourPipeline`
+    pub fn get_colour(&self) -> ColourPipeline {
+        ColourPipeline {
+            sensor: Some(self.all_sensors[self.sensor_index].1.clone())
+                .filter(|_| !self.cie_observer),
+            output: self.all_colour_spaces[self.colour_space_index].1,
+        }
+    }
+
+    /// write the response LUT and the colour matrix of `get_colour` to their buffers
+    fn write_colour(&self, queue: &Queue) {
+        let colour = self.get_colour();
+        queue.write_buffer(
+            &self.sensor_buffer,
+            0,
+            bytemuck::cast_slice(&colour.get_lut()),
+        );
+        queue.write_buffer(
+            &self.colour_buffer,
+            0,
+            bytemuck::cast_slice(&colour.get_matrix_buffer()),
+        );
+    }
 }
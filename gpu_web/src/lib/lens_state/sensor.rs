@@ -1,437 +0,0 @@
-use cgmath::Vector3;
-
-impl Sensor {
-    /// from https://www.image-engineering.de/content/library/diploma_thesis/christian_mauer_spectral_response.pdf
-    pub fn get_all_sensors() -> Vec<(String, Sensor)> {
-        vec![
-            ("Leica M8".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.02, y: 0.01, z: 0.03} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.03, y: 0.02, z: 0.10} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.03, y: 0.04, z: 0.27} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.03, y: 0.04, z: 0.39} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.03, y: 0.05, z: 0.44} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.03, y: 0.06, z: 0.51} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.03, y: 0.09, z: 0.62} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.04, y: 0.12, z: 0.70} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.05, y: 0.18, z: 0.77} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.07, y: 0.30, z: 0.84} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.08, y: 0.40, z: 0.86} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.08, y: 0.44, z: 0.84} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.10, y: 0.60, z: 0.74} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.11, y: 0.75, z: 0.62} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.12, y: 0.93, z: 0.46} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.12, y: 1.03, z: 0.30} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.13, y: 1.02, z: 0.20} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.14, y: 1.00, z: 0.14} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.13, y: 0.92, z: 0.09} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.13, y: 0.82, z: 0.07} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.19, y: 0.67, z: 0.06} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.38, y: 0.51, z: 0.06} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.61, y: 0.30, z: 0.05} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.62, y: 0.20, z: 0.04} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.55, y: 0.12, z: 0.04} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.46, y: 0.08, z: 0.03} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.38, y: 0.06, z: 0.03} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.28, y: 0.04, z: 0.02} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.22, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.16, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.13, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.09, y: 0.03, z: 0.01} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.07, y: 0.03, z: 0.01} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.06, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.04, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.02, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 905., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Nikon D700".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.00, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.03} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.05, y: 0.02, z: 0.38} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.07, y: 0.04, z: 0.70} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.05, y: 0.06, z: 0.87} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.04, y: 0.09, z: 1.04} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.03, y: 0.13, z: 1.02} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.04, y: 0.26, z: 1.01} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.04, y: 0.39, z: 0.90} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.04, y: 0.43, z: 0.78} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.04, y: 0.60, z: 0.56} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.04, y: 0.81, z: 0.32} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.07, y: 0.98, z: 0.17} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.08, y: 1.07, z: 0.07} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.05, y: 1.06, z: 0.04} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.03, y: 1.00, z: 0.02} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.03, y: 0.87, z: 0.01} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.09, y: 0.68, z: 0.01} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.48, y: 0.48, z: 0.00} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.76, y: 0.33, z: 0.00} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.74, y: 0.15, z: 0.00} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.64, y: 0.07, z: 0.00} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.54, y: 0.03, z: 0.00} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.44, y: 0.02, z: 0.00} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.35, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.25, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.20, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.11, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.04, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 905., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Canon EOS 40D".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.01} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.01} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.00, y: 0.01, z: 0.09} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.00, y: 0.03, z: 0.42} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.00, y: 0.05, z: 0.65} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.00, y: 0.08, z: 0.87} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.00, y: 0.09, z: 0.96} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.00, y: 0.13, z: 1.01} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.01, y: 0.29, z: 0.97} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.01, y: 0.54, z: 0.90} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.01, y: 0.70, z: 0.81} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.02, y: 0.94, z: 0.61} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.04, y: 1.01, z: 0.43} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.08, y: 1.08, z: 0.25} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.14, y: 1.06, z: 0.14} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.15, y: 1.01, z: 0.10} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.14, y: 1.00, z: 0.07} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.17, y: 0.87, z: 0.05} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.30, y: 0.75, z: 0.04} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.45, y: 0.58, z: 0.03} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.53, y: 0.42, z: 0.02} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.56, y: 0.24, z: 0.01} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.53, y: 0.15, z: 0.01} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.46, y: 0.08, z: 0.01} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.38, y: 0.05, z: 0.01} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.33, y: 0.03, z: 0.01} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.23, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.19, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.15, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.10, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.03, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 905., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Fuji S5 Pro".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.00, y: 0.01, z: 0.03} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.25} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.44} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.62} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.74} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.00, y: 0.01, z: 0.86} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.00, y: 0.01, z: 0.99} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.00, y: 0.01, z: 1.02} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.00, y: 0.05, z: 1.01} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.01, y: 0.37, z: 0.95} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.01, y: 0.68, z: 0.84} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.01, y: 0.98, z: 0.63} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.01, y: 1.07, z: 0.47} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.02, y: 1.09, z: 0.29} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.03, y: 1.10, z: 0.14} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.02, y: 1.08, z: 0.09} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.01, y: 1.00, z: 0.05} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.01, y: 0.88, z: 0.02} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.05, y: 0.75, z: 0.01} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.31, y: 0.58, z: 0.01} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.83, y: 0.39, z: 0.01} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.90, y: 0.23, z: 0.01} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.86, y: 0.09, z: 0.01} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.79, y: 0.05, z: 0.01} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.72, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.70, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.60, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.54, y: 0.01, z: 0.02} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.41, y: 0.01, z: 0.02} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.20, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.05, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.02, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 900., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Panasonic DMC-LX3".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.02} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.01, y: 0.05, z: 0.18} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.03, y: 0.14, z: 0.54} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.04, y: 0.28, z: 0.95} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.03, y: 0.34, z: 1.03} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.03, y: 0.41, z: 1.01} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.03, y: 0.55, z: 0.98} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.03, y: 0.62, z: 0.87} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.03, y: 0.65, z: 0.79} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.04, y: 0.83, z: 0.59} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.05, y: 1.01, z: 0.46} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.08, y: 1.10, z: 0.31} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.09, y: 1.13, z: 0.21} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.06, y: 1.05, z: 0.15} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.04, y: 1.00, z: 0.10} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.04, y: 0.88, z: 0.06} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.04, y: 0.73, z: 0.04} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.23, y: 0.58, z: 0.03} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.65, y: 0.44, z: 0.03} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.74, y: 0.28, z: 0.02} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.70, y: 0.20, z: 0.02} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.63, y: 0.14, z: 0.02} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.56, y: 0.10, z: 0.02} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.51, y: 0.08, z: 0.02} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.42, y: 0.06, z: 0.03} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.30, y: 0.05, z: 0.02} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.15, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.06, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 905., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Arriflex D-21".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.02, y: 0.02, z: 0.03} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.05, y: 0.02, z: 0.07} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.15, y: 0.04, z: 0.32} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.14, y: 0.05, z: 0.48} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.12, y: 0.06, z: 0.68} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.06, y: 0.08, z: 0.85} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.05, y: 0.13, z: 0.99} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.06, y: 0.18, z: 1.11} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.07, y: 0.27, z: 1.01} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.08, y: 0.34, z: 0.93} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.09, y: 0.37, z: 0.79} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.11, y: 0.47, z: 0.54} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.21, y: 0.79, z: 0.44} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.30, y: 1.02, z: 0.34} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.40, y: 1.10, z: 0.28} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.44, y: 1.15, z: 0.26} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.38, y: 1.00, z: 0.22} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.44, y: 0.85, z: 0.19} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.63, y: 0.55, z: 0.13} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.92, y: 0.36, z: 0.10} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 1.07, y: 0.23, z: 0.08} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 1.02, y: 0.13, z: 0.06} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.80, y: 0.09, z: 0.05} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.74, y: 0.08, z: 0.04} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.53, y: 0.05, z: 0.03} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.38, y: 0.05, z: 0.03} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.23, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.16, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.11, y: 0.03, z: 0.02} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.05, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.03, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.01} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.01} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.01} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 905., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Canon EOS 450D no IR filter".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.04, y: 0.02, z: 0.04} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.04, y: 0.02, z: 0.11} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.03, y: 0.03, z: 0.24} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.02, y: 0.03, z: 0.34} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.01, y: 0.04, z: 0.46} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.00, y: 0.04, z: 0.50} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.00, y: 0.06, z: 0.64} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.00, y: 0.08, z: 0.71} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.01, y: 0.13, z: 0.77} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.01, y: 0.26, z: 0.75} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.02, y: 0.51, z: 0.73} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.02, y: 0.64, z: 0.67} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.04, y: 0.81, z: 0.50} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.06, y: 0.92, z: 0.39} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.07, y: 1.02, z: 0.26} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.09, y: 1.01, z: 0.16} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.12, y: 1.03, z: 0.13} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.17, y: 1.00, z: 0.10} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.23, y: 0.92, z: 0.07} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.37, y: 0.84, z: 0.06} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.59, y: 0.78, z: 0.06} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.78, y: 0.58, z: 0.05} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.77, y: 0.39, z: 0.03} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.80, y: 0.21, z: 0.02} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.87, y: 0.15, z: 0.02} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.82, y: 0.10, z: 0.02} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.77, y: 0.08, z: 0.02} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.83, y: 0.08, z: 0.03} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.77, y: 0.07, z: 0.04} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.69, y: 0.09, z: 0.04} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.65, y: 0.11, z: 0.04} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.60, y: 0.15, z: 0.05} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.62, y: 0.19, z: 0.05} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.63, y: 0.21, z: 0.05} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.58, y: 0.19, z: 0.04} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.53, y: 0.22, z: 0.03} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.38, y: 0.30, z: 0.20} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.23, y: 0.23, z: 0.23} },
-                    SensorDatapoint { wavelength: 905., rgb: Vector3 { x: 0.12, y: 0.13, z: 0.12} },
-                ],
-            }
-        ),(
-            "Hasselblad H3D".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.06, y: 0.02, z: 0.03} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.10, y: 0.03, z: 0.13} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.11, y: 0.04, z: 0.25} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.11, y: 0.05, z: 0.36} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.12, y: 0.06, z: 0.44} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.13, y: 0.08, z: 0.55} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.14, y: 0.11, z: 0.67} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.16, y: 0.16, z: 0.75} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.18, y: 0.19, z: 0.79} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.24, y: 0.28, z: 0.76} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.28, y: 0.35, z: 0.81} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.29, y: 0.39, z: 0.75} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.33, y: 0.49, z: 0.72} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.40, y: 0.66, z: 0.65} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.46, y: 0.80, z: 0.55} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.53, y: 1.03, z: 0.44} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.62, y: 1.07, z: 0.34} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.60, y: 1.00, z: 0.24} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.55, y: 0.90, z: 0.16} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.55, y: 0.74, z: 0.13} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.65, y: 0.66, z: 0.12} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 1.11, y: 0.44, z: 0.09} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 1.55, y: 0.27, z: 0.08} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 1.34, y: 0.16, z: 0.06} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 1.05, y: 0.10, z: 0.04} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.93, y: 0.07, z: 0.04} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.63, y: 0.05, z: 0.03} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.29, y: 0.02, z: 0.02} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.20, y: 0.02, z: 0.01} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.16, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.09, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.04, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.02, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 710., rgb: Vector3 { x: 0.01, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 720., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 906., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        ),(
-            "Nikon D200".to_string(),
-            Sensor {
-                measuremens: vec![
-                    SensorDatapoint { wavelength: 380., rgb: Vector3 { x: 0.02, y: 0.02, z: 0.03} },
-                    SensorDatapoint { wavelength: 390., rgb: Vector3 { x: 0.02, y: 0.01, z: 0.02} },
-                    SensorDatapoint { wavelength: 400., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.01} },
-                    SensorDatapoint { wavelength: 410., rgb: Vector3 { x: 0.01, y: 0.01, z: 0.04} },
-                    SensorDatapoint { wavelength: 420., rgb: Vector3 { x: 0.05, y: 0.05, z: 0.45} },
-                    SensorDatapoint { wavelength: 430., rgb: Vector3 { x: 0.06, y: 0.08, z: 0.72} },
-                    SensorDatapoint { wavelength: 440., rgb: Vector3 { x: 0.04, y: 0.10, z: 0.86} },
-                    SensorDatapoint { wavelength: 450., rgb: Vector3 { x: 0.04, y: 0.14, z: 1.04} },
-                    SensorDatapoint { wavelength: 460., rgb: Vector3 { x: 0.03, y: 0.18, z: 1.03} },
-                    SensorDatapoint { wavelength: 470., rgb: Vector3 { x: 0.03, y: 0.31, z: 1.01} },
-                    SensorDatapoint { wavelength: 480., rgb: Vector3 { x: 0.04, y: 0.42, z: 0.95} },
-                    SensorDatapoint { wavelength: 490., rgb: Vector3 { x: 0.04, y: 0.47, z: 0.68} },
-                    SensorDatapoint { wavelength: 500., rgb: Vector3 { x: 0.03, y: 0.59, z: 0.49} },
-                    SensorDatapoint { wavelength: 510., rgb: Vector3 { x: 0.05, y: 0.88, z: 0.29} },
-                    SensorDatapoint { wavelength: 520., rgb: Vector3 { x: 0.07, y: 1.05, z: 0.14} },
-                    SensorDatapoint { wavelength: 530., rgb: Vector3 { x: 0.08, y: 1.05, z: 0.05} },
-                    SensorDatapoint { wavelength: 540., rgb: Vector3 { x: 0.04, y: 1.10, z: 0.02} },
-                    SensorDatapoint { wavelength: 550., rgb: Vector3 { x: 0.02, y: 1.00, z: 0.01} },
-                    SensorDatapoint { wavelength: 560., rgb: Vector3 { x: 0.03, y: 0.87, z: 0.00} },
-                    SensorDatapoint { wavelength: 570., rgb: Vector3 { x: 0.13, y: 0.72, z: 0.00} },
-                    SensorDatapoint { wavelength: 580., rgb: Vector3 { x: 0.44, y: 0.53, z: 0.00} },
-                    SensorDatapoint { wavelength: 590., rgb: Vector3 { x: 0.90, y: 0.32, z: 0.00} },
-                    SensorDatapoint { wavelength: 600., rgb: Vector3 { x: 0.90, y: 0.16, z: 0.00} },
-                    SensorDatapoint { wavelength: 610., rgb: Vector3 { x: 0.77, y: 0.08, z: 0.00} },
-                    SensorDatapoint { wavelength: 620., rgb: Vector3 { x: 0.68, y: 0.04, z: 0.00} },
-                    SensorDatapoint { wavelength: 630., rgb: Vector3 { x: 0.55, y: 0.03, z: 0.00} },
-                    SensorDatapoint { wavelength: 640., rgb: Vector3 { x: 0.44, y: 0.02, z: 0.00} },
-                    SensorDatapoint { wavelength: 650., rgb: Vector3 { x: 0.31, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 660., rgb: Vector3 { x: 0.21, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 670., rgb: Vector3 { x: 0.10, y: 0.01, z: 0.00} },
-                    SensorDatapoint { wavelength: 680., rgb: Vector3 { x: 0.02, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 690., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 700., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 750., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 800., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 850., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                    SensorDatapoint { wavelength: 906., rgb: Vector3 { x: 0.00, y: 0.00, z: 0.00} },
-                ],
-            }
-        )]
-    }
-}
-
-/// How much a single wavelength influences r, g and b of the Sensor
-pub struct SensorDatapoint {
-    pub rgb: Vector3<f32>,
-    pub wavelength: f32,
-}
-
-/// A representation of a sensor - given by measurements of a particular wavelength
-/// Measurements taken from https://www.image-engineering.de/content/library/diploma_thesis/christian_mauer_spectral_response.pdf
-pub struct Sensor {
-    pub measuremens: Vec<SensorDatapoint>,
-}
-
-impl Sensor {
-    /// get the contained data in r,g,b,wavelength format
-    pub fn get_data(&self) -> Vec<f32> {
-        let mut result = vec![];
-        for measurement in &self.measuremens {
-            result.push(measurement.rgb.x);
-            result.push(measurement.rgb.y);
-            result.push(measurement.rgb.z);
-            result.push(measurement.wavelength);
-        }
-        result
-    }
-}
\ No newline at end of file
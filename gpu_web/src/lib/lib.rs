@@ -2,6 +2,8 @@ pub mod lens_state;
 
 pub mod scenes;
 
+pub mod starburst;
+
 pub mod save_png;
 pub mod state;
 pub mod texture;
@@ -1,8 +1,18 @@
+/// one Ray as stored in the buffers
+struct InitRay {
+  o: vec3<f32>;
+  wavelength: f32;
+  d: vec3<f32>;
+  strength: f32;
+};
+
 struct Ray {
   o: vec3<f32>;
   wavelength: f32;
   d: vec3<f32>;
   strength: f32;
+  // fractions of strength that are s and p polarized
+  polarization: vec2<f32>;
 };
 
 struct Element {
@@ -19,11 +29,31 @@ struct Element {
   c1_2: f32;
   c2_2: f32;
   c3_2: f32;
-  coating_ior: f32;
-  coating_thickness: f32;
+  coating_start: f32;// index of the first layer in Coatings
+  coating_num: f32;// number of coating layers
   position: f32;// num_blades if aperture
-  entry: f32;// 0: false, 1: true, 2: aperture
+  entry: f32;// 0: false, 1: true, 2: aperture, 3: sensor, 4/5: filter front/back
   spherical: f32;// 0: false, 1: true
+  k: f32;// conic constant
+  a4: f32;// even asphere coefficients
+  a6: f32;
+  a8: f32;
+  a10: f32;
+  a12: f32;
+  a14: f32;
+  a16: f32;
+  semi_diameter: f32;// radius of the clear aperture, 0: unlimited
+  absorption1: f32;// absorption coefficient of the glass every 50 nm from 380 nm
+  absorption2: f32;
+  absorption3: f32;
+  absorption4: f32;
+  absorption5: f32;
+  absorption6: f32;
+  absorption7: f32;
+  absorption8: f32;
+  absorption9: f32;
+  formula: f32;// dispersion formula of the glass, 0: Sellmeier, 1: Schott, 2: Herzberger, 3: Conrady
+  formula_2: f32;// dispersion formula of the outer ior
 };
 
 
@@ -45,19 +75,62 @@ struct SimParams {
 
 
 struct PosParams {
-  init: Ray;
+  init: InitRay;
   sensor: f32;
   width: f32;
+  entry_rad: f32;
+  // fraction of s polarized light from the source, 0.5 if unpolarized
+  s_polarized: f32;
 };
 
 
 struct Rays {
-  rays: [[stride(32)]] array<Ray>;
+  rays: [[stride(32)]] array<InitRay>;
 };
 
 
 struct Elements {
-  el: [[stride(72)]] array<Element>;
+  el: [[stride(152)]] array<Element>;
+};
+
+/// one layer of a coating, see `Lens::get_coatings_buffer`
+struct CoatingLayer {
+  b1: f32;
+  b2: f32;
+  b3: f32;
+  c1: f32;
+  c2: f32;
+  c3: f32;
+  thickness: f32;// in µm
+  // `DispersionFormula` of the material
+  formula: f32;
+};
+
+/// relative power of the light source every 5nm from 380nm to 780nm
+struct LightSpectrum {
+  samples: array<f32, 81>;
+};
+
+/// the coating layers of all Elements
+struct Coatings {
+  layers: [[stride(32)]] array<CoatingLayer>;
+};
+
+/// a ghost reflecting at the surfaces ghostSurfaces.el[start..start + num],
+/// the direct path through the lens has no reflections
+struct WhichGhost {
+  start: u32;
+  num: u32;
+};
+
+/// the pairs of Elements the drawn ghosts reflect at
+struct Ghosts {
+  el: [[stride(8)]] array<WhichGhost>;
+};
+
+/// the reflecting surfaces of all ghosts, in the order the light hits them
+struct GhostSurfaces {
+  el: [[stride(4)]] array<u32>;
 };
 
 [[group(0), binding(0)]] var<storage, read_write> rays : Rays;
@@ -66,37 +139,66 @@ struct Elements {
 [[group(1), binding(0)]] var<uniform> posParams : PosParams;
 
 [[group(2), binding(0)]] var<storage, read> elements : Elements;
-
-fn plank(wavelen: f32, temp: f32) -> f32 {
-    let h = 6.62607015e-34; // J/Hz
-    let b = 1.380649e-23; // J/K
-    let c = 299792458.; // m/s
-    let e = 2.718281828459045;
-    let hc = 1.9864458571489286e-25;
-    let hcc2 = 1.1910429723971884e-16;
-    return hcc2
-        / (pow(wavelen, 5.))
-        / (pow(e, (hc) / (wavelen * b * temp)) - 1.) / 1.e12;
+[[group(2), binding(3)]] var<storage, read> coatings : Coatings;
+[[group(2), binding(5)]] var<storage, read> light : LightSpectrum;
+[[group(2), binding(2)]] var<storage, read> whichGhost : Ghosts;
+[[group(2), binding(4)]] var<storage, read> ghostSurfaces : GhostSurfaces;
+
+/// power of the light source at wavelen in µm, interpolated in the LUT
+fn light_power(wavelen: f32) -> f32 {
+    let x = clamp((wavelen - 0.38) / 0.005, 0., 80.);
+    let i = min(u32(x), u32(79));
+    let t = x - f32(i);
+    return light.samples[i] * (1. - t) + light.samples[i + u32(1)] * t;
 }
 
+/// the LUT has a mean of 1, scaled to the brightness of the default 3000K blackbody
 fn str_from_wavelen(wavelen: f32) -> f32 {
-    return plank(wavelen / 1000000., 3000.) * 1000.;
+    return light_power(wavelen) * 463.;
 }
 
-fn ior(self: Element, wavelength: f32) -> f32 {
+/// refractive index from the coefficients of a dispersion formula,
+/// b and c hold the coefficients like `Sellmeier` does
+fn dispersion(formula: f32, b: vec3<f32>, c: vec3<f32>, wavelength: f32) -> f32 {
     let wavelength_sq = wavelength * wavelength;
-    let n_sq = 1. + (self.b1 * wavelength_sq) / (wavelength_sq - self.c1)
-                      + (self.b2 * wavelength_sq) / (wavelength_sq - self.c2)
-                      + (self.b3 * wavelength_sq) / (wavelength_sq - self.c3);
+    if (formula > 2.5) {
+        // Conrady
+        return b.x + b.y / wavelength + b.z / pow(wavelength, 3.5);
+    }
+    if (formula > 1.5) {
+        // Herzberger
+        let l = 1. / (wavelength_sq - 0.028);
+        return b.x + b.y * l + b.z * l * l
+            + wavelength_sq * (c.x + wavelength_sq * (c.y + wavelength_sq * c.z));
+    }
+    if (formula > 0.5) {
+        // Schott
+        let inv_sq = 1. / wavelength_sq;
+        return sqrt(b.x + b.y * wavelength_sq
+            + inv_sq * (b.z + inv_sq * (c.x + inv_sq * (c.y + inv_sq * c.z))));
+    }
+    let n_sq = 1. + (b.x * wavelength_sq) / (wavelength_sq - c.x)
+                  + (b.y * wavelength_sq) / (wavelength_sq - c.y)
+                  + (b.z * wavelength_sq) / (wavelength_sq - c.z);
     return sqrt(n_sq);
 }
 
+fn ior(self: Element, wavelength: f32) -> f32 {
+    return dispersion(
+        self.formula,
+        vec3<f32>(self.b1, self.b2, self.b3),
+        vec3<f32>(self.c1, self.c2, self.c3),
+        wavelength,
+    );
+}
+
 fn ior_other(self: Element, wavelength: f32) -> f32 {
-    let wavelength_sq = wavelength * wavelength;
-    let n_sq = 1. + (self.b1_2 * wavelength_sq) / (wavelength_sq - self.c1_2)
-                      + (self.b2_2 * wavelength_sq) / (wavelength_sq - self.c2_2)
-                      + (self.b3_2 * wavelength_sq) / (wavelength_sq - self.c3_2);
-    return sqrt(n_sq);
+    return dispersion(
+        self.formula_2,
+        vec3<f32>(self.b1_2, self.b2_2, self.b3_2),
+        vec3<f32>(self.c1_2, self.c2_2, self.c3_2),
+        wavelength,
+    );
 }
 
 fn fresnel_r(t1: f32, t2: f32, n1: f32, n2: f32) -> f32 {
@@ -105,31 +207,194 @@ fn fresnel_r(t1: f32, t2: f32, n1: f32, n2: f32) -> f32 {
   return s + p;
 }
 
-fn fresnel_ar(theta0: f32, lambda: f32, thickness: f32, n0: f32, n1: f32, n2: f32) -> f32 {
-    // refracton angle sin coating and the 2nd medium
-    let theta1 = asin(sin(theta0) * n0 / n1);
-    let theta2 = asin(sin(theta0) * n0 / n2);
-    // amplitude for outer refl. / transmission on topmost interface
-    let rs01 = -sin(theta0 - theta1) / sin(theta0 + theta1);
-    let rp01 = tan(theta0 - theta1) / tan(theta0 + theta1);
-    let ts01 = 2. * sin(theta1) * cos(theta0) / sin(theta0 + theta1);
-    let tp01 = ts01 * cos(theta0 - theta1);
-    // amplitude for inner reflection
-    let rs12 = -sin(theta1 - theta2) / sin(theta1 + theta2);
-    let rp12 = tan(theta1 - theta2) / tan(theta1 + theta2);
-    // after passing through first surface twice:
-    // 2 transmissions and 1 reflection
-    let ris = ts01 * ts01 * rs12;
-    let rip = tp01 * tp01 * rp12;
-    // phasedifference between outer and inner reflections
-    let dy = thickness * n1;
-    let dx = tan(theta1) * dy;
-    let delay = sqrt(dx * dx + dy * dy);
-    let rel_phase = 4. * 3.141592653589793 / lambda * (delay - dx * sin(theta0));
-    // Add up sines of different phase and amplitude
-    let out_s2 = rs01 * rs01 + ris * ris + 2. * rs01 * ris * cos(rel_phase);
-    let out_p2 = rp01 * rp01 + rip * rip + 2. * rp01 * rip * cos(rel_phase);
-    return (out_s2 + out_p2) / 2.; // reflectivity
+// complex numbers as vec2<f32>(re, im)
+fn c_mul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+fn c_div(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x + a.y * b.y, a.y * b.x - a.x * b.y) / dot(b, b);
+}
+
+fn c_cos(a: vec2<f32>) -> vec2<f32> {
+    let y = clamp(a.y, -20., 20.);
+    return vec2<f32>(cos(a.x) * (exp(y) + exp(-y)) / 2., -sin(a.x) * (exp(y) - exp(-y)) / 2.);
+}
+
+fn c_sin(a: vec2<f32>) -> vec2<f32> {
+    let y = clamp(a.y, -20., 20.);
+    return vec2<f32>(sin(a.x) * (exp(y) + exp(-y)) / 2., cos(a.x) * (exp(y) - exp(-y)) / 2.);
+}
+
+fn layer_ior(self: CoatingLayer, wavelength: f32) -> f32 {
+    return dispersion(
+        self.formula,
+        vec3<f32>(self.b1, self.b2, self.b3),
+        vec3<f32>(self.c1, self.c2, self.c3),
+        wavelength,
+    );
+}
+
+/// cosine of the angle to the normal in a medium with index n, imaginary beyond the critical angle
+fn cos_in(n_sin: f32, n: f32) -> vec2<f32> {
+    let cos_sq = 1. - (n_sin / n) * (n_sin / n);
+    if (cos_sq >= 0.) {
+        return vec2<f32>(sqrt(max(cos_sq, 0.000001)), 0.);
+    }
+    return vec2<f32>(0., sqrt(-cos_sq));
+}
+
+/// tilted optical admittance for s or p polarization
+fn admittance(n: f32, cos_theta: vec2<f32>, p: bool) -> vec2<f32> {
+    if (p) {
+        return c_div(vec2<f32>(n, 0.), cos_theta);
+    }
+    return n * cos_theta;
+}
+
+/// reflectance (s, p) of the interface between n_outer and n_glass coated with the layers
+/// start..start + num of `coatings`, ordered from the outer medium towards the glass,
+/// see `CoatingStack::reflectance`
+fn coating_reflectance(
+    theta0: f32,
+    lambda: f32,
+    n_outer: f32,
+    n_glass: f32,
+    from_outside: bool,
+    start: u32,
+    num: u32,
+) -> vec2<f32> {
+    var n0 = n_glass;
+    var n_sub = n_outer;
+    if (from_outside) {
+        n0 = n_outer;
+        n_sub = n_glass;
+    }
+    // n sin(theta) is the same in every layer
+    let n_sin = n0 * sin(theta0);
+
+    var reflectance = vec2<f32>(0., 0.);
+    for (var p = u32(0); p < u32(2); p = p + u32(1)) {
+        // (b, c) = M_1 * ... * M_num * (1, eta_sub)
+        var b = vec2<f32>(1., 0.);
+        var c = admittance(n_sub, cos_in(n_sin, n_sub), p > u32(0));
+        for (var k = u32(0); k < num; k = k + u32(1)) {
+            // from the substrate outwards
+            var index = start + k;
+            if (from_outside) {
+                index = start + num - u32(1) - k;
+            }
+            let layer = coatings.layers[index];
+            let n = layer_ior(layer, lambda);
+            let cos_theta = cos_in(n_sin, n);
+            let eta = admittance(n, cos_theta, p > u32(0));
+            let delta = 2. * 3.141592653589793 * n * layer.thickness * cos_theta / lambda;
+            let i_sin = c_mul(vec2<f32>(0., 1.), c_sin(delta));
+            let b_new = c_mul(c_cos(delta), b) + c_mul(c_div(i_sin, eta), c);
+            c = c_mul(c_mul(i_sin, eta), b) + c_mul(c_cos(delta), c);
+            b = b_new;
+        }
+        let eta0 = admittance(n0, cos_in(n_sin, n0), p > u32(0));
+        let r = c_div(c_mul(eta0, b) - c, c_mul(eta0, b) + c);
+        if (p == u32(0)) {
+            reflectance.x = min(dot(r, r), 1.);
+        } else {
+            reflectance.y = min(dot(r, r), 1.);
+        }
+    }
+    return reflectance;
+}
+
+/// scale the s and p parts of the ray by the factors, e.g. the reflectance of an interface
+fn polarize(self: Ray, factors: vec2<f32>) -> Ray {
+    var ray = self;
+    let parts = ray.polarization * factors;
+    let total = parts.x + parts.y;
+    ray.strength = ray.strength * total;
+    if (total > 0.) {
+        ray.polarization = parts / total;
+    }
+    return ray;
+}
+
+/// drop the polarization to store the Ray
+fn init_ray(self: Ray) -> InitRay {
+    return InitRay(self.o, self.wavelength, self.d, self.strength);
+}
+
+/// polarization of the light source: fractions of s and p polarized light
+fn source_polarization() -> vec2<f32> {
+    return vec2<f32>(posParams.s_polarized, 1. - posParams.s_polarized);
+}
+
+struct Asphere {
+  k: f32;
+  a4: f32;
+  a6: f32;
+  a8: f32;
+  a10: f32;
+  a12: f32;
+  a14: f32;
+  a16: f32;
+};
+
+fn get_asphere(self: Element) -> Asphere {
+    return Asphere(self.k, self.a4, self.a6, self.a8, self.a10, self.a12, self.a14, self.a16);
+}
+
+fn is_sphere(self: Asphere) -> bool {
+    return self.k == 0. && self.a4 == 0. && self.a6 == 0. && self.a8 == 0.
+        && self.a10 == 0. && self.a12 == 0. && self.a14 == 0. && self.a16 == 0.;
+}
+
+// distance to the axis of the surface squared
+fn asphere_h_sq(p: vec3<f32>, cylindrical: bool) -> f32 {
+    if (cylindrical) {
+        return p.y * p.y;
+    }
+    return p.x * p.x + p.y * p.y;
+}
+
+// sag of the asphere with its vertex at 0 and its derivative by h²
+fn asphere_sag(self: Asphere, radius: f32, h_sq: f32) -> vec2<f32> {
+    let c = 1. / radius;
+    let root = sqrt(1. - (1. + self.k) * c * c * h_sq);
+    let u = h_sq;
+    let sag = c * u / (1. + root)
+        + u * u * (self.a4 + u * (self.a6 + u * (self.a8 + u * (self.a10
+        + u * (self.a12 + u * (self.a14 + u * self.a16))))));
+    let deriv = c / (2. * root)
+        + u * (2. * self.a4 + u * (3. * self.a6 + u * (4. * self.a8 + u * (5. * self.a10
+        + u * (6. * self.a12 + u * (7. * self.a14 + u * 8. * self.a16))))));
+    return vec2<f32>(sag, deriv);
+}
+
+// newton iteration for the intersection of the ray with the asphere
+fn intersect_asphere(self: Asphere, ray: Ray, position: f32, radius: f32, cylindrical: bool) -> vec3<f32> {
+    var t = (position - ray.o.z) / ray.d.z;
+    for (var i = 0; i < 16; i = i + 1) {
+        let p = ray.o + ray.d * t;
+        let sag = asphere_sag(self, radius, asphere_h_sq(p, cylindrical));
+        var dh_sq = 2. * (p.x * ray.d.x + p.y * ray.d.y);
+        if (cylindrical) {
+            dh_sq = 2. * p.y * ray.d.y;
+        }
+        t = t - (p.z - position - sag.x) / (ray.d.z - sag.y * dh_sq);
+    }
+    return ray.o + ray.d * t;
+}
+
+// normal of the asphere at p facing against the ray
+fn asphere_normal(self: Asphere, ray: Ray, p: vec3<f32>, radius: f32, cylindrical: bool) -> vec3<f32> {
+    let deriv = asphere_sag(self, radius, asphere_h_sq(p, cylindrical)).y;
+    var normal = normalize(vec3<f32>(-2. * deriv * p.x, -2. * deriv * p.y, 1.));
+    if (cylindrical) {
+        normal = normalize(vec3<f32>(0., -2. * deriv * p.y, 1.));
+    }
+    if (dot(normal, ray.d) > 0.) {
+        normal = -normal;
+    }
+    return normal;
 }
 
 fn propagate_element(
@@ -141,13 +406,23 @@ fn propagate_element(
     reflect: bool,
     entry: bool,
     cylindrical: bool,
-    coating_ior: f32,
-    coating_thickness: f32,
+    coating_start: u32,
+    coating_num: u32,
+    asphere: Asphere,
+    semi_diameter: f32,
+    absorption: f32,
 ) -> Ray {
     var ray = self;
     ray.d = normalize(ray.d);
+    // standard sign of the radius
+    var asphere_radius = radius;
+    if (!entry) {
+        asphere_radius = -radius;
+    }
     var intersection: vec3<f32>;
-    if (cylindrical) {
+    if (!is_sphere(asphere)) {
+        intersection = intersect_asphere(asphere, ray, position, asphere_radius, cylindrical);
+    } else if (cylindrical) {
         // cylindrical: x is not affected by curvature
 
         // c: center of the lens surface if interpreted as an entire sphere
@@ -196,8 +471,22 @@ fn propagate_element(
 
     ray.o = intersection;
 
+    // light reaching the surface from inside the glass was absorbed on the way
+    if (entry != (ray.d.z > 0.)) {
+        ray.strength = ray.strength * exp(-absorption * distance(self.o, intersection));
+    }
+
+    // the ray hits the surface outside of its clear aperture
+    if (semi_diameter > 0. && length(intersection.xy) > semi_diameter) {
+        ray.d = ray.d * 0.0;
+        ray.strength = 0.;
+        return ray;
+    }
+
     var normal: vec3<f32>;
-    if (cylindrical) {
+    if (!is_sphere(asphere)) {
+        normal = asphere_normal(asphere, ray, intersection, asphere_radius, cylindrical);
+    } else if (cylindrical) {
         var cy: f32;
         if (entry) {
             cy = position + radius;
@@ -238,27 +527,15 @@ fn propagate_element(
 
         ray.d = ray.d - 2.0 * dot(normal, ray.d) * normal;
 
-        var a: f32;
-        if (entry == (ray.d.z > 0.)) {
-            a = ior;
-        } else {
-            a = 1.0;
-        };
-        var b: f32;
-        if (entry == (ray.d.z > 0.)) {
-            b = 1.0;
-        } else {
-            b = ior;
-        }
-
-        ray.strength = ray.strength * fresnel_ar(
-                acos(dot(normalize(d_in), -normal)),
+        ray = polarize(ray, coating_reflectance(
+                acos(clamp(dot(normalize(d_in), -normal), -1., 1.)),
                 ray.wavelength,
-                coating_thickness,
-                b,
-                coating_ior,
-                a,
-            );
+                other_ior,
+                ior,
+                entry == (d_in.z > 0.),
+                coating_start,
+                coating_num,
+            ));
         
     } else {
         var eta: f32;
@@ -277,26 +554,14 @@ fn propagate_element(
             ray.d = eta * ray.d - (eta * dot(normal, ray.d) + sqrt(k)) * normal;
         }
 
-        var a: f32;
-        if (entry == (ray.d.z > 0.)) {
-            a = ior;
-        } else {
-            a = 1.0;
-        };
-        var b: f32;
-        if (entry == (ray.d.z > 0.)) {
-            b = 1.0;
-        } else {
-            b = ior;
-        }
-        ray.strength = ray.strength * (1.0
-            - fresnel_ar(
-                acos(dot(normalize(d_in), -normal)),
+        ray = polarize(ray, vec2<f32>(1., 1.) - coating_reflectance(
+                acos(clamp(dot(normalize(d_in), -normal), -1., 1.)),
                 ray.wavelength,
-                coating_thickness,
-                b,
-                coating_ior,
-                a,
+                other_ior,
+                ior,
+                entry == (d_in.z > 0.),
+                coating_start,
+                coating_num,
             ));
     }
 
@@ -332,9 +597,72 @@ fn clip_ray_poly(self: Ray, pos: f32, num_edge: u32, size: f32) -> bool {
     return clipped;
 }
 
+/// interpolate a spectrum sampled every 50 nm from 380 nm
+fn sample_spectrum(spectrum: array<f32, 9>, wavelength: f32) -> f32 {
+    var samples = spectrum;
+    let x = clamp((wavelength - 0.38) / 0.05, 0., 8.);
+    let i = min(u32(x), u32(7));
+    return mix(samples[i], samples[i + u32(1)], x - f32(i));
+}
+
+/// reflectance of the sensor, stored in b1 to b3_2
+fn sensor_reflectance(self: Element, wavelength: f32) -> f32 {
+    return sample_spectrum(array<f32, 9>(
+        self.b1, self.b2, self.b3, self.c1, self.c2, self.c3, self.b1_2, self.b2_2, self.b3_2
+    ), wavelength);
+}
+
+/// transmittance of a filter plate, stored in b1_2 to c3_2 and k to a6
+fn filter_transmittance(self: Element, wavelength: f32) -> f32 {
+    return sample_spectrum(array<f32, 9>(
+        self.b1_2, self.b2_2, self.b3_2, self.c1_2, self.c2_2, self.c3_2, self.k, self.a4, self.a6
+    ), wavelength);
+}
+
+/// absorption coefficient of the glass at the surface, stored in absorption1 to absorption9
+fn absorption(self: Element, wavelength: f32) -> f32 {
+    return sample_spectrum(array<f32, 9>(
+        self.absorption1, self.absorption2, self.absorption3, self.absorption4, self.absorption5,
+        self.absorption6, self.absorption7, self.absorption8, self.absorption9
+    ), wavelength);
+}
+
+/// like a glass surface, light reaching a face from inside the plate crossed it once
+fn propagate_filter(self: Ray, element: Element, reflect: bool) -> Ray {
+    // 4: front face, 5: back face
+    let entry = element.entry < 4.5;
+    var ray = self;
+    if (entry != (ray.d.z > 0.)) {
+        ray.strength = ray.strength * filter_transmittance(element, ray.wavelength);
+    }
+    return propagate_element(
+        ray,
+        element.radius,
+        ior(element, ray.wavelength),
+        1.,
+        element.position,
+        reflect,
+        entry,
+        false,
+        u32(0),
+        u32(0),
+        Asphere(0., 0., 0., 0., 0., 0., 0., 0.),
+        element.semi_diameter,
+        // the transmittance of the filter already includes its absorption
+        0.,
+    );
+}
+
 /// propagate a ray through an element
 ///
 fn propagate(self: Ray, element: Element) -> Ray {
+    if (element.entry > 3.) {
+        return propagate_filter(self, element, false);
+    }
+    if (element.entry > 2.) {
+        // only rays reflecting at the sensor are changed by it
+        return intersect_ray_to_ray(self, element.position);
+    }
     if (element.entry > 1.) {
         var ray = self;
         // ray.strength = self.strength * f32(u32(clip_ray_poly(self, u32(element.position), element.radius)));
@@ -351,8 +679,11 @@ fn propagate(self: Ray, element: Element) -> Ray {
             false,
             element.entry > 0.,
             !(element.spherical > 0.),
-            element.coating_ior,
-            element.coating_thickness,
+            u32(element.coating_start),
+            u32(element.coating_num),
+            get_asphere(element),
+            element.semi_diameter,
+            absorption(element, self.wavelength),
         );
     }
 }
@@ -360,6 +691,15 @@ fn propagate(self: Ray, element: Element) -> Ray {
 /// reflect a Ray from an element
 ///
 fn reflect_ray(self: Ray, element: Element) -> Ray {
+    if (element.entry > 3.) {
+        return propagate_filter(self, element, true);
+    }
+    if (element.entry > 2.) {
+        var ray = intersect_ray_to_ray(self, element.position);
+        ray.d.z = -ray.d.z;
+        ray.strength = ray.strength * sensor_reflectance(element, ray.wavelength);
+        return ray;
+    }
     return propagate_element(
         self,
         element.radius,
@@ -369,11 +709,106 @@ fn reflect_ray(self: Ray, element: Element) -> Ray {
         true,
         element.entry > 0.,
         !(element.spherical > 0.),
-        element.coating_ior,
-        element.coating_thickness,
+        u32(element.coating_start),
+        u32(element.coating_num),
+        get_asphere(element),
+        element.semi_diameter,
+        absorption(element, self.wavelength),
     );
 }
 
+/// trace `init` through all elements along `ghost`,
+/// the reflections alternate between going backwards and forwards
+fn trace_ghost(init: Ray, ghost: WhichGhost) -> Ray {
+  var ray = init;
+  // the next element to pass
+  var ele = 0;
+  for (var r = u32(0); r < ghost.num; r = r + u32(1)) {
+    let surface = i32(ghostSurfaces.el[ghost.start + r]);
+    if (r % u32(2) == u32(0)) {
+      for (var k = ele; k < surface; k = k + 1) {
+        ray = propagate(ray, elements.el[k]);
+      }
+      ele = surface - 1;
+    } else {
+      for (var k = ele; k > surface; k = k - 1) {
+        ray = propagate(ray, elements.el[k]);
+      }
+      ele = surface + 1;
+    }
+    ray = reflect_ray(ray, elements.el[surface]);
+  }
+  for (var k = ele; k < i32(arrayLength(&elements.el)); k = k + 1) {
+    ray = propagate(ray, elements.el[k]);
+  }
+  return ray;
+}
+
+/// number of propagation and reflection steps of a ray along `ghost`
+fn ghost_steps(ghost: WhichGhost) -> u32 {
+  var steps = ghost.num;
+  var ele = 0;
+  for (var r = u32(0); r < ghost.num; r = r + u32(1)) {
+    let surface = i32(ghostSurfaces.el[ghost.start + r]);
+    if (r % u32(2) == u32(0)) {
+      steps = steps + u32(surface - ele);
+      ele = surface - 1;
+    } else {
+      steps = steps + u32(ele - surface);
+      ele = surface + 1;
+    }
+  }
+  return steps + u32(i32(arrayLength(&elements.el)) - ele);
+}
+
+/// store the `step`th segment of a ray path starting at `offset` in the rays buffer
+fn store_segment(offset: u32, step: u32, before: Ray, after: Ray) {
+  rays.rays[offset + step * u32(2)] = init_ray(before);
+  rays.rays[offset + step * u32(2) + u32(1)] = init_ray(after);
+}
+
+/// like `trace_ghost`, but store all `ghost_steps(ghost) + 1` segments starting at `offset`,
+/// the last one continues the ray behind the lens
+fn trace_ghost_segments(init: Ray, ghost: WhichGhost, offset: u32) {
+  var ray = init;
+  var step = u32(0);
+  // the next element to pass
+  var ele = 0;
+  for (var r = u32(0); r < ghost.num; r = r + u32(1)) {
+    let surface = i32(ghostSurfaces.el[ghost.start + r]);
+    if (r % u32(2) == u32(0)) {
+      for (var k = ele; k < surface; k = k + 1) {
+        let before = ray;
+        ray = propagate(ray, elements.el[k]);
+        store_segment(offset, step, before, ray);
+        step = step + u32(1);
+      }
+      ele = surface - 1;
+    } else {
+      for (var k = ele; k > surface; k = k - 1) {
+        let before = ray;
+        ray = propagate(ray, elements.el[k]);
+        store_segment(offset, step, before, ray);
+        step = step + u32(1);
+      }
+      ele = surface + 1;
+    }
+    let before = ray;
+    ray = reflect_ray(ray, elements.el[surface]);
+    store_segment(offset, step, before, ray);
+    step = step + u32(1);
+  }
+  for (var k = ele; k < i32(arrayLength(&elements.el)); k = k + 1) {
+    let before = ray;
+    ray = propagate(ray, elements.el[k]);
+    store_segment(offset, step, before, ray);
+    step = step + u32(1);
+  }
+  let before = ray;
+  ray.o = ray.o + ray.d * 100.;
+  store_segment(offset, step, before, ray);
+}
+
 [[stage(compute), workgroup_size(64)]]
 fn main([[builtin(global_invocation_id)]] global_invocation_id: vec3<u32>) {
   let draw_mode = u32(params.draw_mode);//u32(1);
@@ -381,13 +816,11 @@ fn main([[builtin(global_invocation_id)]] global_invocation_id: vec3<u32>) {
 
   var num_segments = u32((draw_mode & u32(2)) > u32(0)) * arrayLength(&elements.el) * u32(2) + u32(2);// if normal drawing
   if ((draw_mode & u32(1)) > u32(0)) {
-    var ghost_num = u32(0);
-    for (var i = u32(0); i < arrayLength(&elements.el) - u32(1); i = i + u32(1)) {
-        for (var j = i + u32(1); j < arrayLength(&elements.el); j = j + u32(1)) {
-            ghost_num = ghost_num + u32(1);
-            if ((ghost_num == which_ghost || which_ghost == u32(0)) && elements.el[i].entry < 1.5 && elements.el[j].entry < 1.5) {
-                num_segments = num_segments + (j - i) * u32(2) + u32(2) + arrayLength(&elements.el) * u32(2) + u32(2);
-            }
+    for (var ghost_num = u32(0); ghost_num < arrayLength(&whichGhost.el); ghost_num = ghost_num + u32(1)) {
+        let ghost = whichGhost.el[ghost_num];
+        // the direct path has no reflections
+        if (ghost.num > u32(0)) {
+            num_segments = num_segments + (ghost_steps(ghost) + u32(1)) * u32(2);
         }
     }
   }
@@ -409,77 +842,31 @@ fn main([[builtin(global_invocation_id)]] global_invocation_id: vec3<u32>) {
 
   var counter = u32(0);
   if ((draw_mode & u32(1)) > u32(0)) {
-    var ghost_num = u32(0);
-    for (var i = u32(0); i < arrayLength(&elements.el) - u32(1); i = i + u32(1)) {
-        for (var j = i + u32(1); j < arrayLength(&elements.el); j = j + u32(1)) {
-            if ( elements.el[i].entry < 1.5 && elements.el[j].entry < 1.5 ){
-            ghost_num = ghost_num + u32(1);
-            if (ghost_num == which_ghost || which_ghost == u32(0)) {
-                // make new ray
-                // pos.x = pos.x + (ray_num_x / f32(num_rays) * width - width / 2.);
-                // pos.y = pos.y + (ray_num_y / f32(num_rays) * width - width / 2.);
-                var dir = posParams.init.d;
-                dir.y = dir.y + f32(ray_num) / f32(num_rays) * width - width / 2.;
-                let wave_num = u32(10);
-                let wavelen_ = f32(ray_num % wave_num);
-                let start_wavelen = 0.38;
-                let end_wavelen = 0.78;
-                let wavelength = start_wavelen + wavelen_ * ((end_wavelen - start_wavelen) / f32(wave_num));
-                var ray = Ray(center_pos, wavelength, dir, str_from_wavelen(wavelength));
-
-                for (var ele = u32(0); ele < arrayLength(&elements.el); ele = ele + u32(1)) {
-                    let element = elements.el[ele];
-                    // if we iterated through all elements up to
-                    // the first reflection point
-
-                    if (ele == j) {
-                        // reflect at the first element,
-                        // which is further down the optical path
-                        rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
-                        ray = reflect_ray(ray, element);
-                        rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                        counter = counter + u32(1);
-
-                        // propagate backwards through system
-                        // until the second reflection
-                        for (var k = j - u32(1); k > i; k = k - u32(1)) { // for k in (i + 1..j).rev() {
-                            rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
-                            ray = propagate(ray, elements.el[k]);
-                            rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                            counter = counter + u32(1);
-                        }
-                        rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
-                        ray = reflect_ray(ray, elements.el[i]);
-                        rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                        counter = counter + u32(1);
-
-                        for (var k = i + u32(1); k <= j; k = k + u32(1)) { // for k in i + 1..=j {
-                            rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
-                            ray = propagate(ray, elements.el[k]);
-                            rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                            counter = counter + u32(1);
-                        }
-                        // println!("strength: {}", ray.strength);
-                    } else {
-                        rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
-                        ray = propagate(ray, element);
-                        rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                        counter = counter + u32(1);
-                    }
-                }
-                rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
-                ray.o = ray.o + ray.d * 100.;
-                rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                counter = counter + u32(1);
-
-                // // only return rays that have made it through
-                // if (ray.d.magnitude() > 0.) {
-                //     rays.push(ray_collection);
-                //     rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
-                //     counter = counter + u32(1);
-                // }
-            }
-            }
+    for (var ghost_num = u32(0); ghost_num < arrayLength(&whichGhost.el); ghost_num = ghost_num + u32(1)) {
+        let ghost = whichGhost.el[ghost_num];
+        // the direct path has no reflections
+        if (ghost.num > u32(0)) {
+            // make new ray
+            // pos.x = pos.x + (ray_num_x / f32(num_rays) * width - width / 2.);
+            // pos.y = pos.y + (ray_num_y / f32(num_rays) * width - width / 2.);
+            var dir = posParams.init.d;
+            dir.y = dir.y + f32(ray_num) / f32(num_rays) * width - width / 2.;
+            let wave_num = u32(10);
+            let wavelen_ = f32(ray_num % wave_num);
+            let start_wavelen = 0.38;
+            let end_wavelen = 0.78;
+            let wavelength = start_wavelen + wavelen_ * ((end_wavelen - start_wavelen) / f32(wave_num));
+            var ray = Ray(center_pos, wavelength, dir, str_from_wavelen(wavelength), source_polarization());
+
+            trace_ghost_segments(ray, ghost, ray_num * num_segments + counter * u32(2));
+            counter = counter + ghost_steps(ghost) + u32(1);
+
+            // // only return rays that have made it through
+            // if (ray.d.magnitude() > 0.) {
+            //     rays.push(ray_collection);
+            //     rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = init_ray(ray);
+            //     counter = counter + u32(1);
+            // }
         }
     }
   }
@@ -491,16 +878,16 @@ fn main([[builtin(global_invocation_id)]] global_invocation_id: vec3<u32>) {
     let start_wavelen = 0.38;
     let end_wavelen = 0.78;
     let wavelength = start_wavelen + wavelen * ((end_wavelen - start_wavelen) / f32(wave_num));
-    var ray = Ray(center_pos, wavelength, dir, str_from_wavelen(wavelength));
+    var ray = Ray(center_pos, wavelength, dir, str_from_wavelen(wavelength), source_polarization());
     for (var i: u32 = u32(0); i < arrayLength(&elements.el); i = i + u32(1)) {
         let element = elements.el[i];
-        rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
+        rays.rays[ray_num * num_segments + counter * u32(2)] = init_ray(ray);
         ray = propagate(ray, element);
-        rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
+        rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = init_ray(ray);
         counter = counter + u32(1);
     }
-    rays.rays[ray_num * num_segments + counter * u32(2)] = ray;
+    rays.rays[ray_num * num_segments + counter * u32(2)] = init_ray(ray);
     ray.o = ray.o + ray.d * 10.;
-    rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = ray;
+    rays.rays[ray_num * num_segments + counter * u32(2) + u32(1)] = init_ray(ray);
   }
 }
@@ -574,6 +574,7 @@ impl PolyRes {
         });
 
         self.render_dots(&mut encoder, &self.high_color_tex.view, lens_state, true);
+        lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
 
         // conversion pass
         self.convert(&mut encoder, device, view, lens_state);
@@ -770,6 +771,11 @@ impl PolyRes {
             }
         }
 
+        // the starburst is at the light of the whole beam, not the last tile
+        lens_state.pos_params[4] = old_x;
+        lens_state.pos_params[5] = old_y;
+        lens_state.update(device, queue);
+
         // conversion pass
         {
             // let vertex_buffer_data = [
@@ -786,6 +792,7 @@ impl PolyRes {
             let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+            lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
 
             // create render pass descriptor and its color attachments
             let color_attachments = [wgpu::RenderPassColorAttachment {
@@ -829,9 +836,6 @@ impl PolyRes {
         self.num_dots = num_dots;
         lens_state.pos_params[9] = old_width;
 
-        lens_state.pos_params[4] = old_x;
-        lens_state.pos_params[5] = old_y;
-
         lens_state.opacity = opacity;
         lens_state.update(device, queue);
         self.update_dots(device, queue, true, lens_state);
@@ -30,11 +30,31 @@ struct Element {
   c1_2: f32;
   c2_2: f32;
   c3_2: f32;
-  coating_ior: f32;
-  coating_thickness: f32;
+  coating_start: f32;// index of the first layer in Coatings
+  coating_num: f32;// number of coating layers
   position: f32;// num_blades if aperture
-  entry: f32;// 0: false, 1: true, 2: aperture
+  entry: f32;// 0: false, 1: true, 2: aperture, 3: sensor, 4/5: filter front/back
   spherical: f32;// 0: false, 1: true
+  k: f32;// conic constant
+  a4: f32;// even asphere coefficients
+  a6: f32;
+  a8: f32;
+  a10: f32;
+  a12: f32;
+  a14: f32;
+  a16: f32;
+  semi_diameter: f32;// radius of the clear aperture, 0: unlimited
+  absorption1: f32;// absorption coefficient of the glass every 50 nm from 380 nm
+  absorption2: f32;
+  absorption3: f32;
+  absorption4: f32;
+  absorption5: f32;
+  absorption6: f32;
+  absorption7: f32;
+  absorption8: f32;
+  absorption9: f32;
+  formula: f32;// dispersion formula of the glass, 0: Sellmeier, 1: Schott, 2: Herzberger, 3: Conrady
+  formula_2: f32;// dispersion formula of the outer ior
 };
 
 struct SimParams {
@@ -77,7 +97,7 @@ struct Sensor {
 
 /// all the Elements of the Lens under test
 struct Elements {
-  el: [[stride(72)]] array<Element>;
+  el: [[stride(152)]] array<Element>;
 };
 
 [[group(0), binding(0)]] var<storage, read> elements : Elements;
@@ -86,9 +106,11 @@ struct Elements {
 [[group(1), binding(1)]] var<storage, read> sensor : Sensor;
 [[group(1), binding(0)]] var<uniform> posParams : PosParams;
 
+/// response of the sensor at wavelength in µm, the LUT has a sample every 10nm
 fn lookup_rgb(wavelength: f32) -> vec3<f32> {
-    let lower_index = u32(clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 34.));
-    let factor = (wavelength % 0.1) * 10.;
+    let x = clamp((wavelength - sensor.measuremens[0].wavelength / 1000.) * 100., 0., 40.);
+    let lower_index = min(u32(x), u32(39));
+    let factor = x - f32(lower_index);
     return sensor.measuremens[lower_index].rgb * (1. - factor)
      + sensor.measuremens[lower_index + u32(1)].rgb * (factor);
 }
@@ -136,7 +158,7 @@ fn isNan( val: f32 ) -> bool {
 fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
   for (var i = u32(0); i < arrayLength(&elements.el); i = i + u32(1)) {
       let element = elements.el[i];
-      if (element.entry > 1.) {
+      if (element.entry > 1. && element.entry < 3.) {
           if (clip_ray_poly(in.aperture_pos, u32(element.b1), element.radius)) {
               return vec4<f32>(0., 0., 0., 0.);
           }
@@ -926,6 +926,7 @@ impl PolyTri {
                     .into(),
             );
 
+            lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
             self.convert(device, &mut encoder, view, lens_state);
 
             queue.submit(Some(encoder.finish()));
@@ -954,6 +955,7 @@ impl PolyTri {
                 panic!("Failed to copy ray buffer!")
             }
         } else {
+            lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
             self.convert(device, &mut encoder, view, lens_state);
             queue.submit(iter::once(encoder.finish()));
         }
@@ -1012,6 +1014,7 @@ impl PolyTri {
             label: Some("Render Encoder"),
         });
 
+        lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
         self.convert(device, &mut encoder, view, lens_state);
         queue.submit(iter::once(encoder.finish()));
 
@@ -1033,6 +1036,7 @@ impl PolyTri {
 
         self.render_dots(&self.high_color_tex.view, &mut encoder, lens_state, true, 1);
 
+        lens_state.render_starburst(&mut encoder, &self.high_color_tex.view);
         self.convert(device, &mut encoder, view, lens_state);
 
         queue.submit(iter::once(encoder.finish()));
@@ -0,0 +1,34 @@
+struct StarburstParams {
+  center: vec2<f32>;
+  half_size: f32;
+  intensity: f32;
+  size: f32;
+  reference: f32;
+  longest: f32;
+  padding: f32;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+};
+
+[[group(0), binding(0)]] var t_starburst: texture_2d<f32>;
+[[group(0), binding(1)]] var s_starburst: sampler;
+[[group(0), binding(2)]] var<uniform> starburst : StarburstParams;
+
+/// a quad around the light, drawn as a triangle strip of 4 vertices
+[[stage(vertex)]]
+fn mainv([[builtin(vertex_index)]] index: u32) -> VertexOutput {
+    let corner = vec2<f32>(f32(index % u32(2)), f32(index / u32(2))) * 2. - 1.;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(starburst.center + corner * starburst.half_size, 0., 1.);
+    out.uv = corner * 0.5 + 0.5;
+    return out;
+}
+
+[[stage(fragment)]]
+fn mainf(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let rgb = textureSample(t_starburst, s_starburst, in.uv).rgb;
+    return vec4<f32>(rgb, 0.);
+}
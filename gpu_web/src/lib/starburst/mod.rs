@@ -0,0 +1,392 @@
+use cgmath::Vector3;
+use polynomial_optics::{Lens, Starburst, STARBURST_PADDING, STARBURST_REFERENCE};
+use wgpu::util::DeviceExt;
+use wgpu::{BindGroup, BindGroupLayout, Buffer, ComputePipeline, Device, Queue, RenderPipeline};
+
+use crate::texture::Texture;
+
+/// side length of the diffraction pattern and the texture it is rendered to
+const STARBURST_SIZE: usize = 256;
+/// longest wavelength in µm, one texel of the texture is one bin of it
+const LONGEST_WAVELENGTH: f64 = 0.78;
+/// length of one unit of the lens in µm, the GUI works in cm
+const LENS_UNIT: f64 = 1e4;
+
+/// ## Diffraction spikes of the aperture around the light
+/// The pattern of the aperture polygon is calculated on the CPU by `polynomial_optics::Starburst`,
+/// a compute pass weights it with the spectrum of the light and the sensor response and
+/// a quad adds it to the flare at the image of the light.
+pub struct GpuStarburst {
+    pub enabled: bool,
+    /// brightness of the starburst relative to the pattern peak
+    pub intensity: f32,
+    /// the real pattern is only a few µm wide, it is drawn this much larger
+    pub scale: f32,
+    /// blades of the aperture the pattern was calculated for
+    num_blades: Option<u32>,
+    /// the light is imaged on the sensor and the pattern can be drawn
+    visible: bool,
+    pattern_buffer: Buffer,
+    /// ```
+    /// struct StarburstParams {
+    ///   0:center_x: f32;
+    ///   1:center_y: f32;
+    ///   2:half_size: f32;
+    ///   3:intensity: f32;
+    ///   4:size: f32;
+    ///   5:reference: f32;
+    ///   6:longest: f32;
+    ///   7:padding: f32;
+    /// };
+    /// ```
+    params: [f32; 8],
+    params_buffer: Buffer,
+    texture: Texture,
+    compute_bind_group_layout: BindGroupLayout,
+    compute_bind_group: BindGroup,
+    compute_pipeline: ComputePipeline,
+    composite_bind_group: BindGroup,
+    composite_pipeline: RenderPipeline,
+}
+
+impl GpuStarburst {
+    pub fn new(
+        device: &Device,
+        params_bind_group_layout: &BindGroupLayout,
+        lens_bind_group_layout: &BindGroupLayout,
+    ) -> Self {
+        // replaced by the pattern of the aperture in `update`
+        let pattern_buffer = Self::pattern_buffer(device, &[0.; 4]);
+        let params = [
+            0.,
+            0.,
+            0.,
+            1.,
+            STARBURST_SIZE as f32,
+            STARBURST_REFERENCE as f32,
+            LONGEST_WAVELENGTH as f32,
+            STARBURST_PADDING as f32,
+        ];
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Starburst Params Buffer"),
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let texture = Texture::create_storage_texture(
+            device,
+            STARBURST_SIZE as u32,
+            wgpu::TextureFormat::Rgba16Float,
+            "starburst",
+        );
+
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::StorageTexture {
+                            access: wgpu::StorageTextureAccess::WriteOnly,
+                            format: wgpu::TextureFormat::Rgba16Float,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("starburst compute layout"),
+            });
+        let compute_bind_group = Self::compute_bind_group(
+            device,
+            &compute_bind_group_layout,
+            &pattern_buffer,
+            &texture,
+            &params_buffer,
+        );
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("starburst compute"),
+                bind_group_layouts: &[
+                    &compute_bind_group_layout,
+                    params_bind_group_layout,
+                    lens_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let compute_shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("starburst compute"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("compute.wgsl").into()),
+        });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("starburst compute"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader,
+            entry_point: "main",
+        });
+
+        let composite_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("starburst composite layout"),
+            });
+        let composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &composite_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("starburst composite"),
+        });
+        let composite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("starburst composite"),
+                bind_group_layouts: &[&composite_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let composite_shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("starburst composite"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("composite.wgsl").into()),
+        });
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("starburst composite"),
+            layout: Some(&composite_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &composite_shader,
+                entry_point: "mainv",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &composite_shader,
+                entry_point: "mainf",
+                targets: &[wgpu::ColorTargetState {
+                    // the format of the high_color_tex of the scenes
+                    format: wgpu::TextureFormat::Rgba16Float,
+                    // added to the flare, the alpha is left as it is
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+                unclipped_depth: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            enabled: false,
+            intensity: 1.,
+            scale: 100.,
+            num_blades: None,
+            visible: false,
+            pattern_buffer,
+            params,
+            params_buffer,
+            texture,
+            compute_bind_group_layout,
+            compute_bind_group,
+            compute_pipeline,
+            composite_bind_group,
+            composite_pipeline,
+        }
+    }
+
+    fn pattern_buffer(device: &Device, pattern: &[f32]) -> Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Starburst Pattern Buffer"),
+            contents: bytemuck::cast_slice(pattern),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        })
+    }
+
+    fn compute_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        pattern_buffer: &Buffer,
+        texture: &Texture,
+        params_buffer: &Buffer,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: pattern_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("starburst compute"),
+        })
+    }
+
+    /// Recalculate the pattern if the aperture changed and place it at the image of a light
+    /// far away in `direction`, `zoom` is the zoom of the draw shaders.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        lens: &Lens,
+        direction: Vector3<f64>,
+        zoom: f32,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let num_blades = lens.num_blades();
+        if num_blades != self.num_blades {
+            if let Some(num_blades) = num_blades {
+                let starburst = Starburst::new(num_blades, STARBURST_SIZE);
+                let pattern = starburst
+                    .pattern
+                    .iter()
+                    .map(|value| *value as f32)
+                    .collect::<Vec<_>>();
+                self.pattern_buffer = Self::pattern_buffer(device, &pattern);
+                self.compute_bind_group = Self::compute_bind_group(
+                    device,
+                    &self.compute_bind_group_layout,
+                    &self.pattern_buffer,
+                    &self.texture,
+                    &self.params_buffer,
+                );
+            }
+            self.num_blades = num_blades;
+        }
+
+        let position = lens.light_position(direction, STARBURST_REFERENCE);
+        let bin_size = lens.starburst_bin_size(LONGEST_WAVELENGTH, LENS_UNIT);
+        self.visible = false;
+        if let (Some(position), Some(bin_size)) = (position, bin_size) {
+            // like the draw shaders place the rays
+            self.params[0] = position[0] as f32 / 16. * zoom;
+            self.params[1] = position[1] as f32 / 16. * zoom;
+            self.params[2] =
+                (STARBURST_SIZE as f64 / 2. * bin_size) as f32 * self.scale / 16. * zoom;
+            self.visible = self.params[2].is_finite();
+        }
+        self.params[3] = self.intensity;
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&self.params));
+    }
+
+    /// add the starburst to the flare in `view`
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        params_bind_group: &BindGroup,
+        lens_bind_group: &BindGroup,
+    ) {
+        if !self.enabled || !self.visible {
+            return;
+        }
+        {
+            let mut cpass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+            cpass.set_pipeline(&self.compute_pipeline);
+            cpass.set_bind_group(0, &self.compute_bind_group, &[]);
+            cpass.set_bind_group(1, params_bind_group, &[]);
+            cpass.set_bind_group(2, lens_bind_group, &[]);
+            let work_group_count = (STARBURST_SIZE as u32 + 8 - 1) / 8; // round up
+            cpass.dispatch(work_group_count, work_group_count, 1);
+        }
+
+        let color_attachments = [wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: true,
+            },
+        }];
+        let render_pass_descriptor = wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &color_attachments,
+            depth_stencil_attachment: None,
+        };
+        let mut rpass = encoder.begin_render_pass(&render_pass_descriptor);
+        rpass.set_pipeline(&self.composite_pipeline);
+        rpass.set_bind_group(0, &self.composite_bind_group, &[]);
+        rpass.draw(0..4, 0..1);
+    }
+}
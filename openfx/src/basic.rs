@@ -45,6 +45,12 @@ struct MyInstanceData {
     lens_param: ParamHandle<Double>,
     entry_param: ParamHandle<Double>,
     width_param: ParamHandle<Double>,
+    lens_zoom_param: ParamHandle<Double>,
+    light_param: ParamHandle<Double>,
+    temperature_param: ParamHandle<Double>,
+    sensor_param: ParamHandle<Double>,
+    response_param: ParamHandle<Double>,
+    colour_space_param: ParamHandle<Double>,
     // gpu: Arc<Mutex<Gpu>>,
 }
 
@@ -159,6 +165,12 @@ const PARAM_Z_NAME: &str = "z";
 const PARAM_LENS_NAME: &str = "lens";
 const PARAM_ENTRY_NAME: &str = "entry";
 const PARAM_WIDTH_NAME: &str = "width";
+const PARAM_LENS_ZOOM_NAME: &str = "lens_zoom";
+const PARAM_LIGHT_NAME: &str = "light";
+const PARAM_TEMPERATURE_NAME: &str = "temperature";
+const PARAM_SENSOR_NAME: &str = "sensor";
+const PARAM_RESPONSE_NAME: &str = "response";
+const PARAM_COLOUR_SPACE_NAME: &str = "colour_space";
 
 impl Execute for SimplePlugin {
     #[allow(clippy::float_cmp)]
@@ -369,6 +381,12 @@ impl Execute for SimplePlugin {
                 let lens_param = param_set.parameter(PARAM_LENS_NAME)?;
                 let entry_param = param_set.parameter(PARAM_ENTRY_NAME)?;
                 let width_param = param_set.parameter(PARAM_WIDTH_NAME)?;
+                let lens_zoom_param = param_set.parameter(PARAM_LENS_ZOOM_NAME)?;
+                let light_param = param_set.parameter(PARAM_LIGHT_NAME)?;
+                let temperature_param = param_set.parameter(PARAM_TEMPERATURE_NAME)?;
+                let sensor_param = param_set.parameter(PARAM_SENSOR_NAME)?;
+                let response_param = param_set.parameter(PARAM_RESPONSE_NAME)?;
+                let colour_space_param = param_set.parameter(PARAM_COLOUR_SPACE_NAME)?;
 
                 let data = MyInstanceData {
                     is_general_effect,
@@ -387,6 +405,12 @@ impl Execute for SimplePlugin {
                     lens_param,
                     entry_param,
                     width_param,
+                    lens_zoom_param,
+                    light_param,
+                    temperature_param,
+                    sensor_param,
+                    response_param,
+                    colour_space_param,
                     // gpu: self.gpu.clone(),
                 };
                 // let mut gpu = self.gpu.lock().unwrap();
@@ -593,6 +617,67 @@ impl Execute for SimplePlugin {
                     5.,
                 )?;
 
+                define_scale_param(
+                    &mut param_set,
+                    PARAM_LENS_ZOOM_NAME,
+                    PARAM_LENS_ZOOM_NAME,
+                    PARAM_LENS_ZOOM_NAME,
+                    "Zoom position of zoom lenses, 0 to 1",
+                    None,
+                    0.,
+                    1.,
+                )?;
+
+                define_scale_param(
+                    &mut param_set,
+                    PARAM_LIGHT_NAME,
+                    PARAM_LIGHT_NAME,
+                    PARAM_LIGHT_NAME,
+                    "Which light source spectrum to use",
+                    None,
+                    0.,
+                    gpu::lens_state::LensState::get_all_light_sources().len() as f64 - 1.,
+                )?;
+
+                // a scale param would start at 1 K
+                let mut param_props = param_set.param_define_double(PARAM_TEMPERATURE_NAME)?;
+                param_props.set_label(PARAM_TEMPERATURE_NAME)?;
+                param_props.set_default(3000.)?;
+                param_props.set_display_min(1000.)?;
+                param_props.set_display_max(25000.)?;
+                param_props.set_hint(
+                    "Colour temperature in K of the custom blackbody and daylight sources",
+                )?;
+                param_props.set_script_name(PARAM_TEMPERATURE_NAME)?;
+
+                define_scale_param(
+                    &mut param_set,
+                    PARAM_SENSOR_NAME,
+                    PARAM_SENSOR_NAME,
+                    PARAM_SENSOR_NAME,
+                    "Which camera sensor response to use",
+                    None,
+                    0.,
+                    gpu::lens_state::LensState::get_all_sensors().len() as f64 - 1.,
+                )?;
+
+                // scale params default to 1, the colour params start at the first choice
+                let mut param_props = param_set.param_define_double(PARAM_RESPONSE_NAME)?;
+                param_props.set_label(PARAM_RESPONSE_NAME)?;
+                param_props.set_default(0.)?;
+                param_props.set_display_min(0.)?;
+                param_props.set_display_max(1.)?;
+                param_props.set_hint("0 for the CIE 1931 observer, 1 for the camera sensor")?;
+                param_props.set_script_name(PARAM_RESPONSE_NAME)?;
+
+                let mut param_props = param_set.param_define_double(PARAM_COLOUR_SPACE_NAME)?;
+                param_props.set_label(PARAM_COLOUR_SPACE_NAME)?;
+                param_props.set_default(0.)?;
+                param_props.set_display_min(0.)?;
+                param_props.set_display_max(2.)?;
+                param_props.set_hint("Output colour space: linear sRGB, Rec.2020 or ACEScg")?;
+                param_props.set_script_name(PARAM_COLOUR_SPACE_NAME)?;
+
                 param_set
                     .param_define_page(PARAM_MAIN_NAME)?
                     .set_children(&[
@@ -608,6 +693,12 @@ impl Execute for SimplePlugin {
                         PARAM_LENS_NAME,
                         PARAM_ENTRY_NAME,
                         PARAM_WIDTH_NAME,
+                        PARAM_LENS_ZOOM_NAME,
+                        PARAM_LIGHT_NAME,
+                        PARAM_TEMPERATURE_NAME,
+                        PARAM_SENSOR_NAME,
+                        PARAM_RESPONSE_NAME,
+                        PARAM_COLOUR_SPACE_NAME,
                     ])?;
                 OK
             }
@@ -665,7 +756,7 @@ impl Execute for SimplePlugin {
 // }
 
 impl MyInstanceData {
-    fn get_data(&self, time: Time) -> Result<(f64, f64, f64, f64, f64, usize, f64, f64, f64, usize, f64, f64)> {
+    fn get_data(&self, time: Time) -> Result<(f64, f64, f64, f64, f64, usize, f64, f64, f64, usize, f64, f64, f64, usize, f64, usize, bool, usize)> {
         Ok((
             self.dots_exponent.get_value_at_time(time)?,
             self.num_wavelengths.get_value_at_time(time)?,
@@ -679,6 +770,12 @@ impl MyInstanceData {
             self.lens_param.get_value_at_time(time)? as usize,
             self.entry_param.get_value_at_time(time)?,
             self.width_param.get_value_at_time(time)?,
+            self.lens_zoom_param.get_value_at_time(time)?,
+            self.light_param.get_value_at_time(time)? as usize,
+            self.temperature_param.get_value_at_time(time)?,
+            self.sensor_param.get_value_at_time(time)? as usize,
+            self.response_param.get_value_at_time(time)? < 0.5,
+            self.colour_space_param.get_value_at_time(time)? as usize,
         ))
     }
 }
@@ -160,6 +160,12 @@ impl Gpu {
                 usize,
                 f64,
                 f64,
+                f64,
+                usize,
+                f64,
+                usize,
+                bool,
+                usize,
             ),
             ofx::Error,
         >,
@@ -182,6 +188,12 @@ impl Gpu {
             lens_index,
             entry_radius,
             width,
+            lens_zoom,
+            light_index,
+            temperature,
+            sensor_index,
+            cie_observer,
+            colour_space,
         ) = parameters.unwrap();
         // println!(
         //     "dots_exponent: {}, num_wavelengths: {}, opacity: {}, zoom_fact: {}, scale_fact: {}, triangulate: {}, pos_x_param: {}, pos_y_param: {}, pos_z_param: {}",
@@ -197,6 +209,11 @@ impl Gpu {
         // );
 
         self.lens_ui.set_lens(lens_index);
+        self.lens_ui.set_zoom(lens_zoom);
+        self.lens_ui.set_light(light_index, temperature);
+        self.lens_ui.set_sensor(sensor_index, &self.state.queue);
+        self.lens_ui
+            .set_colour(cie_observer, colour_space, &self.state.queue);
 
         self.poly_res.num_dots = 10.0_f64.powf(self.lens_ui.dots_exponent) as u32;
         self.poly_tri.dot_side_len = 10.0_f64.powf(self.lens_ui.dots_exponent).sqrt() as u32;
@@ -137,10 +137,12 @@ fn main() {
         radius,
         properties: Properties::Glass(Glass {
             sellmeier: Sellmeier::bk7(),
-            coating: QuarterWaveCoating::none(), //optimal(1.5, 1.0, 0.5),
+            coating: CoatingStack::none(), //optimal(1.5, 1.0, 0.5),
             entry: true,
             outer_ior: Sellmeier::air(),
             spherical: true,
+            asphere: Asphere::sphere(),
+            semi_diameter: None,
         }),
         position: -2.0,
     };
@@ -149,10 +151,12 @@ fn main() {
         radius,
         properties: Properties::Glass(Glass {
             sellmeier: Sellmeier::bk7(),
-            coating: QuarterWaveCoating::none(), //optimal(1.5, 1.0, 0.5),
+            coating: CoatingStack::none(), //optimal(1.5, 1.0, 0.5),
             entry: false,
             outer_ior: Sellmeier::air(),
             spherical: true,
+            asphere: Asphere::sphere(),
+            semi_diameter: None,
         }),
         position: lens_exit_pos,
     };
@@ -0,0 +1,102 @@
+use crate::{DispersionFormula, Sellmeier};
+
+/// wavelength of the helium d-line in µm
+pub const D_LINE: f64 = 0.5875618;
+/// wavelength of the hydrogen F-line in µm
+pub const F_LINE: f64 = 0.4861327;
+/// wavelength of the hydrogen C-line in µm
+pub const C_LINE: f64 = 0.6562725;
+/// wavelength of the mercury g-line in µm
+pub const G_LINE: f64 = 0.4358343;
+
+impl Sellmeier {
+    /// A glass with refractive index `nd` and abbe number `vd`, like patents list them.
+    ///
+    /// `partial_dispersion` is P_g,F = (n_g - n_F) / (n_F - n_C), without it the glass is put
+    /// on the normal line of Schott's K7 and F2. The curve uses the Conrady formula.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let glass = Sellmeier::from_abbe(1.5168, 64.17, None);
+    /// assert!((glass.ior(D_LINE) - 1.5168).abs() < 1e-9);
+    /// assert!((glass.abbe_number() - 64.17).abs() < 1e-6);
+    /// // close to the real thing
+    /// for wavelength in [0.4, 0.5, 0.6, 0.7] {
+    ///     assert!((glass.ior(wavelength) - Sellmeier::bk7().ior(wavelength)).abs() < 5e-4);
+    /// }
+    /// ```
+    pub fn from_abbe(nd: f64, vd: f64, partial_dispersion: Option<f64>) -> Self {
+        let partial_dispersion = partial_dispersion.unwrap_or(0.6438 - 0.001682 * vd);
+        let dispersion = (nd - 1.) / vd;
+
+        // n(λ) = n0 + A / λ + B / λ^3.5 is linear in the coefficients
+        let basis = |wavelength: f64| [1., 1. / wavelength, wavelength.powf(-3.5)];
+        let difference = |a: [f64; 3], b: [f64; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        let rows = [
+            basis(D_LINE),
+            difference(basis(F_LINE), basis(C_LINE)),
+            difference(basis(G_LINE), basis(F_LINE)),
+        ];
+        let b = solve3(rows, [nd, dispersion, partial_dispersion * dispersion]);
+
+        Self {
+            b,
+            formula: DispersionFormula::Conrady,
+            ..Self::air()
+        }
+    }
+
+    /// refractive index at the d-line
+    pub fn nd(&self) -> f64 {
+        self.ior(D_LINE)
+    }
+
+    /// abbe number Vd = (nd - 1) / (nF - nC), infinite for glass without dispersion
+    pub fn abbe_number(&self) -> f64 {
+        (self.nd() - 1.) / (self.ior(F_LINE) - self.ior(C_LINE))
+    }
+
+    /// relative partial dispersion P_g,F = (n_g - n_F) / (n_F - n_C)
+    pub fn partial_dispersion(&self) -> f64 {
+        (self.ior(G_LINE) - self.ior(F_LINE)) / (self.ior(F_LINE) - self.ior(C_LINE))
+    }
+
+    /// Index of the glass of `glasses` closest to `nd` and `vd` on the abbe diagram,
+    /// a difference of 0.01 in nd counts as much as 1 in vd.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let glasses = vec![
+    ///     (String::from("BK7"), Sellmeier::bk7()),
+    ///     (String::from("SF6"), Sellmeier::from_abbe(1.80518, 25.43, None)),
+    /// ];
+    /// assert_eq!(Sellmeier::nearest_glass(1.80610, 25.4, &glasses), Some(1));
+    /// ```
+    pub fn nearest_glass(nd: f64, vd: f64, glasses: &[(String, Sellmeier)]) -> Option<usize> {
+        glasses
+            .iter()
+            .map(|(_name, glass)| ((glass.nd() - nd) * 100.).hypot(glass.abbe_number() - vd))
+            .enumerate()
+            // glasses without dispersion have no place on the diagram
+            .filter(|(_index, distance)| distance.is_finite())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _distance)| index)
+    }
+}
+
+/// solve `rows` x = `rhs` with Cramer's rule
+pub(crate) fn solve3(rows: [[f64; 3]; 3], rhs: [f64; 3]) -> [f64; 3] {
+    let det = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let det_rows = det(rows);
+    let mut x = [0.; 3];
+    for (column, x) in x.iter_mut().enumerate() {
+        let mut replaced = rows;
+        for (row, value) in replaced.iter_mut().zip(rhs) {
+            row[column] = value;
+        }
+        *x = det(replaced) / det_rows;
+    }
+    x
+}
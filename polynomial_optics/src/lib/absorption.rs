@@ -0,0 +1,85 @@
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Sellmeier, Spectrum, SPECTRUM_SAMPLES, SPECTRUM_START, SPECTRUM_STEP};
+
+/// smallest transmittance used, so fully opaque glass still has a finite absorption coefficient
+const MIN_TRANSMITTANCE: f64 = 1e-9;
+
+/// ## Internal transmittance of a glass, like vendor catalogs list it
+/// `tau` is the fraction of light left after travelling `thickness` through the glass,
+/// the reflections at its surfaces are not included.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct InternalTransmittance {
+    pub tau: Spectrum,
+    /// thickness `tau` was measured at in the units of the lens, catalogs usually use 10 mm
+    pub thickness: f64,
+}
+
+impl Hash for InternalTransmittance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tau.hash(state);
+        self.thickness.to_bits().hash(state);
+    }
+}
+
+impl InternalTransmittance {
+    /// absorption coefficient at `wavelength` in µm, per unit of length
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let transmittance = InternalTransmittance {
+    ///     tau: Spectrum::constant(0.9),
+    ///     thickness: 10.,
+    /// };
+    /// // twice as thick, the loss compounds
+    /// let twice = (-transmittance.absorption(0.5) * 20.).exp();
+    /// assert!((twice - 0.81).abs() < 1e-9);
+    /// ```
+    pub fn absorption(&self, wavelength: f64) -> f64 {
+        -self.tau.at(wavelength).max(MIN_TRANSMITTANCE).ln() / self.thickness
+    }
+
+    /// the same transmittance for lengths multiplied by `scale`, like `Prescription::to_lens` does
+    pub fn scaled(self, scale: f64) -> Self {
+        Self {
+            thickness: self.thickness * scale,
+            ..self
+        }
+    }
+}
+
+impl Sellmeier {
+    /// the glass with `tau` of the light left after `thickness` of it
+    pub fn with_transmittance(self, tau: Spectrum, thickness: f64) -> Self {
+        Self {
+            transmittance: Some(InternalTransmittance { tau, thickness }),
+            ..self
+        }
+    }
+
+    /// absorption coefficient at `wavelength` in µm, 0 for glass without transmittance data
+    pub fn absorption(&self, wavelength: f64) -> f64 {
+        self.transmittance
+            .map_or(0., |transmittance| transmittance.absorption(wavelength))
+    }
+
+    /// the absorption coefficient at the wavelengths of the samples of a `Spectrum`
+    pub fn absorption_samples(&self) -> [f64; SPECTRUM_SAMPLES] {
+        let mut samples = [0.; SPECTRUM_SAMPLES];
+        for (i, sample) in samples.iter_mut().enumerate() {
+            *sample = self.absorption(SPECTRUM_START + i as f64 * SPECTRUM_STEP);
+        }
+        samples
+    }
+
+    /// the same glass for lengths multiplied by `scale`
+    pub fn scaled(self, scale: f64) -> Self {
+        Self {
+            transmittance: self
+                .transmittance
+                .map(|transmittance| transmittance.scaled(scale)),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::hash::{Hash, Hasher};
+
+use cgmath::{InnerSpace, Vector3};
+use serde::{Deserialize, Serialize};
+
+/// maximum number of newton steps for intersecting a ray with an asphere
+const ASPHERE_ITERATIONS: usize = 16;
+
+/// ## Even asphere: conic plus polynomial in h²
+/// The sag of a surface with vertex at 0 and (standard sign) radius r is
+/// ```text
+/// z(h) = h² / r / (1 + sqrt(1 - (1 + k) h² / r²)) + a4 h⁴ + a6 h⁶ + ... + a16 h¹⁶
+/// ```
+/// where h is the distance to the optical axis, or only the y component for cylindrical surfaces.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct Asphere {
+    /// conic constant: 0 sphere, -1 paraboloid, < -1 hyperboloid
+    pub k: f64,
+    /// coefficients a4, a6, ..., a16
+    pub a: [f64; 7],
+}
+
+impl Hash for Asphere {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.k.to_bits().hash(state);
+        for a in self.a {
+            a.to_bits().hash(state);
+        }
+    }
+}
+
+impl Asphere {
+    /// no deviation from the sphere
+    pub fn sphere() -> Self {
+        Self::default()
+    }
+
+    pub fn is_sphere(&self) -> bool {
+        self.k == 0. && self.a.iter().all(|a| *a == 0.)
+    }
+
+    /// get the sag at `h_sq` = h² and its derivative by h²
+    /// ```
+    /// # use polynomial_optics::*;
+    /// // a sphere with radius 2: z = r - sqrt(r² - h²)
+    /// let (sag, _) = Asphere::sphere().sag(2., 1.);
+    /// assert!((sag - (2. - 3_f64.sqrt())).abs() < 1e-12);
+    /// // a paraboloid: z = h² / 2r
+    /// let parabola = Asphere { k: -1., a: [0.; 7] };
+    /// assert_eq!(parabola.sag(2., 9.), (9. / 4., 1. / 4.));
+    /// ```
+    pub fn sag(&self, radius: f64, h_sq: f64) -> (f64, f64) {
+        let c = 1. / radius;
+        let root = (1. - (1. + self.k) * c * c * h_sq).sqrt();
+        let mut sag = c * h_sq / (1. + root);
+        let mut deriv = c / (2. * root);
+
+        // h⁴ is the first polynomial term
+        let mut h_pow = h_sq;
+        for (i, a) in self.a.iter().enumerate() {
+            let exp = i as f64 + 2.;
+            deriv += exp * a * h_pow;
+            h_pow *= h_sq;
+            sag += a * h_pow;
+        }
+        (sag, deriv)
+    }
+
+    fn h_sq(p: Vector3<f64>, cylindrical: bool) -> f64 {
+        if cylindrical {
+            p.y * p.y
+        } else {
+            p.x * p.x + p.y * p.y
+        }
+    }
+
+    /// intersect the ray `o + t * d` with the surface with its vertex at `position`,
+    /// returns the intersection and the normal facing against `d`
+    pub fn intersect(
+        &self,
+        o: Vector3<f64>,
+        d: Vector3<f64>,
+        position: f64,
+        radius: f64,
+        cylindrical: bool,
+    ) -> (Vector3<f64>, Vector3<f64>) {
+        // start at the plane of the vertex
+        let mut t = (position - o.z) / d.z;
+        for _ in 0..ASPHERE_ITERATIONS {
+            let p = o + d * t;
+            let (sag, deriv) = self.sag(radius, Self::h_sq(p, cylindrical));
+            let dh_sq = if cylindrical {
+                2. * p.y * d.y
+            } else {
+                2. * (p.x * d.x + p.y * d.y)
+            };
+            let f = p.z - position - sag;
+            t -= f / (d.z - deriv * dh_sq);
+            if f.abs() < 1e-12 {
+                break;
+            }
+        }
+
+        let intersection = o + d * t;
+        let (_sag, deriv) = self.sag(radius, Self::h_sq(intersection, cylindrical));
+        let normal = Vector3::new(
+            if cylindrical {
+                0.
+            } else {
+                -2. * deriv * intersection.x
+            },
+            -2. * deriv * intersection.y,
+            1.,
+        )
+        .normalize();
+
+        if normal.dot(d) > 0. {
+            (intersection, -normal)
+        } else {
+            (intersection, normal)
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Sellmeier, Spectrum};
+
+/// reference thickness in mm the internal transmittance of catalog glasses is converted to
+const CATALOG_THICKNESS: f64 = 10.;
+
+/// ## Formula the coefficients of a `Sellmeier` are used in
+/// The values are the ones the shaders use. `b` holds the first three coefficients in the
+/// order the catalogs list them and `c` the next three, except for `Sellmeier` itself.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum DispersionFormula {
+    /// n² = 1 + Σ b_i λ² / (λ² - c_i)
+    Sellmeier = 0,
+    /// n² = A0 + A1 λ² + A2 λ⁻² + A3 λ⁻⁴ + A4 λ⁻⁶ + A5 λ⁻⁸
+    Schott = 1,
+    /// n = A + B L + C L² + D λ² + E λ⁴ + F λ⁶ with L = 1 / (λ² - 0.028)
+    Herzberger = 2,
+    /// n = n0 + A / λ + B / λ^3.5
+    Conrady = 3,
+}
+
+impl Default for DispersionFormula {
+    fn default() -> Self {
+        Self::Sellmeier
+    }
+}
+
+impl DispersionFormula {
+    /// the formula of a Zemax AGF formula number, `None` for unsupported ones
+    pub fn from_agf(number: u32) -> Option<Self> {
+        match number {
+            1 => Some(Self::Schott),
+            2 => Some(Self::Sellmeier),
+            3 => Some(Self::Herzberger),
+            5 => Some(Self::Conrady),
+            _ => None,
+        }
+    }
+
+    /// only other formulas are written to lens files, old ones and coating layers stay readable
+    pub fn is_sellmeier(&self) -> bool {
+        *self == Self::Sellmeier
+    }
+
+    /// refractive index at `wavelength` in µm
+    pub fn ior(&self, b: &[f64; 3], c: &[f64; 3], wavelength: f64) -> f64 {
+        let wavelength_sq = wavelength * wavelength;
+        match self {
+            Self::Sellmeier => {
+                let mut n_sq = 1.;
+                for i in 0..3 {
+                    n_sq += (b[i] * wavelength_sq) / (wavelength_sq - c[i]);
+                }
+                n_sq.sqrt()
+            }
+            Self::Schott => {
+                let inv_sq = 1. / wavelength_sq;
+                (b[0]
+                    + b[1] * wavelength_sq
+                    + inv_sq * (b[2] + inv_sq * (c[0] + inv_sq * (c[1] + inv_sq * c[2]))))
+                    .sqrt()
+            }
+            Self::Herzberger => {
+                let l = 1. / (wavelength_sq - 0.028);
+                b[0] + b[1] * l
+                    + b[2] * l * l
+                    + wavelength_sq * (c[0] + wavelength_sq * (c[1] + wavelength_sq * c[2]))
+            }
+            Self::Conrady => b[0] + b[1] / wavelength + b[2] / wavelength.powf(3.5),
+        }
+    }
+}
+
+impl Sellmeier {
+    /// Reads a glass catalog, either a Zemax `.agf` or a `.csv` file.
+    /// The glasses are named `Catalog:Name` after the file name.
+    pub fn read_catalog(path: &Path) -> Result<Vec<(String, Sellmeier)>, String> {
+        let catalog = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or(format!("no catalog name in {:?}", path))?;
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) => return Err(format!("problem reading file: {}", err)),
+        };
+        let str = decode_catalog(&bytes);
+        let glasses = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("agf") => Self::parse_agf(&str)?,
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::parse_csv_catalog(&str)?,
+            _ => return Err(format!("unknown catalog format {:?}", path)),
+        };
+        Ok(glasses
+            .into_iter()
+            .map(|(name, glass)| (format!("{}:{}", catalog, name), glass))
+            .collect())
+    }
+
+    /// Parses a Zemax AGF catalog.
+    /// Glasses with a dispersion formula other than Schott, Sellmeier 1, Herzberger or Conrady
+    /// are skipped. `IT` lines become the internal transmittance at 10 mm.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let glasses = Sellmeier::parse_agf(
+    ///     "CC Example catalog
+    ///      NM N-BK7 2 517642.251 1.5168 64.17 0 0 0
+    ///      CD 1.03961212 6.00069867e-3 0.231792344 2.00179144e-2 1.01046945 103.560653 0 0 0 0
+    ///      IT 0.4 0.994 25
+    ///      IT 0.7 0.998 25
+    ///      NM BK7 1 517642.251 1.5168 64.17 0 1 0
+    ///      CD 2.2718929 -1.0108077E-02 1.0592509E-02 2.0816965E-04 -7.6472538E-06 4.9240991E-07",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(glasses.len(), 2);
+    /// assert_eq!(glasses[0].1.b, Sellmeier::bk7().b);
+    /// assert!((glasses[1].1.ior(0.5875618) - 1.5168).abs() < 1e-6);
+    /// // converted from 25 mm to 10 mm
+    /// let transmittance = glasses[0].1.transmittance.unwrap();
+    /// assert!((transmittance.tau.at(0.38) - 0.994_f64.powf(0.4)).abs() < 1e-9);
+    /// ```
+    pub fn parse_agf(str: &str) -> Result<Vec<(String, Sellmeier)>, String> {
+        let mut glasses = vec![];
+        // the glass being read: name, formula and (wavelength in µm, transmittance at 10 mm)
+        let mut current: Option<(String, Option<Sellmeier>, Vec<(f64, f64)>)> = None;
+
+        let mut finish = |current: Option<(String, Option<Sellmeier>, Vec<(f64, f64)>)>| {
+            if let Some((name, Some(glass), transmittance)) = current {
+                glasses.push((name, with_catalog_transmittance(glass, transmittance)));
+            }
+        };
+
+        for (line_num, line) in str.lines().enumerate() {
+            let line_num = line_num + 1;
+            let mut fields = line.split_whitespace();
+            let numbers = |fields: std::str::SplitWhitespace| {
+                fields
+                    .map(|field| field.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("line {}: {}", line_num, err))
+            };
+            match fields.next() {
+                Some("NM") => {
+                    finish(current.take());
+                    let name = fields
+                        .next()
+                        .ok_or(format!("line {}: glass without a name", line_num))?;
+                    let formula = fields
+                        .next()
+                        .and_then(|formula| formula.parse::<f64>().ok())
+                        .and_then(|formula| DispersionFormula::from_agf(formula as u32));
+                    current = Some((
+                        name.to_string(),
+                        formula.map(|formula| Sellmeier {
+                            formula,
+                            ..Sellmeier::air()
+                        }),
+                        vec![],
+                    ));
+                }
+                Some("CD") => {
+                    let coefficients = numbers(fields)?;
+                    if let Some((_, Some(glass), _)) = &mut current {
+                        let coefficient = |i: usize| coefficients.get(i).copied().unwrap_or(0.);
+                        if glass.formula == DispersionFormula::Sellmeier {
+                            // K1 L1 K2 L2 K3 L3
+                            glass.b = [coefficient(0), coefficient(2), coefficient(4)];
+                            glass.c = [coefficient(1), coefficient(3), coefficient(5)];
+                        } else {
+                            glass.b = [coefficient(0), coefficient(1), coefficient(2)];
+                            glass.c = [coefficient(3), coefficient(4), coefficient(5)];
+                        }
+                    }
+                }
+                Some("IT") => {
+                    if let (Some((_, _, transmittance)), [wavelength, tau, thickness, ..]) =
+                        (&mut current, numbers(fields)?.as_slice())
+                    {
+                        if *thickness > 0. {
+                            transmittance
+                                .push((*wavelength, tau.powf(CATALOG_THICKNESS / thickness)));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        finish(current);
+
+        Ok(glasses)
+    }
+
+    /// Parses a CSV catalog with the name in the first column and
+    /// the Sellmeier coefficients in columns named `B1` to `C3`.
+    pub fn parse_csv_catalog(str: &str) -> Result<Vec<(String, Sellmeier)>, String> {
+        let mut rdr = csv::ReaderBuilder::new().from_reader(str.as_bytes());
+        let headers = match rdr.headers() {
+            Ok(headers) => headers.clone(),
+            Err(err) => return Err(format!("problem reading header: {}", err)),
+        };
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name))
+                .ok_or(format!("no column {:?}", name))
+        };
+        let b_columns = [column("B1")?, column("B2")?, column("B3")?];
+        let c_columns = [column("C1")?, column("C2")?, column("C3")?];
+
+        let mut glasses = vec![];
+        for (line_num, record) in rdr.records().flatten().enumerate() {
+            let coefficient = |column: usize| {
+                record
+                    .get(column)
+                    .and_then(|field| field.trim().parse::<f64>().ok())
+                    .ok_or(format!(
+                        "line {}: could not parse column {}",
+                        line_num + 2,
+                        column + 1
+                    ))
+            };
+            let mut glass = Sellmeier::air();
+            for i in 0..3 {
+                glass.b[i] = coefficient(b_columns[i])?;
+                glass.c[i] = coefficient(c_columns[i])?;
+            }
+            glasses.push((record[0].trim().to_string(), glass));
+        }
+        Ok(glasses)
+    }
+}
+
+/// `glass` with the internal transmittance measured at `CATALOG_THICKNESS`, if there is any
+fn with_catalog_transmittance(glass: Sellmeier, mut measurements: Vec<(f64, f64)>) -> Sellmeier {
+    measurements.sort_by(|a, b| a.0.total_cmp(&b.0));
+    measurements.dedup_by(|a, b| a.0 == b.0);
+    for (_, tau) in &mut measurements {
+        *tau = tau.clamp(0., 1.);
+    }
+    match Spectrum::from_measurements(&measurements) {
+        Ok(tau) => glass.with_transmittance(tau, CATALOG_THICKNESS),
+        Err(_) => glass,
+    }
+}
+
+/// Zemax writes catalogs as UTF-16 or as some 8 bit encoding
+fn decode_catalog(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        [0xFE, 0xFF, rest @ ..] => {
+            let units = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
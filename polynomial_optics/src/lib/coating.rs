@@ -0,0 +1,305 @@
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+
+use num::complex::Complex64;
+use serde::{Deserialize, Serialize};
+
+use crate::{DispersionFormula, Lens, Properties, QuarterWaveCoating, Sellmeier};
+
+/// maximum number of layers of a `CoatingStack`, keeps `Glass` `Copy`
+pub const MAX_COATING_LAYERS: usize = 8;
+
+/// ## One thin film of a coating
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CoatingLayer {
+    pub material: Sellmeier,
+    /// physical thickness in µm
+    pub thickness: f64,
+}
+
+impl Hash for CoatingLayer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.material.hash(state);
+        self.thickness.to_bits().hash(state);
+    }
+}
+
+impl CoatingLayer {
+    /// a layer of `material` that is a quarter wave thick at `lambda0`
+    pub fn quarter_wave(material: Sellmeier, lambda0: f64) -> Self {
+        Self {
+            material,
+            thickness: lambda0 / 4. / material.ior(lambda0),
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            material: Sellmeier::air(),
+            thickness: 0.,
+        }
+    }
+}
+
+/// ## Multi-layer thin-film coating of a surface
+/// The layers are ordered from the outer medium (`Glass.outer_ior`) towards the glass.
+/// The reflectance is calculated with the characteristic matrix method, see
+/// Macleod: Thin-Film Optical Filters, chapter 2.
+///
+/// In lens files the stack is a list of layers, the old single layer
+/// `(thickness: .., ior: ..)` of `QuarterWaveCoating` is still accepted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Hash)]
+#[serde(try_from = "CoatingRepr", into = "Vec<CoatingLayer>")]
+pub struct CoatingStack {
+    layers: [CoatingLayer; MAX_COATING_LAYERS],
+    num_layers: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CoatingRepr {
+    Layers(Vec<CoatingLayer>),
+    QuarterWave(QuarterWaveCoating),
+}
+
+impl TryFrom<CoatingRepr> for CoatingStack {
+    type Error = String;
+
+    fn try_from(repr: CoatingRepr) -> Result<Self, Self::Error> {
+        match repr {
+            CoatingRepr::Layers(layers) => Self::new(&layers),
+            CoatingRepr::QuarterWave(coating) => Ok(coating.into()),
+        }
+    }
+}
+
+impl From<CoatingStack> for Vec<CoatingLayer> {
+    fn from(stack: CoatingStack) -> Self {
+        stack.layers().to_vec()
+    }
+}
+
+impl From<QuarterWaveCoating> for CoatingStack {
+    /// a single layer with a constant refractive index
+    fn from(coating: QuarterWaveCoating) -> Self {
+        if coating.thickness > 0. {
+            Self::new(&[CoatingLayer {
+                material: Sellmeier::constant(coating.ior),
+                thickness: coating.thickness,
+            }])
+            .unwrap()
+        } else {
+            Self::none()
+        }
+    }
+}
+
+impl CoatingStack {
+    /// an uncoated surface
+    pub fn none() -> Self {
+        Self {
+            layers: [CoatingLayer::empty(); MAX_COATING_LAYERS],
+            num_layers: 0,
+        }
+    }
+
+    /// the layers from the outer medium towards the glass
+    pub fn new(layers: &[CoatingLayer]) -> Result<Self, String> {
+        if layers.len() > MAX_COATING_LAYERS {
+            return Err(format!(
+                "coating has {} layers, at most {} are supported",
+                layers.len(),
+                MAX_COATING_LAYERS
+            ));
+        }
+        let mut stack = Self::none();
+        stack.layers[..layers.len()].copy_from_slice(layers);
+        stack.num_layers = layers.len();
+        Ok(stack)
+    }
+
+    /// quarter-wave layers of `materials` at `lambda0`, from the outer medium towards the glass
+    pub fn quarter_wave(materials: &[Sellmeier], lambda0: f64) -> Result<Self, String> {
+        Self::new(
+            &materials
+                .iter()
+                .map(|material| CoatingLayer::quarter_wave(*material, lambda0))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// the classic quarter-half-quarter broadband AR coating centered at `lambda0`:
+    /// MgF₂ λ/4, ZrO₂ λ/2, Al₂O₃ λ/4
+    pub fn broadband(lambda0: f64) -> Self {
+        let mut half_wave = CoatingLayer::quarter_wave(Sellmeier::zirconia(), lambda0);
+        half_wave.thickness *= 2.;
+        Self::new(&[
+            CoatingLayer::quarter_wave(Sellmeier::mgf2(), lambda0),
+            half_wave,
+            CoatingLayer::quarter_wave(Sellmeier::sapphire(), lambda0),
+        ])
+        .unwrap()
+    }
+
+    pub fn layers(&self) -> &[CoatingLayer] {
+        &self.layers[..self.num_layers]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_layers == 0
+    }
+
+    /// Reflectance [s, p] of the coated interface between the outer medium with index `n_outer`
+    /// and the glass with index `n_glass`, for light at `lambda` in µm hitting it at `theta0`
+    /// from the outside (`from_outside`) or from within the glass.
+    ///
+    /// Without layers these are the Fresnel equations:
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let [s, p] = CoatingStack::none().reflectance(0., 0.5, 1., 1.5, true);
+    /// assert!((s - 0.04).abs() < 1e-12 && (p - 0.04).abs() < 1e-12);
+    /// // total internal reflection
+    /// let [s, p] = CoatingStack::none().reflectance(1., 0.5, 1., 1.5, false);
+    /// assert!((s - 1.).abs() < 1e-12 && (p - 1.).abs() < 1e-12);
+    /// // a quarter wave of index sqrt(1.5) cancels the reflection at the design wavelength
+    /// let coating = CoatingStack::quarter_wave(&[Sellmeier::constant(1.5_f64.sqrt())], 0.5).unwrap();
+    /// assert!(coating.reflectance(0., 0.5, 1., 1.5, true)[0] < 1e-12);
+    /// assert!(coating.reflectance(0., 0.5, 1., 1.5, false)[0] < 1e-12);
+    /// ```
+    pub fn reflectance(
+        &self,
+        theta0: f64,
+        lambda: f64,
+        n_outer: f64,
+        n_glass: f64,
+        from_outside: bool,
+    ) -> [f64; 2] {
+        let (n0, n_sub) = if from_outside {
+            (n_outer, n_glass)
+        } else {
+            (n_glass, n_outer)
+        };
+        // n sin(theta) is the same in every layer
+        let n_sin = Complex64::from(n0 * theta0.sin());
+        let cos = |n: Complex64| (Complex64::from(1.) - (n_sin / n) * (n_sin / n)).sqrt();
+
+        // the layers in the order the light passes them
+        let layers: Vec<_> = if from_outside {
+            self.layers().iter().collect()
+        } else {
+            self.layers().iter().rev().collect()
+        };
+
+        let mut reflectance = [0.; 2];
+        for (p, reflectance) in reflectance.iter_mut().enumerate() {
+            // tilted optical admittance for s and p polarization
+            let admittance = |n: Complex64| if p == 0 { n * cos(n) } else { n / cos(n) };
+
+            // [b, c] = M_1 * ... * M_q * [1, eta_sub]
+            let mut b = Complex64::from(1.);
+            let mut c = admittance(Complex64::from(n_sub));
+            for layer in layers.iter().rev() {
+                let n = Complex64::from(layer.material.ior(lambda));
+                let eta = admittance(n);
+                let delta = 2. * std::f64::consts::PI * n * layer.thickness * cos(n) / lambda;
+                let i = Complex64::i();
+                let (b_new, c_new) = (
+                    delta.cos() * b + i * delta.sin() / eta * c,
+                    i * eta * delta.sin() * b + delta.cos() * c,
+                );
+                b = b_new;
+                c = c_new;
+            }
+
+            let eta0 = admittance(Complex64::from(n0));
+            *reflectance = ((eta0 * b - c) / (eta0 * b + c)).norm_sqr().min(1.);
+        }
+        reflectance
+    }
+
+    /// reflectance for unpolarized light, the average of s and p of `CoatingStack::reflectance`
+    pub fn fresnel_ar(
+        &self,
+        theta0: f64,
+        lambda: f64,
+        n_outer: f64,
+        n_glass: f64,
+        from_outside: bool,
+    ) -> f64 {
+        let [s, p] = self.reflectance(theta0, lambda, n_outer, n_glass, from_outside);
+        (s + p) / 2.
+    }
+}
+
+impl Sellmeier {
+    /// a material with the same refractive index `n` at every wavelength
+    pub fn constant(n: f64) -> Self {
+        Self {
+            b: [n * n - 1., 0., 0.],
+            c: [0., 0., 0.],
+            transmittance: None,
+            formula: DispersionFormula::Sellmeier,
+        }
+    }
+
+    /// magnesium fluoride (ordinary ray), Dodge 1984
+    pub fn mgf2() -> Self {
+        Self {
+            b: [0.48755108, 0.39875031, 2.3120353],
+            c: [1.882178e-3, 8.951888e-3, 566.13559],
+            transmittance: None,
+            formula: DispersionFormula::Sellmeier,
+        }
+    }
+
+    /// aluminium oxide / sapphire (ordinary ray), Malitson 1962
+    pub fn sapphire() -> Self {
+        Self {
+            b: [1.4313493, 0.65054713, 5.3414021],
+            c: [5.279926e-3, 1.423827e-2, 325.01783],
+            transmittance: None,
+            formula: DispersionFormula::Sellmeier,
+        }
+    }
+
+    /// zirconium dioxide (cubic), Wood and Nassau 1982
+    pub fn zirconia() -> Self {
+        Self {
+            b: [1.347091, 2.117788, 9.452943],
+            c: [3.911622e-3, 2.780188e-2, 591.49012],
+            transmittance: None,
+            formula: DispersionFormula::Sellmeier,
+        }
+    }
+}
+
+impl Lens {
+    /// get the layers of all coatings in form:
+    /// ```text
+    /// struct CoatingLayer {
+    ///   b1: f32; b2: f32; b3: f32;
+    ///   c1: f32; c2: f32; c3: f32;
+    ///   thickness: f32;
+    ///   formula: f32; // `DispersionFormula` of the material
+    /// };
+    /// ```
+    /// the layers of each surface start at `coating_start` of its entry in
+    /// `Lens::get_rt_elements_buffer`, there is always at least one (unused) layer.
+    pub fn get_coatings_buffer(&self) -> Vec<f32> {
+        let mut layers = vec![];
+        for element in &self.elements {
+            if let Properties::Glass(glass) = element.properties {
+                for layer in glass.coating.layers() {
+                    layers.extend(layer.material.b.iter().map(|b| *b as f32));
+                    layers.extend(layer.material.c.iter().map(|c| *c as f32));
+                    layers.push(layer.thickness as f32);
+                    layers.push(layer.material.formula as i32 as f32);
+                }
+            }
+        }
+        if layers.is_empty() {
+            layers.extend([0_f32; 8]);
+        }
+        layers
+    }
+}
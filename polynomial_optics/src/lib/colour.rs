@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use crate::abbe::solve3;
+use crate::{SensorResponse, SENSOR_SAMPLES, SENSOR_START, SENSOR_STEP};
+
+/// wavelength of the first row of `CIE_1931` in µm
+const CIE_START: f64 = 0.38;
+/// distance between the rows of `CIE_1931` in µm
+const CIE_STEP: f64 = 0.01;
+/// CIE 1931 2° colour matching functions x̄, ȳ, z̄ from 380 nm to 780 nm
+#[rustfmt::skip]
+const CIE_1931: [[f64; 3]; 41] = [
+    [0.001368, 0.000039, 0.006450], [0.004243, 0.000120, 0.020050],
+    [0.014310, 0.000396, 0.067850], [0.043510, 0.001210, 0.207400],
+    [0.134380, 0.004000, 0.645600], [0.283900, 0.011600, 1.385600],
+    [0.348280, 0.023000, 1.747060], [0.336200, 0.038000, 1.772110],
+    [0.290800, 0.060000, 1.669200], [0.195360, 0.090980, 1.287640],
+    [0.095640, 0.139020, 0.812950], [0.032010, 0.208020, 0.465180],
+    [0.004900, 0.323000, 0.272000], [0.009300, 0.503000, 0.158200],
+    [0.063270, 0.710000, 0.078250], [0.165500, 0.862000, 0.042160],
+    [0.290400, 0.954000, 0.020300], [0.433450, 0.994950, 0.008750],
+    [0.594500, 0.995000, 0.003900], [0.762100, 0.952000, 0.002100],
+    [0.916300, 0.870000, 0.001650], [1.026300, 0.757000, 0.001100],
+    [1.062200, 0.631000, 0.000800], [1.002600, 0.503000, 0.000340],
+    [0.854450, 0.381000, 0.000190], [0.642400, 0.265000, 0.000050],
+    [0.447900, 0.175000, 0.000020], [0.283500, 0.107000, 0.000000],
+    [0.164900, 0.061000, 0.000000], [0.087400, 0.032000, 0.000000],
+    [0.046770, 0.017000, 0.000000], [0.022700, 0.008210, 0.000000],
+    [0.011359, 0.004102, 0.000000], [0.005790, 0.002091, 0.000000],
+    [0.002899, 0.001047, 0.000000], [0.001440, 0.000520, 0.000000],
+    [0.000690, 0.000249, 0.000000], [0.000332, 0.000120, 0.000000],
+    [0.000166, 0.000060, 0.000000], [0.000083, 0.000030, 0.000000],
+    [0.000042, 0.000015, 0.000000],
+];
+
+/// the CIE 1931 standard observer at `wavelength` in µm, 0 outside of 380 nm to 780 nm
+/// ```
+/// # use polynomial_optics::*;
+/// let xyz = cie_xyz(0.555);
+/// assert!((xyz[1] - 1.).abs() < 0.01);
+/// assert_eq!(cie_xyz(0.8), [0.; 3]);
+/// ```
+pub fn cie_xyz(wavelength: f64) -> [f64; 3] {
+    let x = (wavelength - CIE_START) / CIE_STEP;
+    if !(0. ..=40.).contains(&x) {
+        return [0.; 3];
+    }
+    let i = (x as usize).min(39);
+    let t = x - i as f64;
+    [0, 1, 2].map(|c| CIE_1931[i][c] * (1. - t) + CIE_1931[i + 1][c] * t)
+}
+
+/// ## Linear RGB colour space the flare is output in
+/// The primaries are used as they are, white points are not adapted to each other.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ColourSpace {
+    /// Rec.709 primaries and D65 white
+    LinearSrgb,
+    /// Rec.2020 primaries and D65 white
+    Rec2020,
+    /// ACES AP1 primaries and the ACES white
+    AcesCg,
+}
+
+impl Default for ColourSpace {
+    fn default() -> Self {
+        Self::LinearSrgb
+    }
+}
+
+impl ColourSpace {
+    /// all colour spaces with their names
+    pub fn get_all_colour_spaces() -> Vec<(String, ColourSpace)> {
+        vec![
+            ("linear sRGB".into(), Self::LinearSrgb),
+            ("Rec.2020".into(), Self::Rec2020),
+            ("ACEScg".into(), Self::AcesCg),
+        ]
+    }
+
+    /// matrix from CIE XYZ to the colour space, row-major
+    pub fn from_xyz(&self) -> [[f64; 3]; 3] {
+        match self {
+            Self::LinearSrgb => [
+                [3.2404542, -1.5371385, -0.4985314],
+                [-0.9692660, 1.8760108, 0.0415560],
+                [0.0556434, -0.2040259, 1.0572252],
+            ],
+            Self::Rec2020 => [
+                [1.7166512, -0.3556708, -0.2533663],
+                [-0.6666844, 1.6164812, 0.0157685],
+                [0.0176399, -0.0427706, 0.9421031],
+            ],
+            Self::AcesCg => [
+                [1.6410234, -0.3248033, -0.2364247],
+                [-0.6636629, 1.6153316, 0.0167563],
+                [0.0117219, -0.0082844, 0.9883949],
+            ],
+        }
+    }
+}
+
+/// ## How the wavelengths of the rays become colours
+/// Each wavelength is weighted with a response, the CIE 1931 observer or a camera,
+/// and the sum is turned into `output` by a 3×3 matrix.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ColourPipeline {
+    /// camera the flare is seen by, `None` for the CIE 1931 observer
+    pub sensor: Option<SensorResponse>,
+    /// colour space of the output, linear
+    pub output: ColourSpace,
+}
+
+impl ColourPipeline {
+    /// response to light of `wavelength` in µm, CIE XYZ or the camera RGB
+    pub fn response(&self, wavelength: f64) -> [f64; 3] {
+        match &self.sensor {
+            Some(sensor) => sensor.rgb(wavelength),
+            None => cie_xyz(wavelength),
+        }
+    }
+
+    /// Matrix from the response to `output`, row-major.
+    ///
+    /// Camera RGB is taken to XYZ by the matrix that fits the camera response best to the
+    /// colour matching functions in the least squares sense.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// // a camera seeing like the standard observer is no camera at all
+    /// let measurements = (0..=40)
+    ///     .map(|i| 0.38 + i as f64 * 0.01)
+    ///     .map(|wavelength| (wavelength, cie_xyz(wavelength)))
+    ///     .collect();
+    /// let pipeline = ColourPipeline {
+    ///     sensor: Some(SensorResponse::from_measurements(measurements).unwrap()),
+    ///     output: ColourSpace::Rec2020,
+    /// };
+    /// let expected = ColourSpace::Rec2020.from_xyz();
+    /// for (row, expected) in pipeline.get_matrix().iter().zip(expected) {
+    ///     for (value, expected) in row.iter().zip(expected) {
+    ///         assert!((value - expected).abs() < 1e-6);
+    ///     }
+    /// }
+    /// ```
+    pub fn get_matrix(&self) -> [[f64; 3]; 3] {
+        let from_xyz = self.output.from_xyz();
+        let to_xyz = match &self.sensor {
+            Some(sensor) => camera_to_xyz(sensor),
+            None => return from_xyz,
+        };
+        let mut matrix = [[0.; 3]; 3];
+        for (row, from_xyz) in matrix.iter_mut().zip(from_xyz) {
+            for (column, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| from_xyz[k] * to_xyz[k][column]).sum();
+            }
+        }
+        matrix
+    }
+
+    /// `get_matrix` as the `mat3x3<f32>` of the convert shaders, columns padded to 4 values
+    pub fn get_matrix_buffer(&self) -> [f32; 12] {
+        let matrix = self.get_matrix();
+        let mut buffer = [0.; 12];
+        for (column, values) in buffer.chunks_exact_mut(4).enumerate() {
+            for (row, value) in values.iter_mut().take(3).enumerate() {
+                *value = matrix[row][column] as f32;
+            }
+        }
+        buffer
+    }
+
+    /// `response` every 10nm from 380nm to 780nm in the format of `SensorResponse::get_lut`,
+    /// the draw shaders weight the rays with it
+    pub fn get_lut(&self) -> Vec<f32> {
+        let mut lut = Vec::with_capacity(4 * SENSOR_SAMPLES);
+        for i in 0..SENSOR_SAMPLES {
+            let wavelength = SENSOR_START + i as f64 * SENSOR_STEP;
+            lut.extend(self.response(wavelength).iter().map(|value| *value as f32));
+            lut.push((wavelength * 1000.).round() as f32);
+        }
+        lut
+    }
+
+    /// linear colour of light of `wavelength` in µm in `output`
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let pipeline = ColourPipeline::default();
+    /// let red = pipeline.rgb(0.65);
+    /// assert!(red[0] > 0. && red[1] < 0.);
+    /// let green = pipeline.rgb(0.53);
+    /// assert!(green[1] > green[0] && green[1] > green[2]);
+    /// ```
+    pub fn rgb(&self, wavelength: f64) -> [f64; 3] {
+        let matrix = self.get_matrix();
+        let response = self.response(wavelength);
+        matrix.map(|row| (0..3).map(|i| row[i] * response[i]).sum())
+    }
+
+    /// Colours of `wavelengths` in µm to draw with, out of gamut colours clipped to `output`.
+    /// The brightest channel of all visible wavelengths is scaled to 1 and
+    /// the values are encoded with the sRGB transfer function.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let colours = ColourPipeline::default().get_colours8(&[0.45, 0.53, 0.61, 0.9]);
+    /// assert!(colours[0][2] > colours[0][0]);
+    /// assert!(colours[1][1] > colours[1][2]);
+    /// assert!(colours[2][0] > colours[2][1]);
+    /// assert_eq!(colours[3], [0; 3]);
+    /// ```
+    pub fn get_colours8(&self, wavelengths: &[f64]) -> Vec<[u8; 3]> {
+        let clipped = |wavelength: f64| self.rgb(wavelength).map(|value| value.max(0.));
+        let peak = (0..SENSOR_SAMPLES)
+            .flat_map(|i| clipped(SENSOR_START + i as f64 * SENSOR_STEP))
+            .fold(0., f64::max);
+        wavelengths
+            .iter()
+            .map(|wavelength| {
+                clipped(*wavelength).map(|value| {
+                    let value = if peak > 0. { value / peak } else { 0. };
+                    let encoded = if value <= 0.0031308 {
+                        12.92 * value
+                    } else {
+                        1.055 * value.powf(1. / 2.4) - 0.055
+                    };
+                    (encoded * 255.).round() as u8
+                })
+            })
+            .collect()
+    }
+}
+
+/// matrix from camera RGB to XYZ fitted to the CIE 1931 observer, row-major
+fn camera_to_xyz(sensor: &SensorResponse) -> [[f64; 3]; 3] {
+    // M = (Σ xyz rgbᵀ) (Σ rgb rgbᵀ)⁻¹, the rows of M solve the normal equations
+    let mut rgb_rgb = [[0.; 3]; 3];
+    let mut xyz_rgb = [[0.; 3]; 3];
+    for (i, xyz) in CIE_1931.iter().enumerate() {
+        let rgb = sensor.rgb(CIE_START + i as f64 * CIE_STEP);
+        for row in 0..3 {
+            for column in 0..3 {
+                rgb_rgb[row][column] += rgb[row] * rgb[column];
+                xyz_rgb[row][column] += xyz[row] * rgb[column];
+            }
+        }
+    }
+    // rgb_rgb is symmetric
+    xyz_rgb.map(|row| solve3(rgb_rgb, row))
+}
@@ -0,0 +1,86 @@
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Asphere, CoatingStack, Element, Glass, Properties, Sellmeier, Spectrum, FLAT_RADIUS};
+
+/// ## One face of a plane-parallel filter plate, like an ND, IR-cut or colour filter
+/// Both faces reflect like uncoated glass. Light loses the transmittance once
+/// each time it crosses the plate, so it is applied when light reaches a face from inside.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Filter {
+    pub material: Sellmeier,
+    /// fraction of the light that makes it through the plate once
+    pub transmittance: Spectrum,
+    /// whether this is the front face
+    pub entry: bool,
+    /// radius of the clear aperture, rays hitting the plate further out are stopped
+    #[serde(default)]
+    pub semi_diameter: Option<f64>,
+}
+
+impl Hash for Filter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.material.hash(state);
+        self.transmittance.hash(state);
+        self.entry.hash(state);
+        self.semi_diameter.map(f64::to_bits).hash(state);
+    }
+}
+
+impl Filter {
+    /// the two faces of a plate of `material` that is `thickness` thick with its front at `position`
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let nd = Filter::plate(Sellmeier::bk7(), Spectrum::constant(0.25), 0., 2., None);
+    /// let lens = Lens::new(nd.to_vec(), 10.);
+    /// // both faces reflect
+    /// assert_eq!(lens.get_ghosts_indicies(1, 0), vec![[0, 1]]);
+    ///
+    /// let ray = Ray::new(
+    ///     cgmath::Vector3::new(0., 0., -1.),
+    ///     cgmath::Vector3::new(0., 0., 1.),
+    ///     [0.; 4],
+    ///     0.55,
+    /// );
+    /// let ray = lens.trace_ray(ray, 0, 0);
+    /// assert_eq!(ray.status, RayStatus::Ok);
+    /// // a quarter of the light makes it through, minus what the faces reflect
+    /// assert!(ray.strength < 0.25 && ray.strength > 0.2);
+    /// ```
+    pub fn plate(
+        material: Sellmeier,
+        transmittance: Spectrum,
+        position: f64,
+        thickness: f64,
+        semi_diameter: Option<f64>,
+    ) -> [Element; 2] {
+        let face = |entry, position| Element {
+            radius: if entry { FLAT_RADIUS } else { -FLAT_RADIUS },
+            properties: Properties::Filter(Filter {
+                material,
+                transmittance,
+                entry,
+                semi_diameter,
+            }),
+            position,
+        };
+        [face(true, position), face(false, position + thickness)]
+    }
+
+    /// the glass of the face, without the absorption of the plate
+    pub fn glass(&self) -> Glass {
+        Glass {
+            sellmeier: Sellmeier {
+                transmittance: None,
+                ..self.material
+            },
+            coating: CoatingStack::none(),
+            entry: self.entry,
+            outer_ior: Sellmeier::air(),
+            spherical: true,
+            asphere: Asphere::sphere(),
+            semi_diameter: self.semi_diameter,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::{Lens, Ray, RayStatus};
+
+/// number of rays along each side of the pupil grid traced by `Lens::rms_focus`
+const FOCUS_GRID: usize = 16;
+
+/// What "in focus" means for `Lens::focus`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FocusMode {
+    /// the paraxial image of the object point
+    Paraxial,
+    /// the plane with the smallest RMS spot of real rays through the entrance pupil
+    RmsSpot,
+}
+
+impl Lens {
+    /// z position of the paraxial image of an on-axis point `object_dist` in front of the first
+    /// surface, `f64::INFINITY` for objects at infinity.
+    pub fn paraxial_focus(&self, object_dist: f64, wavelength: f64) -> Result<f64, String> {
+        let paraxial = self.paraxial(wavelength)?;
+        if object_dist.is_infinite() {
+            return Ok(paraxial.back_focal_point);
+        }
+
+        let first = self.elements[0].position;
+        let last = self.elements.last().unwrap().position;
+        let abcd = self.get_abcd(
+            0..self.elements.len(),
+            first - object_dist,
+            last,
+            wavelength,
+        );
+        // a ray from the object point crosses the axis again at the image
+        if abcd.d.abs() < f64::EPSILON {
+            return Err(String::from("image is at infinity"));
+        }
+        Ok(last - abcd.b / abcd.d)
+    }
+
+    /// z position with the smallest RMS spot for an on-axis point `object_dist` in front of the first
+    /// surface, `f64::INFINITY` for objects at infinity.
+    ///
+    /// Traces a grid of real rays filling the entrance pupil with `Lens::trace_ray`, rays that get
+    /// clipped or totally reflected are ignored.
+    pub fn rms_focus(&self, object_dist: f64, wavelength: f64) -> Result<f64, String> {
+        let pupil = self
+            .paraxial(wavelength)?
+            .entrance_pupil
+            .ok_or("lens has no aperture")?;
+        let first = self.elements[0].position;
+        // start in front of the first surface, even if it is concave
+        let start = first - self.elements[0].radius.abs().min(object_dist) - 1.;
+
+        // the spot in x and y at z is a + b * z for each ray
+        let mut a = vec![];
+        let mut b = vec![];
+        for i in 0..FOCUS_GRID {
+            for j in 0..FOCUS_GRID {
+                let x = (i as f64 + 0.5) / FOCUS_GRID as f64 * 2. - 1.;
+                let y = (j as f64 + 0.5) / FOCUS_GRID as f64 * 2. - 1.;
+                if x * x + y * y > 1. {
+                    continue;
+                }
+                let target = Vector3::new(x * pupil.radius, y * pupil.radius, pupil.position);
+                let (o, d) = if object_dist.is_infinite() {
+                    (
+                        Vector3::new(target.x, target.y, start),
+                        Vector3::new(0., 0., 1.),
+                    )
+                } else {
+                    let object = Vector3::new(0., 0., first - object_dist);
+                    let d = (target - object).normalize();
+                    // move the origin to the start plane
+                    (object + d * ((start - object.z) / d.z).max(0.), d)
+                };
+
+                let ray = self.trace_ray(Ray::new(o, d, [x, y, 0., 0.], wavelength), 0, 0);
+                if ray.status != RayStatus::Ok || ray.d.z <= 0. {
+                    continue;
+                }
+                for (o, d) in [(ray.o.x, ray.d.x), (ray.o.y, ray.d.y)] {
+                    b.push(d / ray.d.z);
+                    a.push(o - d / ray.d.z * ray.o.z);
+                }
+            }
+        }
+        if a.len() < 4 {
+            return Err(String::from("not enough rays made it through the lens"));
+        }
+
+        // minimize the spread around the centroid, sum((a - a_mean) + (b - b_mean) * z)^2
+        let a_mean = a.iter().sum::<f64>() / a.len() as f64;
+        let b_mean = b.iter().sum::<f64>() / b.len() as f64;
+        let (mut ab, mut bb) = (0., 0.);
+        for (a, b) in a.iter().zip(&b) {
+            ab += (a - a_mean) * (b - b_mean);
+            bb += (b - b_mean) * (b - b_mean);
+        }
+        if bb < f64::EPSILON {
+            return Err(String::from("rays leave the lens parallel"));
+        }
+        Ok(-ab / bb)
+    }
+
+    /// Move the sensor into focus for an on-axis point `object_dist` in front of the first surface,
+    /// `f64::INFINITY` for objects at infinity. Returns the new `sensor_dist`.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let mut lens = Lens::read_prescription(
+    ///     std::path::Path::new("../lenses/AngenieuxDGauss.fx"),
+    ///     &Sellmeier::get_all_glasses(),
+    ///     1.,
+    /// )
+    /// .unwrap();
+    /// let paraxial = lens.focus(f64::INFINITY, 0.5876, FocusMode::Paraxial).unwrap();
+    /// assert!((paraxial - lens.paraxial(0.5876).unwrap().back_focal_point).abs() < 1e-9);
+    /// // a closer object is focused further back
+    /// let near = lens.focus(1000., 0.5876, FocusMode::Paraxial).unwrap();
+    /// assert!(near > paraxial);
+    /// // spherical aberration moves the best focus a little
+    /// let rms = lens.focus(f64::INFINITY, 0.5876, FocusMode::RmsSpot).unwrap();
+    /// assert!((rms - paraxial).abs() < 5.);
+    /// ```
+    pub fn focus(
+        &mut self,
+        object_dist: f64,
+        wavelength: f64,
+        mode: FocusMode,
+    ) -> Result<f64, String> {
+        // the cover glass moves with the sensor, so focus again once it is in place
+        for _ in 0..if self.sensor_stack.is_some() { 2 } else { 1 } {
+            self.sensor_dist = match mode {
+                FocusMode::Paraxial => self.paraxial_focus(object_dist, wavelength)?,
+                FocusMode::RmsSpot => self.rms_focus(object_dist, wavelength)?,
+            };
+            self.place_sensor_stack();
+        }
+        Ok(self.sensor_dist)
+    }
+}
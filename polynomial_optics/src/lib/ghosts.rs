@@ -0,0 +1,327 @@
+use cgmath::{InnerSpace, Vector3};
+use rayon::prelude::*;
+
+use crate::{Lens, Ray, RayStatus, RayStatusCounts};
+
+/// footprints are at least this large, so a perfectly focused ghost does not get an infinite intensity
+pub const MIN_GHOST_FOOTPRINT: f64 = 1e-4;
+
+/// ## The path of a ghost through the lens
+/// The indices of the elements the light reflects at, in the order they are hit.
+/// The first reflection sends the light backwards, so the indices alternate between
+/// decreasing and increasing and there is always an even number of them.
+/// No reflections is the direct path to the sensor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct GhostPath {
+    reflections: Vec<u32>,
+}
+
+impl GhostPath {
+    /// a path reflecting at `reflections` in order, fails if the light can not travel along it
+    /// ```
+    /// # use polynomial_optics::*;
+    /// assert!(GhostPath::new(vec![5, 2, 4, 1]).is_ok());
+    /// assert!(GhostPath::new(vec![5, 2, 1, 0]).is_err());
+    /// assert!(GhostPath::new(vec![5, 2, 4]).is_err());
+    /// ```
+    pub fn new(reflections: Vec<u32>) -> Result<Self, String> {
+        if reflections.len() % 2 != 0 {
+            return Err(format!(
+                "a ghost needs an even number of reflections, got {}",
+                reflections.len()
+            ));
+        }
+        for (r, pair) in reflections.windows(2).enumerate() {
+            if (r % 2 == 0 && pair[1] >= pair[0]) || (r % 2 == 1 && pair[1] <= pair[0]) {
+                return Err(format!(
+                    "reflection {} at element {} can not be reached from element {}",
+                    r + 1,
+                    pair[1],
+                    pair[0]
+                ));
+            }
+        }
+        Ok(Self { reflections })
+    }
+
+    /// the classic ghost reflected at element `j` and then at element `i`, fails unless `i < j`
+    /// ```
+    /// # use polynomial_optics::*;
+    /// assert_eq!(GhostPath::two_bounce(1, 3).unwrap().reflections(), &[3, 1]);
+    /// assert!(GhostPath::two_bounce(3, 1).is_err());
+    /// assert!(GhostPath::two_bounce(2, 2).is_err());
+    /// ```
+    pub fn two_bounce(i: u32, j: u32) -> Result<Self, String> {
+        Self::new(vec![j, i])
+    }
+
+    /// the path straight to the sensor
+    pub fn direct() -> Self {
+        Self::default()
+    }
+
+    /// the indices of the reflecting elements in the order they are hit
+    pub fn reflections(&self) -> &[u32] {
+        &self.reflections
+    }
+
+    /// number of reflections along the path
+    pub fn bounces(&self) -> usize {
+        self.reflections.len()
+    }
+
+    /// the layout the shaders read paths in: `[start, num]` of each path and the reflections
+    /// of all paths after each other, `start` and `num` index into the second list.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let paths = [
+    ///     GhostPath::two_bounce(1, 3).unwrap(),
+    ///     GhostPath::direct(),
+    ///     GhostPath::new(vec![4, 0, 2, 1]).unwrap(),
+    /// ];
+    /// let (ghosts, surfaces) = GhostPath::get_buffers(&paths);
+    /// assert_eq!(ghosts, vec![[0, 2], [2, 0], [2, 4]]);
+    /// assert_eq!(surfaces, vec![3, 1, 4, 0, 2, 1]);
+    /// ```
+    pub fn get_buffers(paths: &[GhostPath]) -> (Vec<[u32; 2]>, Vec<u32>) {
+        let mut ghosts = Vec::with_capacity(paths.len());
+        let mut surfaces = vec![];
+        for path in paths {
+            ghosts.push([surfaces.len() as u32, path.reflections.len() as u32]);
+            surfaces.extend_from_slice(&path.reflections);
+        }
+        // empty buffers can't be bound
+        if surfaces.is_empty() {
+            surfaces.push(0);
+        }
+        (ghosts, surfaces)
+    }
+}
+
+/// ## Estimated brightness of one ghost, see `Lens::rank_ghosts`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostRank {
+    /// where the ghost reflects
+    pub path: GhostPath,
+    /// fraction of the sampled light that reaches the sensor along this ghost
+    pub energy: f64,
+    /// area of the ellipse covering the ghost on the sensor, two standard deviations wide
+    pub footprint: f64,
+    /// why the sample rays stopped
+    pub status_counts: RayStatusCounts,
+}
+
+impl GhostRank {
+    /// energy per sensor area, ghosts are ranked by this
+    pub fn intensity(&self) -> f64 {
+        self.energy / self.footprint.max(MIN_GHOST_FOOTPRINT)
+    }
+}
+
+/// ## Which ghosts to keep, see `GhostCulling::cull`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GhostCulling {
+    /// drop ghosts with an intensity below this fraction of the brightest one
+    pub min_intensity: f64,
+    /// keep at most this many ghosts, 0 keeps all
+    pub max_ghosts: usize,
+}
+
+impl Default for GhostCulling {
+    fn default() -> Self {
+        Self {
+            min_intensity: 1e-3,
+            max_ghosts: 0,
+        }
+    }
+}
+
+impl GhostCulling {
+    /// the paths of the ghosts of a ranking from `Lens::rank_ghosts` that are bright enough,
+    /// the brightest first
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let ghost = |i, j, energy| GhostRank {
+    ///     path: GhostPath::two_bounce(i, j).unwrap(),
+    ///     energy,
+    ///     footprint: 1.,
+    ///     status_counts: RayStatusCounts::default(),
+    /// };
+    /// let ranking = [ghost(0, 3, 0.1), ghost(1, 2, 0.01), ghost(0, 1, 1e-6)];
+    /// let culling = GhostCulling::default();
+    /// assert_eq!(
+    ///     culling.cull(&ranking),
+    ///     vec![GhostPath::two_bounce(0, 3).unwrap(), GhostPath::two_bounce(1, 2).unwrap()]
+    /// );
+    /// let culling = GhostCulling { max_ghosts: 1, ..culling };
+    /// assert_eq!(culling.cull(&ranking), vec![GhostPath::two_bounce(0, 3).unwrap()]);
+    /// ```
+    pub fn cull(&self, ranking: &[GhostRank]) -> Vec<GhostPath> {
+        let brightest = ranking.iter().map(GhostRank::intensity).fold(0., f64::max);
+        let max_ghosts = if self.max_ghosts == 0 {
+            ranking.len()
+        } else {
+            self.max_ghosts
+        };
+        ranking
+            .iter()
+            .filter(|rank| rank.intensity() >= brightest * self.min_intensity)
+            .take(max_ghosts)
+            .map(|rank| rank.path.clone())
+            .collect()
+    }
+}
+
+impl Lens {
+    /// all ghosts with up to `max_bounces` reflections at glass surfaces and the sensor,
+    /// ordered by the number of reflections and then by the element the light leaves towards the sensor from.
+    /// The two bounce ghosts come in the same order as in `Lens::get_ghosts_indicies`.
+    pub fn all_ghost_paths(&self, max_bounces: usize) -> Vec<GhostPath> {
+        let glass = self
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| element.properties.reflects())
+            .map(|(i, _)| i as u32)
+            .collect::<Vec<_>>();
+
+        // extend every path by one reflection, alternating backwards and forwards
+        let mut paths = vec![];
+        let mut open = glass.iter().map(|&i| vec![i]).collect::<Vec<_>>();
+        for bounces in 2..=max_bounces {
+            open = open
+                .iter()
+                .flat_map(|path| {
+                    let last = *path.last().unwrap();
+                    glass
+                        .iter()
+                        .filter(move |&&next| {
+                            if bounces % 2 == 0 {
+                                next < last
+                            } else {
+                                next > last
+                            }
+                        })
+                        .map(move |&next| {
+                            let mut path = path.clone();
+                            path.push(next);
+                            path
+                        })
+                })
+                .collect();
+            if bounces % 2 == 0 {
+                paths.extend(open.iter().cloned());
+            }
+        }
+        paths.sort_by(|a, b| {
+            a.len()
+                .cmp(&b.len())
+                .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+        });
+        paths
+            .into_iter()
+            .map(|reflections| GhostPath { reflections })
+            .collect()
+    }
+
+    /// the ghosts to draw like `Lens::get_ghosts_indicies`, with up to `max_bounces` reflections,
+    /// `which_ghost` counts from 1, 0 draws all of them
+    pub fn get_ghost_paths(
+        &self,
+        draw_mode: usize,
+        which_ghost: usize,
+        max_bounces: usize,
+    ) -> Vec<GhostPath> {
+        let mut paths = vec![];
+        if draw_mode & 1 > 0 {
+            let mut all = self.all_ghost_paths(max_bounces);
+            if which_ghost == 0 {
+                paths.append(&mut all);
+            } else if which_ghost <= all.len() {
+                paths.push(all.swap_remove(which_ghost - 1));
+            }
+        }
+        if draw_mode & 2 > 0 {
+            paths.push(GhostPath::direct());
+        }
+        paths
+    }
+
+    /// the two bounce ghost `which_ghost` of `Lens::get_ghost_paths`, counting from 1,
+    /// the CPU raytracer uses it so its ghost numbers match the ones drawn on the GPU
+    pub fn get_ghost_path(&self, which_ghost: usize) -> Option<GhostPath> {
+        if which_ghost == 0 {
+            return None;
+        }
+        self.get_ghost_paths(1, which_ghost, 2).pop()
+    }
+
+    /// Estimate the energy and footprint of every ghost in `Lens::all_ghost_paths`
+    /// by tracing a `side_len`² grid of rays from `pos`, with directions spread by `width` around `dir`.
+    ///
+    /// Returns the ghosts ordered by `GhostRank::intensity`, the brightest first.
+    pub fn rank_ghosts(
+        &self,
+        pos: Vector3<f64>,
+        dir: Vector3<f64>,
+        width: f64,
+        wavelength: f64,
+        sensor_pos: f64,
+        side_len: u32,
+        max_bounces: usize,
+    ) -> Vec<GhostRank> {
+        let side_len = side_len.max(2);
+        let mut ranking = self
+            .all_ghost_paths(max_bounces)
+            .into_par_iter()
+            .map(|path| {
+                let mut energy = 0.;
+                let mut status_counts = RayStatusCounts::default();
+                // strength weighted moments of the dots on the sensor
+                let (mut x, mut y, mut xx, mut yy, mut xy) = (0., 0., 0., 0., 0.);
+                for ray_num_x in 0..side_len {
+                    for ray_num_y in 0..side_len {
+                        let mut d = dir;
+                        d.x += ray_num_x as f64 / (side_len - 1) as f64 * width - width / 2.;
+                        d.y += ray_num_y as f64 / (side_len - 1) as f64 * width - width / 2.;
+                        let ray =
+                            Ray::new(pos, d.normalize(), [pos.x, pos.y, d.x, d.y], wavelength);
+                        let (ray, status) = self.trace_ghost(&path, ray, sensor_pos);
+                        status_counts.add(status);
+                        if status == RayStatus::Ok && ray.strength.is_finite() {
+                            energy += ray.strength;
+                            x += ray.o.x * ray.strength;
+                            y += ray.o.y * ray.strength;
+                            xx += ray.o.x * ray.o.x * ray.strength;
+                            yy += ray.o.y * ray.o.y * ray.strength;
+                            xy += ray.o.x * ray.o.y * ray.strength;
+                        }
+                    }
+                }
+
+                let footprint = if energy > 0. {
+                    let (x, y) = (x / energy, y / energy);
+                    let var_x = xx / energy - x * x;
+                    let var_y = yy / energy - y * y;
+                    let cov = xy / energy - x * y;
+                    // the 2 sigma ellipse has the half axes 2 * sqrt(eigenvalues of the covariance)
+                    4. * std::f64::consts::PI * (var_x * var_y - cov * cov).max(0.).sqrt()
+                } else {
+                    0.
+                };
+                GhostRank {
+                    path,
+                    energy: energy / (side_len * side_len) as f64,
+                    footprint,
+                    status_counts,
+                }
+            })
+            .collect::<Vec<_>>();
+        ranking.sort_by(|a, b| {
+            b.intensity()
+                .partial_cmp(&a.intensity())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        ranking
+    }
+}
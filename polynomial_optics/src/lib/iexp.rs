@@ -19,3 +19,45 @@ macro_rules! iexp {
         $crate::iexp::<$count, _, _>($iterator)
     };
 }
+
+/// Exponents of all monomials in `N` variables with a total degree of at most `degree`
+/// in graded lexicographic order: by total degree, then by the exponent of the first variable
+/// from high to low, then the second one and so on.
+/// ```
+/// # use polynomial_optics::*;
+/// assert_eq!(
+///     exponents::<2>(2),
+///     vec![[0, 0], [1, 0], [0, 1], [2, 0], [1, 1], [0, 2]]
+/// );
+/// // (degree + N choose N) monomials, each one once
+/// for degree in 0..8 {
+///     let terms = exponents::<4>(degree);
+///     assert_eq!(terms.len(), (1..=4).map(|i| degree + i).product::<usize>() / 24);
+///     assert_eq!(terms.len(), num_exponents(4, degree));
+///     assert!(terms.windows(2).all(|pair| pair[0] != pair[1]));
+///     assert!(terms.iter().all(|term| term.iter().sum::<usize>() <= degree));
+///
+///     let terms = exponents::<5>(degree);
+///     assert_eq!(terms.len(), (1..=5).map(|i| degree + i).product::<usize>() / 120);
+/// }
+/// ```
+pub fn exponents<const N: usize>(degree: usize) -> Vec<[usize; N]> {
+    let total = |exponent: &[usize; N]| exponent.iter().sum::<usize>();
+    let mut exponents = iexp!(0..=degree, N)
+        .filter(|exponent| total(exponent) <= degree)
+        .collect::<Vec<_>>();
+    exponents.sort_by(|a, b| total(a).cmp(&total(b)).then_with(|| b.cmp(a)));
+    exponents
+}
+
+/// Number of monomials in `num_vars` variables with a total degree of at most `degree`,
+/// the binomial coefficient (degree + num_vars choose num_vars).
+/// ```
+/// # use polynomial_optics::*;
+/// assert_eq!(num_exponents(4, 3), 35);
+/// assert_eq!(num_exponents(2, 0), 1);
+/// ```
+pub fn num_exponents(num_vars: usize, degree: usize) -> usize {
+    // each partial product is a binomial coefficient itself, so the division is exact
+    (1..=num_vars).fold(1, |num, i| num * (degree + i) / i)
+}
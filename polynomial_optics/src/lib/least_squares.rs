@@ -0,0 +1,244 @@
+/// ## Least squares solver for fitting polynomials
+/// Solves min |Ax - b|² + λ²|x|² with a Householder QR decomposition with column pivoting,
+/// so the normal equations AᵀA, which square the condition number, are never formed.
+/// ```
+/// # use polynomial_optics::*;
+/// // a polynomial of degree 9 in x on [-1, 1], the monomials are nearly dependent
+/// let num_points = 200;
+/// let degree: usize = 9;
+/// let xs = (0..num_points)
+///     .map(|i| -1. + 2. * i as f64 / (num_points - 1) as f64)
+///     .collect::<Vec<_>>();
+/// let expected = (0..=degree).map(|i| (i as f64 - 4.5) / 3.).collect::<Vec<_>>();
+/// let design = xs
+///     .iter()
+///     .flat_map(|x| (0..=degree).map(move |i| x.powi(i as i32)))
+///     .collect::<Vec<_>>();
+/// let values = xs
+///     .iter()
+///     .map(|x| expected.iter().enumerate().map(|(i, c)| c * x.powi(i as i32)).sum())
+///     .collect::<Vec<f64>>();
+///
+/// let solution = LeastSquares::default()
+///     .solve(&design, degree + 1, &values)
+///     .unwrap();
+/// for (coefficient, expected) in solution.coefficients.iter().zip(&expected) {
+///     assert!((coefficient - expected).abs() < 1e-8);
+/// }
+/// assert!(solution.condition > 100.);
+///
+/// // regularisation pulls the coefficients towards 0
+/// let regularised = LeastSquares {
+///     regularisation: 1.,
+///     ..Default::default()
+/// }
+/// .solve(&design, degree + 1, &values)
+/// .unwrap();
+/// let norm = |x: &[f64]| x.iter().map(|c| c * c).sum::<f64>();
+/// assert!(norm(&regularised.coefficients) < norm(&solution.coefficients));
+/// assert!(regularised.condition < solution.condition);
+///
+/// // more terms than points
+/// assert!(LeastSquares::default().solve(&design[..5 * 10], 10, &values[..5]).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeastSquares {
+    /// scale the columns of A to unit length before the decomposition, the coefficients are
+    /// scaled back, so terms of very different magnitude don't lose precision
+    pub scale_columns: bool,
+    /// Tikhonov regularisation λ, applies to the scaled columns if `scale_columns` is set
+    pub regularisation: f64,
+}
+
+impl Default for LeastSquares {
+    fn default() -> Self {
+        Self {
+            scale_columns: true,
+            regularisation: 0.,
+        }
+    }
+}
+
+/// ## Result of `LeastSquares::solve`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeastSquaresSolution {
+    /// x, one coefficient per column of A
+    pub coefficients: Vec<f64>,
+    /// Estimated condition number of the (scaled and regularised) system,
+    /// |r_11| / |r_nn| of the pivoted R, a lower bound of the 2-norm condition number.
+    pub condition: f64,
+}
+
+impl LeastSquares {
+    /// Solve for the `num_columns` coefficients. `design` is A in row-major order,
+    /// one row per point, `values` is b with one value per point.
+    pub fn solve(
+        &self,
+        design: &[f64],
+        num_columns: usize,
+        values: &[f64],
+    ) -> Result<LeastSquaresSolution, String> {
+        let n = num_columns;
+        if n == 0 {
+            return Err(String::from("no terms to fit"));
+        }
+        if design.len() != values.len() * n {
+            return Err(format!(
+                "{} values for a design matrix of {} entries and {} columns",
+                values.len(),
+                design.len(),
+                n
+            ));
+        }
+        if self.regularisation == 0. && values.len() < n {
+            return Err(format!(
+                "{} points are too few to fit {} terms",
+                values.len(),
+                n
+            ));
+        }
+
+        // column-major copy, the regularisation adds λI below A
+        let num_points = values.len();
+        let regularised = self.regularisation != 0.;
+        let m = if regularised {
+            num_points + n
+        } else {
+            num_points
+        };
+        let mut columns = vec![vec![0.; m]; n];
+        for (row, values) in design.chunks_exact(n).enumerate() {
+            for (column, value) in columns.iter_mut().zip(values) {
+                column[row] = *value;
+            }
+        }
+        let scales = columns
+            .iter_mut()
+            .map(|column| {
+                let norm = column.iter().map(|value| value * value).sum::<f64>().sqrt();
+                let scale = if self.scale_columns && norm > 0. {
+                    1. / norm
+                } else {
+                    1.
+                };
+                column.iter_mut().for_each(|value| *value *= scale);
+                scale
+            })
+            .collect::<Vec<_>>();
+        if regularised {
+            for (i, column) in columns.iter_mut().enumerate() {
+                column[num_points + i] = self.regularisation;
+            }
+        }
+        let mut b = values.to_vec();
+        b.resize(m, 0.);
+
+        // Householder QR with column pivoting, R ends up in the upper triangle of `columns`
+        let mut permutation = (0..n).collect::<Vec<_>>();
+        for k in 0..n {
+            let norm_below = |column: &Vec<f64>| column[k..].iter().map(|v| v * v).sum::<f64>();
+            let pivot = (k..n)
+                .max_by(|a, b| norm_below(&columns[*a]).total_cmp(&norm_below(&columns[*b])))
+                .unwrap();
+            columns.swap(k, pivot);
+            permutation.swap(k, pivot);
+
+            let norm = norm_below(&columns[k]).sqrt();
+            if norm == 0. {
+                break;
+            }
+            let alpha = if columns[k][k] > 0. { -norm } else { norm };
+            let mut v = columns[k][k..].to_vec();
+            v[0] -= alpha;
+            let v_norm_sq = v.iter().map(|v| v * v).sum::<f64>();
+            if v_norm_sq > 0. {
+                let reflect = |x: &mut [f64]| {
+                    let s =
+                        2. * v.iter().zip(x.iter()).map(|(v, x)| v * x).sum::<f64>() / v_norm_sq;
+                    x.iter_mut().zip(&v).for_each(|(x, v)| *x -= s * v);
+                };
+                for column in columns.iter_mut().skip(k + 1) {
+                    reflect(&mut column[k..]);
+                }
+                reflect(&mut b[k..]);
+            }
+            columns[k][k] = alpha;
+            columns[k][k + 1..].iter_mut().for_each(|value| *value = 0.);
+        }
+
+        let r = |row: usize, column: usize| columns[column][row];
+        let largest = r(0, 0).abs();
+        let tolerance = f64::EPSILON * m.max(n) as f64 * largest;
+        if let Some(rank) = (0..n).find(|&k| r(k, k).abs() <= tolerance) {
+            return Err(format!(
+                "the terms are linearly dependent, only {} of {} are independent",
+                rank, n
+            ));
+        }
+        let condition = largest / r(n - 1, n - 1).abs();
+
+        // back substitution, then undo the pivoting and scaling
+        let mut z = vec![0.; n];
+        for k in (0..n).rev() {
+            let sum = (k + 1..n).map(|j| r(k, j) * z[j]).sum::<f64>();
+            z[k] = (b[k] - sum) / r(k, k);
+        }
+        let mut coefficients = vec![0.; n];
+        for (k, z) in z.iter().enumerate() {
+            let column = permutation[k];
+            coefficients[column] = z * scales[column];
+        }
+        Ok(LeastSquaresSolution {
+            coefficients,
+            condition,
+        })
+    }
+}
+
+/// Coefficients of the `num_terms` terms fitted to `values`, `design` holds the terms evaluated
+/// at every point, one row per point. Goes through `LeastSquares` unless `intel-mkl` is enabled.
+#[cfg(not(feature = "intel-mkl"))]
+pub(crate) fn solve_fit<N: Copy + num::ToPrimitive + num::FromPrimitive>(
+    design: Vec<N>,
+    num_terms: usize,
+    values: Vec<N>,
+) -> Result<Vec<N>, String> {
+    let to_f64 = |values: Vec<N>| {
+        values
+            .into_iter()
+            .map(|value| value.to_f64().ok_or("value isn't representable as f64"))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let solution = LeastSquares::default().solve(&to_f64(design)?, num_terms, &to_f64(values)?)?;
+    solution
+        .coefficients
+        .into_iter()
+        .map(|c| N::from_f64(c).ok_or_else(|| String::from("coefficient out of range")))
+        .collect()
+}
+
+/// Coefficients of the `num_terms` terms fitted to `values`, `design` holds the terms evaluated
+/// at every point, one row per point. Solves the normal equations with mathru and Intel MKL.
+#[cfg(feature = "intel-mkl")]
+pub(crate) fn solve_fit<
+    N: mathru::algebra::abstr::Field
+        + mathru::algebra::abstr::Scalar
+        + mathru::algebra::abstr::AbsDiffEq,
+>(
+    design: Vec<N>,
+    num_terms: usize,
+    values: Vec<N>,
+) -> Result<Vec<N>, String> {
+    use mathru::algebra::linear::{
+        matrix::{Solve, Transpose},
+        Matrix, Vector,
+    };
+    // mathru is column-major, so this is the transposed design matrix
+    let m = Matrix::new(num_terms, values.len(), design);
+    let y = m.clone() * Vector::new_column(values);
+    let x = m.clone() * m.transpose();
+    let c = x
+        .solve(&y)
+        .map_err(|_| String::from("the normal equations are singular"))?;
+    Ok(c.iter().copied().collect())
+}
@@ -6,8 +6,11 @@ use std::io::prelude::*;
 
 use crate::{Monomial, Polynomial};
 
+/// polynomial in x, y, z and w in the basis of the products of legendre polynomials
 #[derive(Debug, Clone)]
 pub struct Legendre4d {
+    /// coefficients in the graded lexicographic order of `poly_index_to_multi_index`,
+    /// coefficients stored in the previous order have to be fitted again
     coefficiencts: Vec<f64>,
     basis: LegendreBasis,
     degree: usize,
@@ -236,17 +239,8 @@ impl LegendreBasis {
 
 impl Legendre4d {
     pub fn new(basis: LegendreBasis) -> Legendre4d {
-        let mut coefficiencts = vec![];
         let degree = basis.degree;
-        for i in 0..=degree {
-            for j in 0..=degree - i {
-                for k in 0..=degree - i - j {
-                    for _ in 0..=degree - i - j - k {
-                        coefficiencts.push(1.);
-                    }
-                }
-            }
-        }
+        let coefficiencts = vec![1.; Legendre4d::num_polys(degree)];
         Legendre4d {
             coefficiencts,
             basis,
@@ -254,40 +248,30 @@ impl Legendre4d {
         }
     }
 
+    /// number of basis polynomials up to a total degree of `degree`
+    /// ```
+    /// # use polynomial_optics::*;
+    /// for degree in 0..6 {
+    ///     let num_polys = Legendre4d::num_polys(degree);
+    ///     assert_eq!(num_polys, (degree + 1) * (degree + 2) * (degree + 3) * (degree + 4) / 24);
+    ///     let last = Legendre4d::poly_index_to_multi_index(num_polys - 1, degree).unwrap();
+    ///     assert_eq!(last, (0, 0, 0, degree));
+    ///     assert_eq!(Legendre4d::poly_index_to_multi_index(num_polys, degree), None);
+    /// }
+    /// ```
     pub fn num_polys(degree: usize) -> usize {
-        let mut num = 0;
-        for i in 0..=degree {
-            for j in 0..=degree - i {
-                for k in 0..=degree - i - j {
-                    for _ in 0..=degree - i - j - k {
-                        num += 1;
-                    }
-                }
-            }
-        }
-        num
+        crate::num_exponents(4, degree)
     }
 
+    /// degrees of the basis polynomials in x, y, z and w of the `index`th coefficient
     pub fn poly_index_to_multi_index(
         index: usize,
         degree: usize,
     ) -> Option<(usize, usize, usize, usize)> {
-        let mut counter = 0;
-        for i in 0..=degree {
-            for j in 0..=degree - i {
-                for k in 0..=degree - i - j {
-                    for l in 0..=degree - i - j - k {
-                        if counter == index {
-                            return Some((i, j, k, l));
-                        }
-                        counter += 1;
-                    }
-                }
-            }
-        }
-        None
+        Self::multi_indices(degree).get(index).copied()
     }
 
+    /// index of the coefficient of the basis polynomials of degrees `i`, `j`, `k` and `l`
     pub fn poly_multi_index_to_index(
         i: usize,
         j: usize,
@@ -295,20 +279,17 @@ impl Legendre4d {
         l: usize,
         degree: usize,
     ) -> Option<usize> {
-        let mut counter = 0;
-        for m in 0..=degree {
-            for n in 0..=degree - m {
-                for o in 0..=degree - m - n {
-                    for p in 0..=degree - m - n - o {
-                        if m == i && n == j && o == k && p == l {
-                            return Some(counter);
-                        }
-                        counter += 1;
-                    }
-                }
-            }
-        }
-        None
+        Self::multi_indices(degree)
+            .iter()
+            .position(|&multi_index| multi_index == (i, j, k, l))
+    }
+
+    /// the multi indices of all coefficients in order, see `crate::exponents`
+    fn multi_indices(degree: usize) -> Vec<(usize, usize, usize, usize)> {
+        crate::exponents::<4>(degree)
+            .into_iter()
+            .map(|[i, j, k, l]| (i, j, k, l))
+            .collect()
     }
 
     /// fit under the assumption that the basis is orthonomal
@@ -320,12 +301,9 @@ impl Legendre4d {
         //     println!("[{:?}]: {}", multi_index, self.basis.sqare(points, multi_index));
         // }).collect::<Vec<_>>();
 
-        self.coefficiencts = (0..Legendre4d::num_polys(self.degree))
+        self.coefficiencts = Legendre4d::multi_indices(self.degree)
             .into_par_iter()
-            .map(|i| {
-                let multi_index = Legendre4d::poly_index_to_multi_index(i, self.degree).unwrap();
-                self.basis.integrate_over_vec4d(points, multi_index)
-            })
+            .map(|multi_index| self.basis.integrate_over_vec4d(points, multi_index))
             .collect();
     }
 
@@ -351,10 +329,8 @@ impl Legendre4d {
     pub fn eval(&self, x: &(f64, f64, f64, f64)) -> f64 {
         self.coefficiencts
             .par_iter()
-            .enumerate()
-            .map(|(index, c)| {
-                let (i, j, k, l) =
-                    Legendre4d::poly_index_to_multi_index(index, self.degree).unwrap();
+            .zip(Legendre4d::multi_indices(self.degree))
+            .map(|(c, (i, j, k, l))| {
                 c * self.basis.basis[i].eval([x.0])
                     * self.basis.basis[j].eval([x.1])
                     * self.basis.basis[k].eval([x.2])
@@ -382,17 +358,12 @@ impl Legendre4d {
         let coefficiencts = self
             .coefficiencts
             .iter()
-            .enumerate()
-            .filter(|(_, c)| {
+            .zip(Legendre4d::multi_indices(self.degree))
+            .filter(|(c, _)| {
                 coefficients.len() == size
                     || c.abs() > coefficients[coefficients.len() - 1 - size].abs()
             })
-            .map(|(i, c)| {
-                (
-                    *c,
-                    Legendre4d::poly_index_to_multi_index(i, self.degree).unwrap(),
-                )
-            })
+            .map(|(c, multi_index)| (*c, multi_index))
             .collect();
         SparseLegendre4d {
             degree: self.degree,
@@ -405,8 +376,11 @@ impl Legendre4d {
 impl Display for Legendre4d {
     fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
         let mut s = vec![];
-        for (i, c) in self.coefficiencts.iter().enumerate() {
-            let (i, j, k, l) = Legendre4d::poly_index_to_multi_index(i, self.degree).unwrap();
+        for (c, (i, j, k, l)) in self
+            .coefficiencts
+            .iter()
+            .zip(Legendre4d::multi_indices(self.degree))
+        {
             s.push(format!(
                 "{:?}*({})*({})*({})*({})",
                 c,
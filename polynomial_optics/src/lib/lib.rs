@@ -15,3 +15,54 @@ pub use raytracer_autodiff::*;
 
 pub mod iexp;
 pub use iexp::*;
+
+pub mod prescription;
+pub use prescription::*;
+
+pub mod paraxial;
+pub use paraxial::*;
+
+pub mod focus;
+pub use focus::*;
+
+pub mod asphere;
+pub use asphere::*;
+
+pub mod coating;
+pub use coating::*;
+
+pub mod ghosts;
+pub use ghosts::*;
+
+pub mod spectrum;
+pub use spectrum::*;
+
+pub mod sensor_stack;
+pub use sensor_stack::*;
+
+pub mod filter;
+pub use filter::*;
+
+pub mod absorption;
+pub use absorption::*;
+
+pub mod catalog;
+pub use catalog::*;
+
+pub mod abbe;
+pub use abbe::*;
+
+pub mod light_source;
+pub use light_source::*;
+
+pub mod sensor_response;
+pub use sensor_response::*;
+
+pub mod colour;
+pub use colour::*;
+
+pub mod starburst;
+pub use starburst::*;
+
+pub mod least_squares;
+pub use least_squares::*;
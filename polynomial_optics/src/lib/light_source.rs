@@ -0,0 +1,252 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::spectrum::parse_csv_measurements;
+
+/// number of samples of the emission LUT the shaders get
+pub const LIGHT_SAMPLES: usize = 81;
+/// wavelength of the first sample of the emission LUT in µm
+pub const LIGHT_START: f64 = 0.38;
+/// distance between the samples of the emission LUT in µm
+pub const LIGHT_STEP: f64 = 0.005;
+
+/// name of the blackbody in `LightSource::get_all_light_sources` that takes any temperature
+pub const CUSTOM_BLACKBODY: &str = "Blackbody";
+/// name of the daylight in `LightSource::get_all_light_sources` that takes any temperature
+pub const CUSTOM_DAYLIGHT: &str = "Daylight";
+
+/// second radiation constant c2 = hc / k in m K
+const C2: f64 = 1.438776877e-2;
+
+/// wavelength of the first value of the daylight tables in µm
+const DAYLIGHT_START: f64 = 0.38;
+/// distance between the values of the daylight tables in µm
+const DAYLIGHT_STEP: f64 = 0.01;
+/// mean daylight spectrum S0 of the CIE daylight model, 380 nm to 780 nm
+#[rustfmt::skip]
+const DAYLIGHT_S0: [f64; 41] = [
+    63.4, 65.8, 94.8, 104.8, 105.9, 96.8, 113.9, 125.6, 125.5, 121.3,
+    121.3, 113.5, 113.1, 110.8, 106.5, 108.8, 105.3, 104.4, 100.0, 96.0,
+    95.1, 89.1, 90.5, 90.3, 88.4, 84.0, 85.1, 81.9, 82.6, 84.9,
+    81.3, 71.9, 74.3, 76.4, 63.3, 71.7, 77.0, 65.2, 47.7, 68.6,
+    65.0,
+];
+/// first characteristic vector S1 of the CIE daylight model
+#[rustfmt::skip]
+const DAYLIGHT_S1: [f64; 41] = [
+    38.5, 35.0, 43.4, 46.3, 43.9, 37.1, 36.7, 35.9, 32.6, 27.9,
+    24.3, 20.1, 16.2, 13.2, 8.6, 6.1, 4.2, 1.9, 0.0, -1.6,
+    -3.5, -3.5, -5.8, -7.2, -8.6, -9.5, -10.9, -10.7, -12.0, -14.0,
+    -13.6, -12.0, -13.3, -12.9, -10.6, -11.6, -12.2, -10.2, -7.8, -11.2,
+    -10.4,
+];
+/// second characteristic vector S2 of the CIE daylight model
+#[rustfmt::skip]
+const DAYLIGHT_S2: [f64; 41] = [
+    3.0, 1.2, -1.1, -0.5, -0.7, -1.2, -2.6, -2.9, -2.8, -2.6,
+    -2.6, -1.8, -1.5, -1.3, -1.2, -1.0, -0.5, -0.3, 0.0, 0.2,
+    0.5, 2.1, 3.2, 4.1, 4.7, 5.1, 6.7, 7.3, 8.6, 9.8,
+    10.2, 8.3, 9.6, 8.5, 7.0, 7.6, 8.0, 6.7, 5.2, 7.4,
+    6.8,
+];
+
+/// ## Spectrum of the light that causes the flare
+/// Only the shape matters, `LightSource::power` is normalized to a mean of 1 over 380 nm to 780 nm.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LightSource {
+    /// a black body at a temperature in K, like a tungsten lamp
+    Blackbody(f64),
+    /// CIE daylight at a correlated colour temperature in K, D65 is 6504 K
+    Daylight(f64),
+    /// the same power at all wavelengths, CIE illuminant E
+    EqualEnergy,
+    /// measurements of `(wavelength in µm, relative power)`, no light outside of them
+    Measured(Vec<(f64, f64)>),
+}
+
+impl Default for LightSource {
+    fn default() -> Self {
+        Self::Blackbody(3000.)
+    }
+}
+
+impl LightSource {
+    /// the built in light sources, the custom blackbody and daylight come first
+    pub fn get_all_light_sources() -> Vec<(String, LightSource)> {
+        vec![
+            (CUSTOM_BLACKBODY.into(), Self::default()),
+            (CUSTOM_DAYLIGHT.into(), Self::Daylight(6504.)),
+            // CIE illuminant A, a tungsten lamp
+            ("CIE A".into(), Self::Blackbody(2856.)),
+            ("CIE D50".into(), Self::Daylight(5003.)),
+            ("CIE D55".into(), Self::Daylight(5503.)),
+            ("CIE D65".into(), Self::Daylight(6504.)),
+            ("CIE D75".into(), Self::Daylight(7504.)),
+            ("CIE E".into(), Self::EqualEnergy),
+        ]
+    }
+
+    /// the light source at `temperature` in K if it is the custom blackbody or daylight `name`
+    /// of `LightSource::get_all_light_sources`, the CIE illuminants keep their temperature
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let all = LightSource::get_all_light_sources();
+    /// let with_temperature = |name: &str| {
+    ///     let (name, light_source) = all.iter().find(|(other, _)| other == name).unwrap();
+    ///     light_source.with_temperature(name, 4000.)
+    /// };
+    /// assert_eq!(with_temperature(CUSTOM_BLACKBODY), LightSource::Blackbody(4000.));
+    /// assert_eq!(with_temperature(CUSTOM_DAYLIGHT), LightSource::Daylight(4000.));
+    /// assert_eq!(with_temperature("CIE A"), LightSource::Blackbody(2856.));
+    /// assert_eq!(with_temperature("CIE D65"), LightSource::Daylight(6504.));
+    /// ```
+    pub fn with_temperature(&self, name: &str, temperature: f64) -> LightSource {
+        match self {
+            Self::Blackbody(_) if name == CUSTOM_BLACKBODY => Self::Blackbody(temperature),
+            Self::Daylight(_) if name == CUSTOM_DAYLIGHT => Self::Daylight(temperature),
+            light_source => light_source.clone(),
+        }
+    }
+
+    /// Reads a spectrum from a CSV file, see `LightSource::parse_csv`.
+    pub fn read_csv(path: &Path) -> Result<LightSource, String> {
+        match std::fs::read_to_string(path) {
+            Ok(str) => Self::parse_csv(str.as_str()),
+            Err(err) => Err(format!("problem reading file: {}", err)),
+        }
+    }
+
+    /// Parses lines of `wavelength in nm, relative power`, a header line is skipped.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// // a low pressure sodium lamp
+    /// let sodium = LightSource::parse_csv(
+    ///     "wavelength,power
+    ///      585, 0
+    ///      589, 100
+    ///      593, 0",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(sodium.power(0.5), 0.);
+    /// assert!(sodium.power(0.589) > 50.);
+    ///
+    /// assert!(LightSource::parse_csv("500, -1").is_err());
+    /// ```
+    pub fn parse_csv(str: &str) -> Result<LightSource, String> {
+        let measurements = parse_csv_measurements(str)?
+            .into_iter()
+            .map(|(wavelength, power)| (wavelength / 1000., power))
+            .collect::<Vec<_>>();
+        Self::from_measurements(measurements)
+    }
+
+    /// a light source from measurements of `(wavelength in µm, relative power)`
+    pub fn from_measurements(measurements: Vec<(f64, f64)>) -> Result<LightSource, String> {
+        if measurements.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(String::from("measurements are not sorted by wavelength"));
+        }
+        if let Some((wavelength, power)) = measurements
+            .iter()
+            .find(|(_, power)| !power.is_finite() || *power < 0.)
+        {
+            return Err(format!("power {} at {}µm is negative", power, wavelength));
+        }
+        let light_source = Self::Measured(measurements);
+        if light_source.mean_emission() > 0. {
+            Ok(light_source)
+        } else {
+            Err(String::from("no light between 380nm and 780nm"))
+        }
+    }
+
+    /// unnormalized spectral power at `wavelength` in µm
+    fn emission(&self, wavelength: f64) -> f64 {
+        match self {
+            Self::Blackbody(temperature) => {
+                let wavelength = wavelength * 1e-6;
+                1. / wavelength.powi(5) / ((C2 / (wavelength * temperature)).exp() - 1.)
+            }
+            Self::Daylight(temperature) => {
+                let (m1, m2) = daylight_factors(*temperature);
+                let x = ((wavelength - DAYLIGHT_START) / DAYLIGHT_STEP).clamp(0., 40.);
+                let i = (x as usize).min(39);
+                let t = x - i as f64;
+                let lerp = |table: &[f64; 41]| table[i] * (1. - t) + table[i + 1] * t;
+                (lerp(&DAYLIGHT_S0) + m1 * lerp(&DAYLIGHT_S1) + m2 * lerp(&DAYLIGHT_S2)).max(0.)
+            }
+            Self::EqualEnergy => 1.,
+            Self::Measured(measurements) => {
+                let next = measurements.partition_point(|(w, _)| *w < wavelength);
+                if next == 0 || next == measurements.len() {
+                    match measurements.get(next) {
+                        Some((w, power)) if *w == wavelength => *power,
+                        _ => 0.,
+                    }
+                } else {
+                    let (w0, p0) = measurements[next - 1];
+                    let (w1, p1) = measurements[next];
+                    p0 + (p1 - p0) * (wavelength - w0) / (w1 - w0)
+                }
+            }
+        }
+    }
+
+    /// mean of `emission` over the samples of the LUT
+    fn mean_emission(&self) -> f64 {
+        (0..LIGHT_SAMPLES)
+            .map(|i| self.emission(LIGHT_START + i as f64 * LIGHT_STEP))
+            .sum::<f64>()
+            / LIGHT_SAMPLES as f64
+    }
+
+    /// Relative spectral power at `wavelength` in µm, with a mean of 1 over the visible range.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let d65 = LightSource::Daylight(6504.);
+    /// // bluer than a tungsten lamp
+    /// assert!(d65.power(0.45) > d65.power(0.65));
+    /// let tungsten = LightSource::Blackbody(2856.);
+    /// assert!(tungsten.power(0.45) < tungsten.power(0.65));
+    /// ```
+    pub fn power(&self, wavelength: f64) -> f64 {
+        let mean = self.mean_emission();
+        if mean > 0. {
+            self.emission(wavelength) / mean
+        } else {
+            0.
+        }
+    }
+
+    /// `LightSource::power` every 5 nm from 380 nm to 780 nm, the LUT of the shaders
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let lut = LightSource::EqualEnergy.get_lut();
+    /// assert!(lut.iter().all(|power| (power - 1.).abs() < 1e-6));
+    /// ```
+    pub fn get_lut(&self) -> [f32; LIGHT_SAMPLES] {
+        let mean = self.mean_emission();
+        let mut lut = [0.; LIGHT_SAMPLES];
+        if mean > 0. {
+            for (i, power) in lut.iter_mut().enumerate() {
+                *power = (self.emission(LIGHT_START + i as f64 * LIGHT_STEP) / mean) as f32;
+            }
+        }
+        lut
+    }
+}
+
+/// weights M1 and M2 of the characteristic vectors for CIE daylight at `temperature` in K
+fn daylight_factors(temperature: f64) -> (f64, f64) {
+    let t = temperature.clamp(4000., 25000.);
+    let x = if t <= 7000. {
+        -4.6070e9 / t.powi(3) + 2.9678e6 / t.powi(2) + 0.09911e3 / t + 0.244063
+    } else {
+        -2.0064e9 / t.powi(3) + 1.9018e6 / t.powi(2) + 0.24748e3 / t + 0.237040
+    };
+    let y = -3.000 * x * x + 2.870 * x - 0.275;
+    let m = 0.0241 + 0.2562 * x - 0.7341 * y;
+    let m1 = (-1.3515 - 1.7703 * x + 5.9114 * y) / m;
+    let m2 = (0.0300 - 31.4424 * x + 30.0717 * y) / m;
+    (m1, m2)
+}
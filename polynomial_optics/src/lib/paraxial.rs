@@ -0,0 +1,220 @@
+use crate::{Lens, Properties};
+
+/// ## Ray transfer matrix acting on (height, n * angle)
+/// ```text
+/// | y'    |   | a b |   | y    |
+/// | n' u' | = | c d | * | n u  |
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Abcd {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+}
+
+impl Abcd {
+    pub fn identity() -> Self {
+        Self {
+            a: 1.,
+            b: 0.,
+            c: 0.,
+            d: 1.,
+        }
+    }
+
+    /// propagation over `distance` in a medium with refractive index `ior`
+    pub fn translation(distance: f64, ior: f64) -> Self {
+        Self {
+            b: distance / ior,
+            ..Self::identity()
+        }
+    }
+
+    /// refraction at a surface with (standard sign) radius `radius` from `n1` into `n2`
+    pub fn refraction(radius: f64, n1: f64, n2: f64) -> Self {
+        Self {
+            c: -(n2 - n1) / radius,
+            ..Self::identity()
+        }
+    }
+
+    /// first apply `self`, then `other`
+    pub fn then(&self, other: &Abcd) -> Self {
+        Self {
+            a: other.a * self.a + other.b * self.c,
+            b: other.a * self.b + other.b * self.d,
+            c: other.c * self.a + other.d * self.c,
+            d: other.c * self.b + other.d * self.d,
+        }
+    }
+}
+
+/// ## Image of the aperture stop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pupil {
+    /// z position of the pupil
+    pub position: f64,
+    pub radius: f64,
+}
+
+/// ## First-order properties of a `Lens`
+/// All positions are absolute z coordinates like `Element.position`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Paraxial {
+    /// wavelength the properties were calculated at in µm
+    pub wavelength: f64,
+    /// effective focal length
+    pub efl: f64,
+    /// back focal length: distance from the last surface to the back focal point
+    pub bfl: f64,
+    /// front focal length: distance from the front focal point to the first surface
+    pub ffl: f64,
+    /// position of the front principal plane
+    pub front_principal_plane: f64,
+    /// position of the back principal plane
+    pub back_principal_plane: f64,
+    /// position of the back focal point, where the sensor has to be for objects at infinity
+    pub back_focal_point: f64,
+    /// image of the aperture as seen from the front, `None` if the lens has no aperture
+    pub entrance_pupil: Option<Pupil>,
+    /// image of the aperture as seen from the sensor, `None` if the lens has no aperture
+    pub exit_pupil: Option<Pupil>,
+    /// efl / entrance pupil diameter
+    pub f_number: Option<f64>,
+    /// distance from the exit pupil to the sensor / exit pupil diameter
+    pub working_f_number: Option<f64>,
+}
+
+impl Lens {
+    /// Matrix of the surfaces `self.elements[range]`, from the plane at `start` to the plane at `end`.
+    /// Only the y-z plane is traced, so cylindrical surfaces count as spherical ones.
+    pub(crate) fn get_abcd(
+        &self,
+        range: std::ops::Range<usize>,
+        start: f64,
+        end: f64,
+        wavelength: f64,
+    ) -> Abcd {
+        let mut abcd = Abcd::identity();
+        let mut pos = start;
+        let mut ior = 1.;
+        for element in &self.elements[range] {
+            if let Some(glass) = element.properties.glass() {
+                abcd = abcd.then(&Abcd::translation(element.position - pos, ior));
+                pos = element.position;
+                // like `Ray::propagate`: entry from air into the glass, exit from the glass into air
+                let (radius, new_ior) = if glass.entry {
+                    (element.radius, glass.sellmeier.ior(wavelength))
+                } else {
+                    (-element.radius, 1.)
+                };
+                abcd = abcd.then(&Abcd::refraction(radius, ior, new_ior));
+                ior = new_ior;
+            }
+        }
+        abcd.then(&Abcd::translation(end - pos, ior))
+    }
+
+    /// Calculate the paraxial properties of the lens at `wavelength` in µm.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// // a thin-ish bi-convex lens
+    /// let lens = Lens::new(
+    ///     vec![
+    ///         Element {
+    ///             radius: 50.,
+    ///             properties: Properties::Glass(Glass {
+    ///                 sellmeier: Sellmeier::bk7(),
+    ///                 coating: CoatingStack::none(),
+    ///                 entry: true,
+    ///                 outer_ior: Sellmeier::air(),
+    ///                 spherical: true,
+    ///                 asphere: Asphere::sphere(),
+    ///                 semi_diameter: None,
+    ///             }),
+    ///             position: 0.,
+    ///         },
+    ///         Element {
+    ///             radius: 50.,
+    ///             properties: Properties::Glass(Glass {
+    ///                 sellmeier: Sellmeier::bk7(),
+    ///                 coating: CoatingStack::none(),
+    ///                 entry: false,
+    ///                 outer_ior: Sellmeier::air(),
+    ///                 spherical: true,
+    ///                 asphere: Asphere::sphere(),
+    ///                 semi_diameter: None,
+    ///             }),
+    ///             position: 1.,
+    ///         },
+    ///     ],
+    ///     50.,
+    /// );
+    /// let paraxial = lens.paraxial(0.5876).unwrap();
+    /// // lensmaker's equation
+    /// let n = Sellmeier::bk7().ior(0.5876);
+    /// let power = (n - 1.) * (2. / 50. - (n - 1.) / (n * 50. * 50.));
+    /// assert!((paraxial.efl - 1. / power).abs() < 1e-9);
+    /// assert!(paraxial.entrance_pupil.is_none());
+    /// ```
+    pub fn paraxial(&self, wavelength: f64) -> Result<Paraxial, String> {
+        let first = self
+            .elements
+            .first()
+            .ok_or("lens has no elements")?
+            .position;
+        let last = self.elements.last().unwrap().position;
+
+        let system = self.get_abcd(0..self.elements.len(), first, last, wavelength);
+        if system.c.abs() < f64::EPSILON {
+            return Err(String::from("lens is afocal"));
+        }
+        let efl = -1. / system.c;
+        let bfl = -system.a / system.c;
+        let ffl = -system.d / system.c;
+
+        let stop = self
+            .elements
+            .iter()
+            .position(|element| matches!(element.properties, Properties::Aperture(_)));
+
+        let (entrance_pupil, exit_pupil) = match stop {
+            Some(stop) => {
+                let stop_pos = self.elements[stop].position;
+                let stop_radius = self.elements[stop].radius;
+
+                // chief ray: image of the stop center in object space has b == 0
+                let front = self.get_abcd(0..stop, first, stop_pos, wavelength);
+                let entrance_pupil = Pupil {
+                    position: first + front.b / front.a,
+                    radius: (stop_radius / front.a).abs(),
+                };
+
+                // image of the stop center in image space
+                let rear = self.get_abcd(stop..self.elements.len(), stop_pos, last, wavelength);
+                let exit_pupil = Pupil {
+                    position: last - rear.b / rear.d,
+                    radius: (stop_radius / rear.d).abs(),
+                };
+                (Some(entrance_pupil), Some(exit_pupil))
+            }
+            None => (None, None),
+        };
+
+        Ok(Paraxial {
+            wavelength,
+            efl,
+            bfl,
+            ffl,
+            front_principal_plane: first + (system.d - 1.) / system.c,
+            back_principal_plane: last + (1. - system.a) / system.c,
+            back_focal_point: last + bfl,
+            entrance_pupil,
+            exit_pupil,
+            f_number: entrance_pupil.map(|pupil| (efl / (2. * pupil.radius)).abs()),
+            working_f_number: exit_pupil
+                .map(|pupil| ((self.sensor_dist - pupil.position) / (2. * pupil.radius)).abs()),
+        })
+    }
+}
@@ -1,6 +1,4 @@
 use mathru::algebra::abstr::{AbsDiffEq, Field, Scalar};
-use mathru::algebra::linear::matrix::Transpose;
-use mathru::algebra::linear::{matrix::Solve, Matrix, Vector};
 use num::traits::Zero;
 use rand::prelude::IteratorRandom;
 use rand::Rng;
@@ -117,7 +115,15 @@ impl<N, const DEGREE: usize> Polynom2d<N, DEGREE> {
 
 #[allow(clippy::many_single_char_names)]
 impl<
-        N: Add + Copy + std::iter::Sum<N> + PowUsize + Field + Scalar + AbsDiffEq,
+        N: Add
+            + Copy
+            + std::iter::Sum<N>
+            + PowUsize
+            + Field
+            + Scalar
+            + AbsDiffEq
+            + num::ToPrimitive
+            + num::FromPrimitive,
         const DEGREE: usize,
     > Polynom2d<N, DEGREE>
 {
@@ -128,40 +134,20 @@ impl<
     /// };
     /// let p = vec![(1.0,1.0,f.eval(1.0, 1.0)), (-1.0,1.0,f.eval(-1.0, 1.0))
     ///              , (1.0,-1.0,f.eval(1.0, -1.0)), (-1.0,-1.0,f.eval(-1.0, -1.0))];
-    /// let res = Polynom2d::<_, 2>::fit(p);
+    /// let res = Polynom2d::<_, 2>::fit(p).unwrap();
     /// println!("{:?}", res);
     /// assert!(f == res);
     /// ```
-    pub fn fit(points: &[(N, N, N)]) -> Polynom2d<N, DEGREE> {
+    pub fn fit(points: &[(N, N, N)]) -> Result<Polynom2d<N, DEGREE>, String> {
         let terms = Self::get_terms();
         let num_terms = Self::get_num_terms();
-        let mut m = Matrix::<N>::zero(num_terms, num_terms);
-        let mut k = Vector::<N>::zero(num_terms);
-        for (iter, element) in m.iter_mut().enumerate() {
-            let (i, j) = (iter / (num_terms), iter % (num_terms));
-            let a = terms[i];
-            let b = terms[j];
-            // println!("i:{},j:{}, a:{:?}, b:{:?}", i, j, a, b);
-            *element = points
-                .iter()
-                .map(|(x, y, _d)| (*x).upow(a.0 + b.0) * (*y).upow(a.1 + b.1))
-                .sum::<N>()
-        }
-        for (i, element) in k.iter_mut().enumerate() {
-            let a = (i / DEGREE, i % DEGREE);
-            *element = points
-                .iter()
-                .map(|(x, y, d)| *d * (*x).upow(a.0) * (*y).upow(a.1))
-                .sum::<N>()
-        }
-        // println!("m: {:?}", m);
-        // println!("k: {:?}", k);
-        let c = m.solve(&k).unwrap();
-        let mut coefficients = Vec::with_capacity(num_terms);
-        for element in c.iter() {
-            coefficients.push(*element);
-        }
-        Polynom2d { coefficients }
+        let design = points
+            .iter()
+            .flat_map(|&(x, y, _d)| terms.iter().map(move |a| x.upow(a.0) * y.upow(a.1)))
+            .collect();
+        let values = points.iter().map(|&(_x, _y, d)| d).collect();
+        let coefficients = crate::solve_fit(design, num_terms, values)?;
+        Ok(Polynom2d { coefficients })
     }
 }
 
@@ -181,6 +167,8 @@ impl<
             + Scalar
             + mathru::algebra::abstr::AbsDiffEq
             + mathru::elementary::Power
+            + num::ToPrimitive
+            + num::FromPrimitive
             + Copy,
         const DEGREE: usize,
     > Polynom2d<N, DEGREE>
@@ -225,6 +213,7 @@ impl<
                 now = Instant::now();
             }
             counter += 1;
+            let previous = phi.clone();
 
             phi.terms.push(self.get_monomial(*i, *j, *coefficient));
             let mut min = Self::dist(&phi, points);
@@ -256,7 +245,11 @@ impl<
                     }
                 }
             }
-            phi.fit(points)
+            if let Err(err) = phi.fit(points) {
+                // the picked term is degenerate on `points`, keep the previous terms
+                println!("{}: not picking the term, {}", counter, err);
+                phi = previous;
+            }
         }
         phi
     }
@@ -361,8 +354,12 @@ impl<N: PartialEq + Copy, const DEGREE: usize> std::cmp::PartialEq for Polynom2d
     }
 }
 
+/// dense polynomial in x, y, z and w
 #[derive(Debug, Clone)]
 pub struct Polynom4d<N> {
+    /// coefficients of the terms of `get_terms`, in graded lexicographic order since the
+    /// exponents are generated by `crate::exponents`; coefficient vectors stored in the
+    /// previous order don't match the terms anymore and have to be fitted again
     pub coefficients: Vec<N>,
     degree: usize,
 }
@@ -414,66 +411,20 @@ where
 }
 
 impl<N> Polynom4d<N> {
+    /// exponents of the terms of `coefficients`, see `crate::exponents`
     pub fn get_terms(&self) -> Vec<(usize, usize, usize, usize)> {
-        let mut terms = vec![];
-
-        for i in 0..=self.degree {
-            for j in 0..=self.degree - i {
-                for k in 0..=self.degree - (i + j) {
-                    for l in 0..=self.degree - (i + j + k) {
-                        terms.push((i, j, k, l));
-                    }
-                }
-            }
-        }
-        terms
+        Self::get_terms_fn(self.degree)
     }
 
     fn get_terms_fn(degree: usize) -> Vec<(usize, usize, usize, usize)> {
-        let mut terms = vec![];
-
-        for i in 0..=degree {
-            for j in 0..=degree - i {
-                for k in 0..=degree - (i + j) {
-                    for l in 0..=degree - (i + j + k) {
-                        terms.push((i, j, k, l));
-                    }
-                }
-            }
-        }
-        terms
+        crate::exponents::<4>(degree)
+            .into_iter()
+            .map(|[i, j, k, l]| (i, j, k, l))
+            .collect()
     }
 
     fn get_num_terms(&self) -> usize {
-        let mut num_terms = 0;
-        for i in 0..=self.degree {
-            for j in 0..=self.degree - i {
-                for k in 0..=self.degree - (i + j) {
-                    for _ in 0..=self.degree - (i + j + k) {
-                        num_terms += 1;
-                    }
-                }
-            }
-        }
-        num_terms
-    }
-
-    fn get_index(&self, i: usize, j: usize, k: usize, l: usize) -> usize {
-        let mut index = 0;
-        for m in 0..=self.degree {
-            for n in 0..=self.degree - m {
-                for o in 0..=self.degree - (m + n) {
-                    for p in 0..=self.degree - (m + n + o) {
-                        if m == i && n == j && o == k && p == l {
-                            return index;
-                        }
-                        index += 1;
-                    }
-                }
-            }
-        }
-        println!("did not find index {} {} {} {}", i, j, k, l);
-        return 10000000;
+        crate::num_exponents(4, self.degree)
     }
 }
 
@@ -493,6 +444,8 @@ impl<
             + Scalar
             + mathru::algebra::abstr::AbsDiffEq
             + mathru::elementary::Power
+            + num::ToPrimitive
+            + num::FromPrimitive
             + Copy,
     > Polynom4d<N>
 {
@@ -530,7 +483,7 @@ impl<
         num_max_terms: usize,
         cheap: bool,
         dont_inter_fit: bool,
-    ) -> crate::Polynomial<N, 4> {
+    ) -> Result<crate::Polynomial<N, 4>, String> {
         let mut phi = crate::Polynomial::<_, 4>::new(vec![]);
         let mut now = Instant::now();
         let terms = self.get_terms();
@@ -599,12 +552,13 @@ impl<
                 .any(|&mon| mon.exponents == [min_i, min_j, min_k, min_l])
             {
                 println!("\nNo better term found!!!");
-                phi.fit(points);
-                return phi;
+                phi.fit(points)?;
+                return Ok(phi);
             }
             if cfg!(debug_assertions) {
                 println!(" {}", min);
             }
+            let previous = phi.clone();
             if phi.terms.len() < num_max_terms {
                 phi.terms
                     .push(self.get_monomial(min_i, min_j, min_k, min_l, min_c));
@@ -626,7 +580,11 @@ impl<
             }
             // println!("pre-fit: {}", phi);
             if !dont_inter_fit {
-                phi.fit(points);
+                if let Err(err) = phi.fit(points) {
+                    // the picked term is degenerate on `points`, keep the previous terms
+                    println!("{}: not picking the term, {}", counter, err);
+                    phi = previous;
+                }
             }
             // println!("post-fit: {}", phi);
         }
@@ -642,27 +600,21 @@ impl<
             println!("resulting polynomial: {:?}", phi);
         }
         println!("total time: {:?}", now.elapsed());
-        phi
+        Ok(phi)
     }
 
     pub fn get_sparse_dumb(&self, num_max_terms: usize) -> crate::Polynomial<N, 4> {
         let mut res = crate::Polynomial::<_, 4>::new(vec![]);
-        let mut terms = self.get_terms();
+        let mut terms = self
+            .get_terms()
+            .into_iter()
+            .zip(self.coefficients.iter().copied())
+            .collect::<Vec<_>>();
         println!("got {} terms", terms.len());
 
-        terms.sort_by(|a, b| {
-            self.coefficients[self.get_index(b.0, b.1, b.2, b.3)]
-                .abs()
-                .partial_cmp(&self.coefficients[self.get_index(a.0, a.1, a.2, a.3)].abs())
-                .unwrap()
-        });
+        terms.sort_by(|(_, a), (_, b)| b.abs().partial_cmp(&a.abs()).unwrap());
 
-        // terms.iter().for_each(|(i, j, k, l)| {
-        //     println!("{}", self.coefficients[self.get_index(*i, *j, *k, *l)]);
-        // });
-
-        for &(i, j, k, l) in terms.iter().take(num_max_terms) {
-            let coefficient = self.coefficients[self.get_index(i, j, k, l)];
+        for &((i, j, k, l), coefficient) in terms.iter().take(num_max_terms) {
             res.terms.push(self.get_monomial(i, j, k, l, coefficient));
         }
 
@@ -675,7 +627,79 @@ impl<
     }
 }
 
+/// errors at the fitted points and term counts of the LASSO and the OMP fit,
+/// see `Polynom4d::compare_sparse`
+#[derive(Debug, Clone, Copy)]
+pub struct SparseComparison {
+    pub lasso_error: f64,
+    pub lasso_terms: usize,
+    pub omp_error: f64,
+    pub omp_terms: usize,
+}
+
 impl Polynom4d<f64> {
+    /// Polynomial regression with the QR solver, independent of the `intel-mkl` feature.
+    /// Returns the fit and the estimated condition number of the system.
+    pub fn fit_least_squares(
+        points: &[(f64, f64, f64, f64, f64)],
+        degree: usize,
+        solver: &crate::LeastSquares,
+    ) -> Result<(Polynom4d<f64>, f64), String> {
+        let design = Self::design_matrix(points, degree);
+        let values = points.iter().map(|point| point.4).collect::<Vec<_>>();
+        let solution = solver.solve(&design, crate::num_exponents(4, degree), &values)?;
+        Ok((
+            Polynom4d {
+                coefficients: solution.coefficients,
+                degree,
+            },
+            solution.condition,
+        ))
+    }
+
+    /// Sparse polynomial of at most `num_max_terms` of the terms with `Polynomial::get_sparse_lasso`,
+    /// for the same `points` and `num_max_terms` as `get_sparse`, compare both with `error`.
+    pub fn get_sparse_lasso(
+        &self,
+        points: &[(f64, f64, f64, f64, f64)],
+        num_max_terms: usize,
+    ) -> Result<crate::Polynomial<f64, 4>, String> {
+        let points = points
+            .iter()
+            .map(|p| ([p.0, p.1, p.2, p.3], p.4))
+            .collect::<Vec<_>>();
+        crate::Polynomial::<f64, 4>::dense(self.degree).get_sparse_lasso(
+            &points,
+            num_max_terms,
+            &crate::LeastSquares::default(),
+        )
+    }
+
+    /// Fits `get_sparse_lasso` and `Polynomial::get_sparse_omp` with at most `num_max_terms`
+    /// of the terms to the same `points` and compares them with `error` at those points.
+    pub fn compare_sparse(
+        &self,
+        points: &[(f64, f64, f64, f64, f64)],
+        num_max_terms: usize,
+    ) -> Result<SparseComparison, String> {
+        let lasso = self.get_sparse_lasso(points, num_max_terms)?;
+        let omp_points = points
+            .iter()
+            .map(|p| ([p.0, p.1, p.2, p.3], p.4))
+            .collect::<Vec<_>>();
+        let omp = crate::Polynomial::<f64, 4>::dense(self.degree).get_sparse_omp(
+            &omp_points,
+            num_max_terms,
+            &crate::LeastSquares::default(),
+        )?;
+        Ok(SparseComparison {
+            lasso_error: lasso.error(points),
+            lasso_terms: lasso.terms.len(),
+            omp_error: omp.error(points),
+            omp_terms: omp.terms.len(),
+        })
+    }
+
     fn temp_to_size(num_terms: usize, temp: f64) -> usize {
         // let t = temp / (temp + 1.0);
         let t = temp.powi(3);
@@ -696,7 +720,7 @@ impl Polynom4d<f64> {
         num_terms: usize,
         num_samples: usize,
         num_iterations: usize,
-    ) -> crate::Polynomial<f64, 4> {
+    ) -> Result<crate::Polynomial<f64, 4>, String> {
         assert!(num_samples <= points.len());
         let debug = cfg!(debug_assertions);
 
@@ -772,13 +796,16 @@ impl Polynom4d<f64> {
             }
             let now = Instant::now();
 
-            res.fit(&points[offset..offset + num_samples]);
+            // terms that are degenerate on the samples are never accepted
+            let new_error = match res.fit(&points[offset..offset + num_samples]) {
+                Ok(()) => res.approx_error(points, num_samples, offset),
+                Err(_) => f64::INFINITY,
+            };
             if debug {
                 println!("fitting took {:?}", now.elapsed());
             }
             let now = Instant::now();
 
-            let new_error = res.approx_error(points, num_samples, offset);
             if debug {
                 println!("error calc took {:?}", now.elapsed());
                 println!("{} {}", error, new_error);
@@ -811,14 +838,14 @@ impl Polynom4d<f64> {
                     .unwrap();
             }
         }
-        res.fit(points);
+        res.fit(points)?;
         // println!(
         //     "actual error = {} error = {}",
         //     res.error(points),
         //     res.approx_error(points, num_samples, 0)
         // );
 
-        res
+        Ok(res)
     }
 }
 
@@ -832,10 +859,37 @@ impl<N: PowUsize + AddAssign + Zero + Copy + Mul<Output = N>> Polynom4d<N> {
         }
         sum
     }
+
+    /// every term of `degree` evaluated at every point, one row per point
+    fn design_matrix(points: &[(N, N, N, N, N)], degree: usize) -> Vec<N> {
+        let terms = Self::get_terms_fn(degree);
+        points
+            .iter()
+            .flat_map(|point| {
+                terms.iter().map(move |a| {
+                    (point.0).upow(a.0)
+                        * (point.1).upow(a.1)
+                        * (point.2).upow(a.2)
+                        * (point.3).upow(a.3)
+                })
+            })
+            .collect()
+    }
 }
 
 #[allow(clippy::many_single_char_names)]
-impl<N: Add + Copy + std::iter::Sum<N> + PowUsize + Field + Scalar + AbsDiffEq> Polynom4d<N> {
+impl<
+        N: Add
+            + Copy
+            + std::iter::Sum<N>
+            + PowUsize
+            + Field
+            + Scalar
+            + AbsDiffEq
+            + num::ToPrimitive
+            + num::FromPrimitive,
+    > Polynom4d<N>
+{
     /// polynomial regression
     /// ```
     /// use polynomial_optics::*;
@@ -853,46 +907,22 @@ impl<N: Add + Copy + std::iter::Sum<N> + PowUsize + Field + Scalar + AbsDiffEq>
     ///    }
     /// }
     /// println!("{:?}", p);
-    /// let res = Polynom4d::<_, 2>::fit(p);
+    /// let res = Polynom4d::<_, 2>::fit(p).unwrap();
     /// println!("{:?}", res);
     /// assert!(f == res);
     /// ```
-    pub fn fit(points: &[(N, N, N, N, N)], degree: usize) -> Polynom4d<N> {
-        let terms = Self::get_terms_fn(degree);
-        let num_terms = terms.len();
-        // println!("num of points: {}", points.len());
-        let mut now = std::time::Instant::now();
-        let mut m = Matrix::<N>::zero(num_terms, points.len());
-        let y = Vector::<N>::new_column(points.iter().map(|point| point.4).collect::<Vec<_>>());
-        // println!("init: {:?}", now.elapsed());
-        now = std::time::Instant::now();
-
-        // iter_mut is column first
-        for (iter, element) in m.iter_mut().enumerate() {
-            let (point, i) = (iter / (num_terms), iter % (num_terms));
-            let a = terms[i];
-            *element = (points[point].0).upow(a.0)
-                * (points[point].1).upow(a.1)
-                * (points[point].2).upow(a.2)
-                * (points[point].3).upow(a.3);
-        }
-        // println!("set Matrix: {:?} dim: {:?}", now.elapsed(), m.dim());
-        now = std::time::Instant::now();
-        let y = m.clone() * y;
-        let x = m.clone() * m.clone().transpose();
-
-        let c = x.solve(&y).unwrap();
-        // println!("solve: {:?}", now.elapsed());
-        now = std::time::Instant::now();
-        let mut coefficients = Vec::with_capacity(num_terms);
-        for element in c.iter() {
-            coefficients.push(*element);
-        }
+    pub fn fit(points: &[(N, N, N, N, N)], degree: usize) -> Result<Polynom4d<N>, String> {
+        let num_terms = crate::num_exponents(4, degree);
+        let now = std::time::Instant::now();
+        let design = Self::design_matrix(points, degree);
+        let values = points.iter().map(|point| point.4).collect::<Vec<_>>();
+
+        let coefficients = crate::solve_fit(design, num_terms, values)?;
         println!("coefficients: {:?}", now.elapsed());
-        Polynom4d {
+        Ok(Polynom4d {
             coefficients,
             degree,
-        }
+        })
     }
 }
 
@@ -0,0 +1,369 @@
+use std::path::Path;
+
+use crate::{Asphere, CoatingStack, Element, Glass, Lens, Properties, Sellmeier, VariableGap};
+
+/// radii above this (or 0) are treated as flat surfaces
+pub const FLAT_RADIUS: f64 = 100_000.;
+/// number of blades given to apertures read from a prescription
+pub const DEFAULT_NUM_BLADES: u32 = 6;
+
+/// the medium behind a surface of a prescription
+#[derive(Debug, Clone, PartialEq)]
+pub enum Material {
+    Air,
+    /// an aperture stop, air behind it
+    Iris,
+    Glass {
+        name: String,
+        /// refractive index at the d-line, if given
+        nd: Option<f64>,
+        /// abbe number, if given
+        vd: Option<f64>,
+    },
+}
+
+/// the distance from a surface to the next one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Thickness {
+    Fixed(f64),
+    /// `0.86/59.0`: one thickness per zoom position
+    Variable(Vec<f64>),
+    /// `BFL`: the back focal length, the sensor is put at the paraxial focus
+    BackFocus,
+}
+
+/// ## One row of a prescription table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    /// line in the file this surface was read from, starting at 1
+    pub line: usize,
+    pub radius: f64,
+    pub thickness: Thickness,
+    pub material: Material,
+    /// semi-aperture (`sa`) of the surface
+    pub semi_aperture: Option<f64>,
+    /// whether the material was prefixed with `cx_`
+    pub cylindrical: bool,
+}
+
+/// # A lens as given in a patent
+/// Reads the radius/thickness/material/nd/Vd/semi-aperture tables in `lenses/`:
+/// ```text
+/// // ANGENIEUX; US PAT 2,701,982; F/1.1; EFL=100mm
+/// #EFL     100     // effective focal length
+/// // radius  thickness  matl  index  vno   sa
+/// 164.12     10.99      SF5   1.673  32.2  54
+/// 559.28     0.23       air                54
+/// 10000      15.00      IRIS               25.5
+/// ```
+/// Each row describes one surface and the medium behind it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Prescription {
+    /// effective focal length(s) from the `#EFL` header
+    pub efl: Vec<f64>,
+    /// diagonal field of view(s) in degrees from the `#FOV` header
+    pub fov: Vec<f64>,
+    pub surfaces: Vec<Surface>,
+}
+
+impl Prescription {
+    /// Reads the prescription from the path provided.
+    pub fn read(path: &Path) -> Result<Prescription, String> {
+        match std::fs::read_to_string(path) {
+            Ok(str) => Self::parse(str.as_str()),
+            Err(err) => Err(format!("problem reading file: {}", err)),
+        }
+    }
+
+    /// Parses a prescription, errors contain the line number of the offending line.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let prescription = Prescription::parse(
+    ///     "#EFL 100 // focal length
+    ///      42.97   9.8   LAK9 1.6910 54.8 19.2
+    ///      -115.33 4.16  air            19.2
+    ///      100000  4.0   IRIS           15",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(prescription.efl, vec![100.]);
+    /// assert_eq!(prescription.surfaces.len(), 3);
+    /// assert_eq!(prescription.surfaces[2].material, Material::Iris);
+    ///
+    /// let err = Prescription::parse("42.97 9.8 LAK9\nfoo 1.0 air").unwrap_err();
+    /// assert!(err.starts_with("line 2:"));
+    /// ```
+    pub fn parse(str: &str) -> Result<Prescription, String> {
+        let mut prescription = Prescription::default();
+
+        for (line_num, line) in str.lines().enumerate() {
+            let line_num = line_num + 1;
+            let line = match line.find("//") {
+                Some(comment) => &line[..comment],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('#') {
+                let mut words = header.split_whitespace();
+                let target = match words.next().map(|word| word.to_uppercase()).as_deref() {
+                    Some("EFL") => &mut prescription.efl,
+                    Some("FOV") => &mut prescription.fov,
+                    // any other line starting with '#' is a comment
+                    _ => continue,
+                };
+                for word in words {
+                    target.push(parse_num(word, line_num, "header value")?);
+                }
+                continue;
+            }
+
+            prescription
+                .surfaces
+                .push(Self::parse_surface(line, line_num)?);
+        }
+
+        if prescription.surfaces.is_empty() {
+            return Err(String::from("no surfaces found"));
+        }
+        Ok(prescription)
+    }
+
+    fn parse_surface(line: &str, line_num: usize) -> Result<Surface, String> {
+        let columns = line.split_whitespace().collect::<Vec<_>>();
+        if columns.len() < 3 {
+            return Err(format!(
+                "line {}: expected at least radius, thickness and material, found {:?}",
+                line_num, line
+            ));
+        }
+
+        let radius = parse_num(columns[0], line_num, "radius")?;
+        let thickness = if columns[1].eq_ignore_ascii_case("BFL") {
+            Thickness::BackFocus
+        } else if columns[1].contains('/') {
+            Thickness::Variable(
+                columns[1]
+                    .split('/')
+                    .map(|thickness| parse_num(thickness, line_num, "thickness"))
+                    .collect::<Result<_, _>>()?,
+            )
+        } else {
+            Thickness::Fixed(parse_num(columns[1], line_num, "thickness")?)
+        };
+
+        let (cylindrical, material_name) = match columns[2].strip_prefix("cx_") {
+            Some(name) => (true, name),
+            None => (false, columns[2]),
+        };
+        let numbers = columns[3..]
+            .iter()
+            .map(|column| parse_num(column, line_num, "column"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (material, semi_aperture) = if material_name.eq_ignore_ascii_case("air") {
+            (Material::Air, numbers.first().copied())
+        } else if material_name.eq_ignore_ascii_case("iris") {
+            (Material::Iris, numbers.first().copied())
+        } else {
+            (
+                Material::Glass {
+                    name: material_name.to_string(),
+                    nd: numbers.get(0).copied(),
+                    vd: numbers.get(1).copied(),
+                },
+                numbers.get(2).copied(),
+            )
+        };
+
+        Ok(Surface {
+            line: line_num,
+            radius,
+            thickness,
+            material,
+            semi_aperture,
+            cylindrical,
+        })
+    }
+
+    /// Find the glass called `name` in `glasses`, ignoring case, dashes and the vendor
+    /// prefixes (N-, S-, P-) the catalogs put in front of lead-free glasses.
+    /// Unknown glasses and the ones named `abbe` are made up from nd and vd,
+    /// a constant index is used if only nd is given.
+    fn get_glass(
+        name: &str,
+        nd: Option<f64>,
+        vd: Option<f64>,
+        glasses: &[(String, Sellmeier)],
+        line: usize,
+    ) -> Result<Sellmeier, String> {
+        let normalize = |name: &str| name.to_uppercase().replace(&['-', ' '][..], "");
+        let name = normalize(name);
+        let prefixes = if name == "ABBE" {
+            &[][..]
+        } else {
+            &["", "N", "S", "P"][..]
+        };
+        for prefix in prefixes {
+            let name = format!("{}{}", prefix, name);
+            // catalog glasses are found without their `Catalog:` prefix as well
+            if let Some((_name, glass)) = glasses.iter().find(|(other, _)| {
+                normalize(other) == name || normalize(other.rsplit(':').next().unwrap()) == name
+            }) {
+                return Ok(*glass);
+            }
+        }
+        match (nd, vd) {
+            (Some(nd), Some(vd)) if vd > 0. => Ok(Sellmeier::from_abbe(nd, vd, None)),
+            (Some(nd), _) => Ok(Sellmeier::constant(nd)),
+            (None, _) => Err(format!(
+                "line {}: unknown glass {:?} without a refractive index",
+                line, name
+            )),
+        }
+    }
+
+    /// Convert to a `Lens` starting at 0 with every length multiplied by `scale`.
+    ///
+    /// `glasses` is searched for the material names, see `Sellmeier::get_all_glasses`.
+    /// Their lengths are in mm like the prescription, so they are scaled as well.
+    /// A cemented surface becomes the exit of the first glass and the entry of the second
+    /// at the same position, surfaces with air on both sides are dropped.
+    /// Variable thicknesses become `Lens.gaps`, the lens is set to zoom position 0.
+    pub fn to_lens(&self, glasses: &[(String, Sellmeier)], scale: f64) -> Result<Lens, String> {
+        let mut elements = vec![];
+        let mut gaps = vec![];
+        // line of the `BFL` thickness
+        let mut back_focus = None;
+        let mut position = 0.;
+        // the glass in front of the current surface
+        let mut before: Option<(Sellmeier, f64)> = None;
+
+        for (i, surface) in self.surfaces.iter().enumerate() {
+            let radius = if surface.radius == 0. || surface.radius.abs() > FLAT_RADIUS {
+                FLAT_RADIUS.copysign(surface.radius)
+            } else {
+                surface.radius
+            } * scale;
+
+            let after = match &surface.material {
+                Material::Glass { name, nd, vd } => {
+                    Some(Self::get_glass(name, *nd, *vd, glasses, surface.line)?.scaled(scale))
+                }
+                Material::Air | Material::Iris => None,
+            };
+
+            if let Some((sellmeier, _)) = before {
+                elements.push(Element {
+                    radius: -radius,
+                    properties: Properties::Glass(Glass {
+                        sellmeier,
+                        coating: CoatingStack::none(),
+                        entry: false,
+                        outer_ior: after.unwrap_or_else(Sellmeier::air),
+                        spherical: !surface.cylindrical,
+                        asphere: Asphere::sphere(),
+                        semi_diameter: surface.semi_aperture.map(|sa| sa * scale),
+                    }),
+                    position,
+                });
+            }
+            if let Material::Iris = surface.material {
+                elements.push(Element {
+                    radius: surface.semi_aperture.ok_or(format!(
+                        "line {}: IRIS without a semi-aperture",
+                        surface.line
+                    ))? * scale,
+                    properties: Properties::Aperture(DEFAULT_NUM_BLADES),
+                    position,
+                });
+            }
+            if let Some(sellmeier) = after {
+                elements.push(Element {
+                    radius,
+                    properties: Properties::Glass(Glass {
+                        sellmeier,
+                        coating: CoatingStack::none(),
+                        entry: true,
+                        outer_ior: before.map_or_else(Sellmeier::air, |(glass, _)| glass),
+                        spherical: !surface.cylindrical,
+                        asphere: Asphere::sphere(),
+                        semi_diameter: surface.semi_aperture.map(|sa| sa * scale),
+                    }),
+                    position,
+                });
+            }
+
+            before = after.map(|glass| (glass, radius));
+            position += match surface.thickness {
+                Thickness::Fixed(thickness) => thickness * scale,
+                Thickness::Variable(ref thicknesses) => {
+                    gaps.push(VariableGap {
+                        index: elements.len(),
+                        thicknesses: thicknesses.iter().map(|t| t * scale).collect(),
+                    });
+                    thicknesses[0] * scale
+                }
+                Thickness::BackFocus if i + 1 == self.surfaces.len() => {
+                    back_focus = Some(surface.line);
+                    0.
+                }
+                Thickness::BackFocus => {
+                    return Err(format!(
+                        "line {}: BFL is only allowed for the last surface",
+                        surface.line
+                    ))
+                }
+            };
+        }
+
+        if before.is_some() {
+            return Err(String::from("the last surface is not followed by air"));
+        }
+
+        // the thickness after the last surface is the distance to the sensor
+        let mut lens = Lens::new(elements, position);
+        lens.gaps = gaps;
+        if let Some(line) = back_focus {
+            // focus at the d-line
+            let paraxial = lens.paraxial(0.5876)?;
+            if paraxial.bfl <= 0. {
+                return Err(format!(
+                    "line {}: BFL is {:.3}, the focus is in front of the last surface",
+                    line, paraxial.bfl
+                ));
+            }
+            lens.sensor_dist = paraxial.back_focal_point;
+        }
+        Ok(lens)
+    }
+}
+
+fn parse_num(str: &str, line: usize, what: &str) -> Result<f64, String> {
+    str.parse()
+        .map_err(|_| format!("line {}: could not parse {} {:?}", line, what, str))
+}
+
+impl Lens {
+    /// Reads a patent-style prescription (`.txt` / `.fx`) from the path provided,
+    /// see `Prescription` for the format.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let lens = Lens::read_prescription(
+    ///     std::path::Path::new("../lenses/AngenieuxDGauss.fx"),
+    ///     &Sellmeier::get_all_glasses(),
+    ///     1.,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(lens.elements.len(), 17);
+    /// ```
+    pub fn read_prescription(
+        path: &Path,
+        glasses: &[(String, Sellmeier)],
+        scale: f64,
+    ) -> Result<Lens, String> {
+        Prescription::read(path)?.to_lens(glasses, scale)
+    }
+}
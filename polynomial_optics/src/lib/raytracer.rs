@@ -12,6 +12,11 @@ use serde::{Deserialize, Serialize};
 use cgmath::{num_traits::Pow, prelude::*, Vector2, Vector3};
 use tiny_skia::{Color, Pixmap};
 
+use crate::{
+    Asphere, CoatingStack, ColourPipeline, DispersionFormula, Filter, GhostPath,
+    InternalTransmittance, LightSource, SensorStack, Spectrum, SPECTRUM_SAMPLES,
+};
+
 ///struct DrawRay {
 ///  pos: vec2<f32>;
 ///  aperture_pos: vec2<f32>;
@@ -30,6 +35,91 @@ pub struct DrawRay {
     pub wavelength: f64,
 }
 
+/// polarization state of unpolarized light, see `Ray.polarization`
+pub const UNPOLARIZED: [f64; 2] = [0.5, 0.5];
+
+/// ## Why a ray stopped on its way through the lens system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RayStatus {
+    /// the ray is still travelling
+    Ok,
+    /// hit an aperture or a surface outside of its clear aperture
+    ClippedByAperture,
+    /// the ray does not intersect the surface at all
+    MissedSurface,
+    TotalInternalReflection,
+    /// the ray never reaches the sensor plane
+    BehindSensor,
+}
+
+impl Default for RayStatus {
+    fn default() -> Self {
+        RayStatus::Ok
+    }
+}
+
+/// `Lens::get_dots` stops tracing after this many rays per requested dot
+pub const MAX_TRIES_PER_DOT: usize = 100;
+
+/// ## Number of traced rays per `RayStatus`, see `Lens::get_dots`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RayStatusCounts {
+    pub ok: usize,
+    pub clipped_by_aperture: usize,
+    pub missed_surface: usize,
+    pub total_internal_reflection: usize,
+    pub behind_sensor: usize,
+}
+
+impl RayStatusCounts {
+    pub fn add(&mut self, status: RayStatus) {
+        match status {
+            RayStatus::Ok => self.ok += 1,
+            RayStatus::ClippedByAperture => self.clipped_by_aperture += 1,
+            RayStatus::MissedSurface => self.missed_surface += 1,
+            RayStatus::TotalInternalReflection => self.total_internal_reflection += 1,
+            RayStatus::BehindSensor => self.behind_sensor += 1,
+        }
+    }
+
+    /// number of rays that did not make it to the sensor
+    pub fn terminated(&self) -> usize {
+        self.clipped_by_aperture
+            + self.missed_surface
+            + self.total_internal_reflection
+            + self.behind_sensor
+    }
+
+    pub fn total(&self) -> usize {
+        self.ok + self.terminated()
+    }
+}
+
+impl std::ops::Add for RayStatusCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            ok: self.ok + other.ok,
+            clipped_by_aperture: self.clipped_by_aperture + other.clipped_by_aperture,
+            missed_surface: self.missed_surface + other.missed_surface,
+            total_internal_reflection: self.total_internal_reflection
+                + other.total_internal_reflection,
+            behind_sensor: self.behind_sensor + other.behind_sensor,
+        }
+    }
+}
+
+impl std::iter::FromIterator<RayStatus> for RayStatusCounts {
+    fn from_iter<I: IntoIterator<Item = RayStatus>>(iter: I) -> Self {
+        let mut counts = Self::default();
+        for status in iter {
+            counts.add(status);
+        }
+        counts
+    }
+}
+
 /// ## A ray at a plane in the lens system
 #[derive(Debug, Clone, Copy)]
 pub struct Ray {
@@ -40,10 +130,14 @@ pub struct Ray {
     /// direction of the Ray, 0 if in the path of the optical axis, is a unit vector
     pub d: cgmath::Vector3<f64>,
     pub strength: f64,
+    /// fractions of `strength` that are s and p polarized at the last interface, sum to 1
+    pub polarization: [f64; 2],
     pub ghost_num: u32,
     pub init_pos: [f64; 4],
     pub aperture_pos: [f64; 2],
     pub entry_pos: [f64; 2],
+    /// why the ray stopped, the first termination is kept
+    pub status: RayStatus,
 }
 
 impl Default for Ray {
@@ -52,11 +146,13 @@ impl Default for Ray {
             o: Vector3::new(0., 0., 0.),
             d: Vector3::new(0., 0., 1.),
             strength: 1.,
+            polarization: UNPOLARIZED,
             wavelength: 0.5,
             ghost_num: 0,
             init_pos: [0., 0., 0., 0.],
             aperture_pos: [0., 0.],
             entry_pos: [0., 0.],
+            status: RayStatus::Ok,
         }
     }
 }
@@ -83,86 +179,6 @@ impl Ray {
 
         self.o += self.d * num_z;
     }
-
-    pub fn get_rgb(&self) -> (u8, u8, u8) {
-        Self::wave_length_to_rgb(self.wavelength)
-    }
-
-    /**
-     * Taken from Earl F. Glynn's web page:
-     * <a href="http://www.efg2.com/Lab/ScienceAndEngineering/Spectra.htm">Spectra Lab Report</a>
-     */
-    fn wave_length_to_rgb(wavelength: f64) -> (u8, u8, u8) {
-        // convert from µm to nm
-        let wavelength = wavelength * 1000.;
-        let gamma = 0.80;
-        let intensity_max = 255.;
-        let factor;
-        let red;
-        let green;
-        let blue;
-
-        if (380. ..440.).contains(&wavelength) {
-            red = -(wavelength - 440.) / (440. - 380.);
-            green = 0.0;
-            blue = 1.0;
-        } else if (440. ..490.).contains(&wavelength) {
-            red = 0.0;
-            green = (wavelength - 440.) / (490. - 440.);
-            blue = 1.0;
-        } else if (490. ..510.).contains(&wavelength) {
-            red = 0.0;
-            green = 1.0;
-            blue = -(wavelength - 510.) / (510. - 490.);
-        } else if (510. ..580.).contains(&wavelength) {
-            red = (wavelength - 510.) / (580. - 510.);
-            green = 1.0;
-            blue = 0.0;
-        } else if (580. ..645.).contains(&wavelength) {
-            red = 1.0;
-            green = -(wavelength - 645.) / (645. - 580.);
-            blue = 0.0;
-        } else if (645. ..781.).contains(&wavelength) {
-            red = 1.0;
-            green = 0.0;
-            blue = 0.0;
-        } else {
-            red = 0.0;
-            green = 0.0;
-            blue = 0.0;
-        }
-
-        // Let the intensity fall off near the vision limits
-
-        if (380. ..420.).contains(&wavelength) {
-            factor = 0.3 + 0.7 * (wavelength - 380.) / (420. - 380.);
-        } else if (420. ..701.).contains(&wavelength) {
-            factor = 1.0;
-        } else if (701. ..781.).contains(&wavelength) {
-            factor = 0.3 + 0.7 * (780. - wavelength) / (780. - 700.);
-        } else {
-            factor = 0.0;
-        }
-
-        // Don't want 0^x = 1 for x <> 0
-        (
-            if red == 0.0 {
-                0
-            } else {
-                num::Float::round(intensity_max * (red * factor).pow(gamma)) as u8
-            },
-            if green == 0.0 {
-                0
-            } else {
-                num::Float::round(intensity_max * (green * factor).pow(gamma)) as u8
-            },
-            if blue == 0.0 {
-                0
-            } else {
-                num::Float::round(intensity_max * (blue * factor).pow(gamma)) as u8
-            },
-        )
-    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -303,6 +319,12 @@ impl QuarterWaveCoating {
 pub struct Sellmeier {
     pub b: [f64; 3],
     pub c: [f64; 3],
+    /// absorption inside the glass, `None` for clear glass
+    #[serde(default)]
+    pub transmittance: Option<InternalTransmittance>,
+    /// how `b` and `c` are used, glasses from catalogs may not use the Sellmeier equation
+    #[serde(default, skip_serializing_if = "DispersionFormula::is_sellmeier")]
+    pub formula: DispersionFormula,
 }
 
 impl Hash for Sellmeier {
@@ -313,6 +335,8 @@ impl Hash for Sellmeier {
             let c = self.c[i].to_bits();
             c.hash(state);
         }
+        self.transmittance.hash(state);
+        self.formula.hash(state);
     }
 }
 
@@ -321,16 +345,13 @@ impl Sellmeier {
         Self {
             b: [0., 0., 0.],
             c: [0., 0., 0.],
+            transmittance: None,
+            formula: DispersionFormula::Sellmeier,
         }
     }
 
     pub fn ior(&self, wavelength: f64) -> f64 {
-        let wavelength_sq = wavelength * wavelength;
-        let mut n_sq = 1.;
-        for i in 0..3 {
-            n_sq += (self.b[i] * wavelength_sq) / (wavelength_sq - self.c[i]);
-        }
-        n_sq.sqrt()
+        self.formula.ior(&self.b, &self.c, wavelength)
     }
 }
 
@@ -339,6 +360,8 @@ impl Sellmeier {
         Self {
             b: [1.03961212, 0.231792344, 1.01046945],
             c: [6.00069867e-3, 2.00179144e-2, 1.03560653e2],
+            transmittance: None,
+            formula: DispersionFormula::Sellmeier,
         }
     }
 
@@ -350,24 +373,7 @@ impl Sellmeier {
     /// panic!();
     /// ```
     pub fn get_all_glasses() -> Vec<(String, Sellmeier)> {
-        let mut glasses = vec![];
-
-        let mut rdr =
-            csv::ReaderBuilder::new().from_reader(include_str!("../../LaCroix.csv").as_bytes());
-        for result in rdr.records().flatten() {
-            let b = [
-                result[1].parse().unwrap(),
-                result[2].parse().unwrap(),
-                result[3].parse().unwrap(),
-            ];
-            let c = [
-                result[4].parse().unwrap(),
-                result[5].parse().unwrap(),
-                result[6].parse().unwrap(),
-            ];
-            let glass = Sellmeier { b, c };
-            glasses.push((result[0].trim().to_string(), glass));
-        }
+        let mut glasses = Self::parse_csv_catalog(include_str!("../../LaCroix.csv")).unwrap();
         glasses.sort_by_key(|(name, _glass)| name.clone());
         glasses
     }
@@ -375,15 +381,33 @@ impl Sellmeier {
 
 /// ## Properties of a particular glass
 /// saves ior and coating
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Glass {
     /// ior vs air
     pub sellmeier: Sellmeier,
-    /// coating - modifies wavelength, only used for reflection
-    pub coating: QuarterWaveCoating,
+    /// coating of the surface, changes how much light is reflected and transmitted
+    pub coating: CoatingStack,
     pub entry: bool,
     pub outer_ior: Sellmeier,
     pub spherical: bool,
+    /// deviation of the surface from a sphere
+    #[serde(default)]
+    pub asphere: Asphere,
+    /// radius of the clear aperture, rays hitting the surface further out are stopped
+    #[serde(default)]
+    pub semi_diameter: Option<f64>,
+}
+
+impl Hash for Glass {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sellmeier.hash(state);
+        self.coating.hash(state);
+        self.entry.hash(state);
+        self.outer_ior.hash(state);
+        self.spherical.hash(state);
+        self.asphere.hash(state);
+        self.semi_diameter.map(f64::to_bits).hash(state);
+    }
 }
 
 /// # One element in a lens system
@@ -393,7 +417,7 @@ pub struct Glass {
 ///    radius: 3.,
 ///    properties: Properties::Glass(Glass {
 ///        ior: 1.5,
-///        coating: QuarterWaveCoating::none(),
+///        coating: CoatingStack::none(),
 ///        entry: true,
 ///        spherical: true,
 ///    }),
@@ -430,6 +454,29 @@ impl Hash for Element {
 pub enum Properties {
     Glass(Glass),
     Aperture(u32),
+    /// the sensor surface at the end of a `SensorStack`, with its reflectance
+    Sensor(Spectrum),
+    /// one face of a filter plate
+    Filter(Filter),
+}
+
+impl Properties {
+    /// whether light reflected at the element can form a ghost
+    pub fn reflects(&self) -> bool {
+        matches!(
+            self,
+            Properties::Glass(_) | Properties::Sensor(_) | Properties::Filter(_)
+        )
+    }
+
+    /// the glass light refracts at, if the element is a glass surface or a filter face
+    pub fn glass(&self) -> Option<Glass> {
+        match self {
+            Properties::Glass(glass) => Some(*glass),
+            Properties::Filter(filter) => Some(filter.glass()),
+            Properties::Aperture(_) | Properties::Sensor(_) => None,
+        }
+    }
 }
 
 impl Ray {
@@ -443,11 +490,33 @@ impl Ray {
         }
     }
 
-    fn fresnel_r(t1: f64, t2: f64, n1: f64, n2: f64) -> f64 {
-        let s = 0.5 * ((n1 * t1.cos() - n2 * t2.cos()) / (n1 * t1.cos() + n2 * t2.cos())).pow(2);
-        let p = 0.5 * ((n1 * t2.cos() - n2 * t1.cos()) / (n1 * t2.cos() + n2 * t1.cos())).pow(2);
+    /// set the fraction `s` of the intensity that is s polarized, 0.5 for unpolarized light
+    pub fn polarized(mut self, s: f64) -> Ray {
+        self.polarization = [s, 1. - s];
+        self
+    }
 
-        s + p
+    /// Scale the s and p parts of the ray by `factors`, e.g. the reflectance of an interface.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let mut ray = Ray::default();
+    /// ray.polarize([0.2, 0.]);
+    /// assert_eq!(ray.strength, 0.1);
+    /// // only s polarized light is left
+    /// assert_eq!(ray.polarization, [1., 0.]);
+    /// ray.polarize([0.5, 0.5]);
+    /// assert_eq!(ray.strength, 0.05);
+    /// ```
+    pub fn polarize(&mut self, factors: [f64; 2]) {
+        let parts = [
+            self.polarization[0] * factors[0],
+            self.polarization[1] * factors[1],
+        ];
+        let total = parts[0] + parts[1];
+        self.strength *= total;
+        if total > 0. {
+            self.polarization = [parts[0] / total, parts[1] / total];
+        }
     }
 
     fn propagate_element(
@@ -458,7 +527,24 @@ impl Ray {
         reflect: bool,
         entry: bool,
         cylindrical: bool,
-    ) {
+    ) -> RayStatus {
+        let start = self.o;
+        if !glass.asphere.is_sphere() {
+            let radius = if entry { *radius } else { -*radius };
+            let (intersection, normal) = glass
+                .asphere
+                .intersect(self.o, self.d, position, radius, cylindrical);
+            self.o = intersection;
+            if !intersection.is_finite() {
+                return self.terminate(RayStatus::MissedSurface);
+            }
+            self.absorb(glass, start, entry);
+            if self.clip_semi_diameter(glass) {
+                return self.status;
+            }
+            return self.interact(glass, normal, reflect, entry);
+        }
+
         let intersection = if cylindrical {
             // cylindrical: x is not affected by curvature
 
@@ -478,12 +564,14 @@ impl Ray {
             let d: Vector2<f64> = Vector2 {
                 x: self.d.y,
                 y: self.d.z,
-            }
-            .normalize();
+            };
+            // distances along d in the y-z plane have to be scaled to distances along self.d
+            let len = d.magnitude();
+            let d = d / len;
             let delta: f64 = d.dot(o - c).pow(2) - ((o - c).magnitude().pow(2) - radius.pow(2));
 
-            let d1 = -(d.dot(o - c)) - delta.sqrt();
-            let d2 = -(d.dot(o - c)) + delta.sqrt();
+            let d1 = (-(d.dot(o - c)) - delta.sqrt()) / len;
+            let d2 = (-(d.dot(o - c)) + delta.sqrt()) / len;
 
             if (entry == (self.d.z > 0.)) == (radius > &0.) {
                 self.o + self.d * d1
@@ -516,6 +604,13 @@ impl Ray {
         };
 
         self.o = intersection;
+        if !intersection.is_finite() {
+            return self.terminate(RayStatus::MissedSurface);
+        }
+        self.absorb(glass, start, entry);
+        if self.clip_semi_diameter(glass) {
+            return self.status;
+        }
 
         let normal = if cylindrical {
             let c = Vector2::new(
@@ -529,8 +624,7 @@ impl Ray {
             let intersection: Vector2<f64> = Vector2 {
                 x: intersection.y,
                 y: intersection.z,
-            }
-            .normalize();
+            };
 
             let normal2d = intersection - c;
 
@@ -562,25 +656,80 @@ impl Ray {
             }
         };
 
+        self.interact(glass, normal, reflect, entry)
+    }
+
+    /// stop the ray with `status`, the direction is zeroed as well
+    fn terminate(&mut self, status: RayStatus) -> RayStatus {
+        self.d *= 0.;
+        self.status = status;
+        status
+    }
+
+    /// light reaching a surface of `glass` from inside of it was absorbed on the way from `start`
+    fn absorb(&mut self, glass: &Glass, start: Vector3<f64>, entry: bool) {
+        if entry != (self.d.z > 0.) {
+            let absorption = glass.sellmeier.absorption(self.wavelength);
+            self.strength *= (-absorption * (self.o - start).magnitude()).exp();
+        }
+    }
+
+    /// stop the ray if it hit the surface of `glass` outside of its clear aperture
+    fn clip_semi_diameter(&mut self, glass: &Glass) -> bool {
+        match glass.semi_diameter {
+            Some(semi_diameter) if self.o.x.hypot(self.o.y) > semi_diameter => {
+                self.terminate(RayStatus::ClippedByAperture);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// move to the sensor plane at `position`, the ray is stopped if it does not travel towards it
+    fn hit_sensor(&mut self, position: f64) -> RayStatus {
+        if !((position - self.o.z) / self.d.z >= 0.) {
+            return self.terminate(RayStatus::BehindSensor);
+        }
+        self.mov_plane(position);
+        self.status
+    }
+
+    /// like a glass surface, light reaching a face from inside the plate crossed it once
+    fn propagate_filter(&mut self, element: &Element, filter: &Filter, reflect: bool) -> RayStatus {
+        if filter.entry != (self.d.z > 0.) {
+            let transmittance = filter.transmittance.at(self.wavelength);
+            self.polarize([transmittance, transmittance]);
+        }
+        self.propagate_element(
+            &element.radius,
+            &filter.glass(),
+            element.position,
+            reflect,
+            filter.entry,
+            false,
+        )
+    }
+
+    /// reflect or refract at the surface of `glass` with `normal` facing against the ray
+    fn interact(
+        &mut self,
+        glass: &Glass,
+        normal: Vector3<f64>,
+        reflect: bool,
+        entry: bool,
+    ) -> RayStatus {
         if reflect {
             let d_in = self.d;
 
             self.d = self.d - 2.0 * normal.dot(self.d) * normal;
 
-            self.strength *= glass.coating.fresnel_ar(
+            self.polarize(glass.coating.reflectance(
                 d_in.angle(-normal).0,
                 self.wavelength,
-                if entry {
-                    glass.outer_ior.ior(self.wavelength)
-                } else {
-                    glass.sellmeier.ior(self.wavelength)
-                },
-                if entry {
-                    glass.sellmeier.ior(self.wavelength)
-                } else {
-                    glass.outer_ior.ior(self.wavelength)
-                },
-            );
+                glass.outer_ior.ior(self.wavelength),
+                glass.sellmeier.ior(self.wavelength),
+                entry == (d_in.z > 0.),
+            ));
             //     d_in.angle(normal).0,
             //     self.d.angle(-normal).0,
             //     if entry == (self.d.z > 0.) {
@@ -609,30 +758,24 @@ impl Ray {
             if k < 0.0 {
                 // total reflection
                 // println!("total reflection");
-                self.d *= 0.0; // or genDType(0.0)
+                self.terminate(RayStatus::TotalInternalReflection);
             } else {
                 self.d = eta * self.d - (eta * normal.dot(self.d) + k.sqrt()) * normal;
             }
 
-            self.strength *= 1.0
-                - Ray::fresnel_r(
-                    d_in.angle(-normal).0,
-                    self.d.angle(-normal).0,
-                    if entry {
-                        1.0
-                    } else {
-                        glass.sellmeier.ior(self.wavelength)
-                    },
-                    if entry {
-                        glass.sellmeier.ior(self.wavelength)
-                    } else {
-                        1.0
-                    },
-                );
+            let [s, p] = glass.coating.reflectance(
+                d_in.angle(-normal).0,
+                self.wavelength,
+                glass.outer_ior.ior(self.wavelength),
+                glass.sellmeier.ior(self.wavelength),
+                entry == (d_in.z > 0.),
+            );
+            self.polarize([1. - s, 1. - p]);
         }
+        self.status
     }
 
-    fn clip_poly(&mut self, pos: f64, num_edge: u32, size: f64) {
+    fn clip_poly(&mut self, pos: f64, num_edge: u32, size: f64) -> RayStatus {
         self.mov_plane(pos);
 
         let mut clipped = false;
@@ -647,13 +790,17 @@ impl Ray {
             clipped = clipped || (dist > size);
         }
         if clipped {
-            self.d *= 0.;
+            self.terminate(RayStatus::ClippedByAperture);
         }
+        self.status
     }
 
-    /// propagate a ray through an element
-    ///
-    pub fn propagate(&mut self, element: &Element) {
+    /// propagate a ray through an element,
+    /// a ray that already stopped is left as it is
+    pub fn propagate(&mut self, element: &Element) -> RayStatus {
+        if self.status != RayStatus::Ok {
+            return self.status;
+        }
         match element.properties {
             Properties::Glass(glass) => self.propagate_element(
                 &element.radius,
@@ -661,18 +808,23 @@ impl Ray {
                 element.position,
                 false,
                 glass.entry,
-                !glass.entry,
+                !glass.spherical,
             ),
             Properties::Aperture(properties) => {
                 self.aperture_pos = self.intersect(element.position);
                 self.clip_poly(element.position, properties, element.radius)
             }
-        };
+            Properties::Sensor(_) => self.hit_sensor(element.position),
+            Properties::Filter(filter) => self.propagate_filter(element, &filter, false),
+        }
     }
 
-    /// reflect a Ray from an element
-    ///
-    pub fn reflect(&mut self, element: &Element) {
+    /// reflect a Ray from an element,
+    /// a ray that already stopped is left as it is
+    pub fn reflect(&mut self, element: &Element) -> RayStatus {
+        if self.status != RayStatus::Ok {
+            return self.status;
+        }
         match element.properties {
             Properties::Glass(glass) => self.propagate_element(
                 &element.radius,
@@ -680,12 +832,57 @@ impl Ray {
                 element.position,
                 true,
                 glass.entry,
-                !glass.entry,
+                !glass.spherical,
             ),
             Properties::Aperture(properties) => {
                 self.clip_poly(element.position, properties, element.radius)
             }
-        };
+            Properties::Sensor(reflectance) => {
+                if self.hit_sensor(element.position) == RayStatus::Ok {
+                    self.d.z = -self.d.z;
+                    let reflectance = reflectance.at(self.wavelength);
+                    self.polarize([reflectance, reflectance]);
+                }
+                self.status
+            }
+            Properties::Filter(filter) => self.propagate_filter(element, &filter, true),
+        }
+    }
+}
+
+/// ## A distance in a zoom lens that changes with the zoom position
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VariableGap {
+    /// the gap is in front of `elements[index]`, `index == elements.len()` is the gap to the sensor
+    pub index: usize,
+    /// the thickness at each zoom position, spread evenly over zoom 0..=1
+    pub thicknesses: Vec<f64>,
+}
+
+impl VariableGap {
+    /// linearly interpolate the thickness at `zoom` in 0..=1
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let gap = VariableGap {
+    ///     index: 0,
+    ///     thicknesses: vec![1., 3., 2.],
+    /// };
+    /// assert_eq!(gap.at(0.), 1.);
+    /// assert_eq!(gap.at(0.25), 2.);
+    /// assert_eq!(gap.at(0.75), 2.5);
+    /// assert_eq!(gap.at(1.), 2.);
+    /// ```
+    pub fn at(&self, zoom: f64) -> f64 {
+        match self.thicknesses.len() {
+            0 => 0.,
+            1 => self.thicknesses[0],
+            len => {
+                let pos = zoom.clamp(0., 1.) * (len - 1) as f64;
+                let i = (pos.floor() as usize).min(len - 2);
+                let t = pos - i as f64;
+                self.thicknesses[i] * (1. - t) + self.thicknesses[i + 1] * t
+            }
+        }
     }
 }
 
@@ -693,6 +890,21 @@ impl Ray {
 pub struct Lens {
     pub elements: Vec<Element>,
     pub sensor_dist: f64,
+    /// distances that change with `zoom`
+    #[serde(default)]
+    pub gaps: Vec<VariableGap>,
+    /// zoom position in 0..=1 the element positions are currently set for
+    #[serde(default)]
+    pub zoom: f64,
+    /// cover glass and sensor at the end of `elements`, see `Lens::set_sensor_stack`
+    #[serde(default)]
+    pub sensor_stack: Option<SensorStack>,
+    /// spectrum of the light `Lens::draw` traces
+    #[serde(default)]
+    pub light_source: LightSource,
+    /// colours `Lens::draw` draws the rays in
+    #[serde(default)]
+    pub colour: ColourPipeline,
 }
 
 impl Hash for Lens {
@@ -758,7 +970,27 @@ impl Lens {
         Self {
             elements,
             sensor_dist,
+            gaps: vec![],
+            zoom: 0.,
+            sensor_stack: None,
+            light_source: LightSource::default(),
+            colour: ColourPipeline::default(),
+        }
+    }
+
+    /// Move the elements behind each variable gap to match the zoom position `zoom` in 0..=1.
+    ///
+    /// The sensor is behind every gap, so it moves along with the elements.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        let zoom = zoom.clamp(0., 1.);
+        for gap in &self.gaps {
+            let shift = gap.at(zoom) - gap.at(self.zoom);
+            for element in self.elements.iter_mut().skip(gap.index) {
+                element.position += shift;
+            }
+            self.sensor_dist += shift;
         }
+        self.zoom = zoom;
     }
 
     /// get elements in form:
@@ -783,7 +1015,15 @@ impl Lens {
                     }
                     elements.push(element.radius as f32);
                 }
+                Properties::Filter(filter) => {
+                    match filter.entry {
+                        true => elements.push((element.position + element.radius) as f32),
+                        false => elements.push((element.position - element.radius) as f32),
+                    }
+                    elements.push(element.radius as f32);
+                }
                 Properties::Aperture(aperture) => (), //TODO: render Aperture
+                Properties::Sensor(_) => (),
             }
         }
         elements
@@ -794,10 +1034,22 @@ impl Lens {
     /// struct Element {
     ///   radius: f32;
     ///   glass: Sellmeier;
+    ///   outer_ior: Sellmeier;
+    ///   coating_start: f32;
+    ///   coating_num: f32;
     ///   position: f32;
-    ///   entry: bool;
+    ///   entry: bool; // 2: aperture, 3: sensor, 4 and 5: front and back of a filter
     ///   spherical: bool;
+    ///   asphere: Asphere;
+    ///   semi_diameter: f32;
+    ///   absorption: [f32; 9]; // absorption coefficient of the glass at the `Spectrum` samples
+    ///   formula: f32; // `DispersionFormula` of glass
+    ///   outer_formula: f32; // `DispersionFormula` of outer_ior
     /// };
+    /// pub struct Asphere {
+    ///     pub k: f64,
+    ///     pub a: [f64; 7],
+    /// }
     /// pub struct Sellmeier {
     ///     pub b: [f64; 3],
     ///     pub c: [f64; 3],
@@ -806,6 +1058,8 @@ impl Lens {
     /// only works if elements are entry and exit alternatively
     pub fn get_rt_elements_buffer(&self) -> Vec<f32> {
         let mut elements = vec![];
+        // index of the first layer in `Lens::get_coatings_buffer`
+        let mut coating_start = 0;
 
         for element in &self.elements {
             match element.properties {
@@ -823,11 +1077,17 @@ impl Lens {
                     elements.push(glass.outer_ior.c[0] as f32);
                     elements.push(glass.outer_ior.c[1] as f32);
                     elements.push(glass.outer_ior.c[2] as f32);
-                    elements.push(glass.coating.ior as f32);
-                    elements.push(glass.coating.thickness as f32);
+                    elements.push(coating_start as f32);
+                    elements.push(glass.coating.layers().len() as f32);
+                    coating_start += glass.coating.layers().len();
                     elements.push(element.position as f32);
                     elements.push(glass.entry as i32 as f32);
                     elements.push(glass.spherical as i32 as f32);
+                    elements.push(glass.asphere.k as f32);
+                    for a in glass.asphere.a {
+                        elements.push(a as f32);
+                    }
+                    elements.push(glass.semi_diameter.unwrap_or(0.) as f32);
                 }
                 Properties::Aperture(aperture) => {
                     elements.push(element.radius as f32);
@@ -852,8 +1112,70 @@ impl Lens {
                     elements.push(element.position as f32);
                     elements.push(2_f32);
                     elements.push(2_f32);
+                    // no asphere
+                    elements.extend([0_f32; 8]);
+                    // no clear aperture
+                    elements.push(0_f32);
+                }
+                Properties::Sensor(reflectance) => {
+                    elements.push(0_f32);
+                    // the reflectance samples in place of the sellmeier coefficients
+                    for sample in reflectance.samples {
+                        elements.push(sample as f32);
+                    }
+                    elements.extend([0_f32; 12 - SPECTRUM_SAMPLES]);
+                    // no coating
+                    elements.push(0_f32);
+                    elements.push(0_f32);
+                    elements.push(element.position as f32);
+                    elements.push(3_f32);
+                    elements.push(0_f32);
+                    // no asphere
+                    elements.extend([0_f32; 8]);
+                    // no clear aperture
+                    elements.push(0_f32);
+                }
+                Properties::Filter(filter) => {
+                    elements.push(element.radius as f32);
+                    elements.push(filter.material.b[0] as f32);
+                    elements.push(filter.material.b[1] as f32);
+                    elements.push(filter.material.b[2] as f32);
+                    elements.push(filter.material.c[0] as f32);
+                    elements.push(filter.material.c[1] as f32);
+                    elements.push(filter.material.c[2] as f32);
+                    // the transmittance samples in place of the outer ior,
+                    // the coating and the first asphere coefficients
+                    let samples = filter.transmittance.samples;
+                    for sample in &samples[..6] {
+                        elements.push(*sample as f32);
+                    }
+                    // no coating
+                    elements.push(0_f32);
+                    elements.push(0_f32);
+                    elements.push(element.position as f32);
+                    elements.push(if filter.entry { 4_f32 } else { 5_f32 });
+                    elements.push(1_f32);
+                    for sample in &samples[6..] {
+                        elements.push(*sample as f32);
+                    }
+                    elements.extend([0_f32; 8 - (SPECTRUM_SAMPLES - 6)]);
+                    elements.push(filter.semi_diameter.unwrap_or(0.) as f32);
                 }
             }
+            // the transmittance of a filter already includes its absorption
+            let absorption = match element.properties {
+                Properties::Glass(glass) => glass.sellmeier.absorption_samples(),
+                _ => [0.; SPECTRUM_SAMPLES],
+            };
+            elements.extend(absorption.iter().map(|absorption| *absorption as f32));
+            let formulas = match element.properties {
+                Properties::Glass(glass) => [glass.sellmeier.formula, glass.outer_ior.formula],
+                Properties::Filter(filter) => {
+                    [filter.material.formula, DispersionFormula::Sellmeier]
+                }
+                _ => [DispersionFormula::Sellmeier; 2],
+            };
+            elements.extend(formulas.iter().map(|formula| *formula as i32 as f32));
         }
 
         elements
@@ -865,12 +1187,12 @@ impl Lens {
             let mut ghost_num = 0;
             for i in 0..self.elements.len() - 1 {
                 for j in i + 1..self.elements.len() {
-                    if let Properties::Glass(_) = self.elements[i].properties {
-                        if let Properties::Glass(_) = self.elements[j].properties {
-                            ghost_num += 1;
-                            if ghost_num == which_ghost || which_ghost == 0 {
-                                return Some([i as u32, j as u32]);
-                            }
+                    if self.elements[i].properties.reflects()
+                        && self.elements[j].properties.reflects()
+                    {
+                        ghost_num += 1;
+                        if ghost_num == which_ghost || which_ghost == 0 {
+                            return Some([i as u32, j as u32]);
                         }
                     }
                 }
@@ -886,12 +1208,12 @@ impl Lens {
             let mut ghost_num = 0;
             for i in 0..self.elements.len() - 1 {
                 for j in i + 1..self.elements.len() {
-                    if let Properties::Glass(_) = self.elements[i].properties {
-                        if let Properties::Glass(_) = self.elements[j].properties {
-                            ghost_num += 1;
-                            if ghost_num == which_ghost || which_ghost == 0 {
-                                rays.push([i as u32, j as u32]);
-                            }
+                    if self.elements[i].properties.reflects()
+                        && self.elements[j].properties.reflects()
+                    {
+                        ghost_num += 1;
+                        if ghost_num == which_ghost || which_ghost == 0 {
+                            rays.push([i as u32, j as u32]);
                         }
                     }
                 }
@@ -904,14 +1226,13 @@ impl Lens {
     }
 
     /// draws z y of the distance
-    pub fn draw_rays(pixmap: &mut Pixmap, ray1: &Ray, ray2: &Ray) {
+    pub fn draw_rays(pixmap: &mut Pixmap, ray1: &Ray, ray2: &Ray, color: [u8; 3]) {
         let mut paint = tiny_skia::Paint::default();
         //paint.set_color(self.color);
-        let color = ray1.get_rgb();
         paint.set_color(Color::from_rgba8(
-            color.0,
-            color.1,
-            color.2,
+            color[0],
+            color[1],
+            color[2],
             (255.0 * ray1.strength.sqrt() * 0.5) as u8,
         ));
         paint.anti_alias = true;
@@ -952,40 +1273,27 @@ impl Lens {
         );
     }
 
-    /// Planks law in SI units
-    /// ```
-    /// # use polynomial_optics::raytracer::*;
-    /// println!("{}", Lens::boltzmann(450./1_000_000., 3000.));
-    /// panic!();
-    /// ```
-    fn plank(wavelen: f64, temp: f64) -> f64 {
-        let h = 6.62607015e-34; // J/Hz
-        let b = 1.380649e-23; // J/K
-        let c = 299792458.; // m/s
-        (2. * h * c.pow(2))
-            / (wavelen.pow(5))
-            / (std::f64::consts::E.pow((h * c) / (wavelen * b * temp)) - 1.)
-    }
-
-    fn str_from_wavelen(wavelen: f64) -> f64 {
-        // -((wavelen - 0.45) * 4.) * ((wavelen - 0.45) * 4.) + 1.
-        // std::f64::consts::E
-        //     .pow(-((wavelen - 0.55) * 8.) * (wavelen - 0.55) * 8. - 15. * (wavelen - 0.55))
-        //     / 2.5
-        Self::plank(wavelen / 1_000., 5_000.) / 250.
+    fn str_from_wavelen(&self, wavelen: f64) -> f64 {
+        self.light_source.power(wavelen)
     }
 
     pub fn draw(&self, pixmap: &mut Pixmap) {
         let num_rays = 5000;
         let width = 2.0;
         let wave_num = 20;
+        let start_wavelen = 0.38;
+        let end_wavelen = 0.78;
+        let wavelengths = (0..wave_num)
+            .map(|wavelen| {
+                start_wavelen + wavelen as f64 * ((end_wavelen - start_wavelen) / wave_num as f64)
+            })
+            .collect::<Vec<_>>();
+        let colours = self.colour.get_colours8(&wavelengths);
         for ray_num in 0..num_rays {
             let wavelen = ray_num % wave_num;
-            let start_wavelen = 0.38;
-            let end_wavelen = 0.78;
-            let wavelength =
-                start_wavelen + wavelen as f64 * ((end_wavelen - start_wavelen) / wave_num as f64);
-            let strength = Self::str_from_wavelen(wavelength) / 10.;
+            let wavelength = wavelengths[wavelen];
+            let colour = colours[wavelen];
+            let strength = self.str_from_wavelen(wavelength) / 10.;
             // for i in 0..self.elements.len() {
             //     for j in i..self.elements.len() {
             for i in 0..=0 {
@@ -1015,32 +1323,32 @@ impl Lens {
                             // reflect at the first element,
                             // which is further down the optical path
                             ray.reflect(element);
-                            Lens::draw_rays(pixmap, &one, &ray);
+                            Lens::draw_rays(pixmap, &one, &ray, colour);
                             one = ray;
                             // propagate backwards through system
                             // until the second reflection
                             for k in (i + 1..j).rev() {
                                 ray.propagate(&self.elements[k]);
-                                Lens::draw_rays(pixmap, &one, &ray);
+                                Lens::draw_rays(pixmap, &one, &ray, colour);
                                 one = ray;
                             }
                             ray.reflect(&self.elements[i]);
-                            Lens::draw_rays(pixmap, &one, &ray);
+                            Lens::draw_rays(pixmap, &one, &ray, colour);
                             one = ray;
                             for k in i + 1..j {
                                 ray.propagate(&self.elements[k]);
-                                Lens::draw_rays(pixmap, &one, &ray);
+                                Lens::draw_rays(pixmap, &one, &ray, colour);
                                 one = ray;
                             }
                             // println!("strength: {}", ray.strength);
                         } else {
                             ray.propagate(element);
-                            Lens::draw_rays(pixmap, &one, &ray);
+                            Lens::draw_rays(pixmap, &one, &ray, colour);
                             one = ray;
                         }
                     }
                     ray.o += ray.d * 100.;
-                    Lens::draw_rays(pixmap, &one, &ray);
+                    Lens::draw_rays(pixmap, &one, &ray, colour);
                 }
             }
             // let mut ray = Ray {
@@ -1076,6 +1384,8 @@ impl Lens {
         ray
     }
 
+    /// Trace a line of `num_rays` rays along the ghosts of `Lens::get_ghost_paths`,
+    /// returns z, y and strength before and after every element.
     pub fn get_rays(
         &self,
         num_rays: u32,
@@ -1085,9 +1395,9 @@ impl Lens {
         which_ghost: u32,
     ) -> Vec<f32> {
         let mut rays = vec![];
+        let paths = self.get_ghost_paths(draw_mode as usize, which_ghost as usize, 2);
 
         let width = 2.0;
-        let mut old_strength;
         for ray_num in 0..num_rays {
             let wave_num = 10;
             let wavelen = (ray_num % wave_num) as f64;
@@ -1095,101 +1405,17 @@ impl Lens {
             let end_wavelen = 0.78;
             let wavelength =
                 start_wavelen + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
-            if draw_mode & 1 > 0 {
-                let mut ghost_num = 0;
-                for i in 0..self.elements.len() - 1 {
-                    for j in i + 1..self.elements.len() {
-                        ghost_num += 1;
-                        if ghost_num == which_ghost || which_ghost == 0 {
-                            // make new ray
-                            let mut pos = center_pos;
-                            pos.y += ray_num as f64 / (num_rays as f64) * width - width / 2.;
-                            let mut ray = Ray::new(pos, direction, [0., 0., 0., pos.y], wavelength);
-                            rays.push(ray.o.z);
-                            rays.push(ray.o.y);
-                            rays.push(ray.strength);
-                            old_strength = ray.strength;
-
-                            for (ele, element) in self.elements.iter().enumerate() {
-                                // if we iterated through all elements up to
-                                // the first reflection point
-
-                                if ele == j {
-                                    // reflect at the first element,
-                                    // which is further down the optical path
-                                    ray.reflect(element);
-                                    rays.push(ray.o.z);
-                                    rays.push(ray.o.y);
-                                    rays.push(old_strength);
-                                    old_strength = ray.strength;
-
-                                    rays.push(ray.o.z);
-                                    rays.push(ray.o.y);
-                                    rays.push(ray.strength);
-                                    // propagate backwards through system
-                                    // until the second reflection
-                                    for k in (i + 1..j).rev() {
-                                        ray.propagate(&self.elements[k]);
-                                        rays.push(ray.o.z);
-                                        rays.push(ray.o.y);
-                                        rays.push(old_strength);
-                                        old_strength = ray.strength;
-
-                                        rays.push(ray.o.z);
-                                        rays.push(ray.o.y);
-                                        rays.push(ray.strength);
-                                    }
-                                    ray.reflect(&self.elements[i]);
-                                    rays.push(ray.o.z);
-                                    rays.push(ray.o.y);
-                                    rays.push(old_strength);
-                                    old_strength = ray.strength;
-
-                                    rays.push(ray.o.z);
-                                    rays.push(ray.o.y);
-                                    rays.push(ray.strength);
-                                    for k in i + 1..=j {
-                                        ray.propagate(&self.elements[k]);
-                                        rays.push(ray.o.z);
-                                        rays.push(ray.o.y);
-                                        rays.push(old_strength);
-                                        old_strength = ray.strength;
-
-                                        rays.push(ray.o.z);
-                                        rays.push(ray.o.y);
-                                        rays.push(ray.strength);
-                                    }
-                                    // println!("strength: {}", ray.strength);
-                                } else {
-                                    ray.propagate(element);
-                                    rays.push(ray.o.z);
-                                    rays.push(ray.o.y);
-                                    rays.push(old_strength);
-                                    old_strength = ray.strength;
-
-                                    rays.push(ray.o.z);
-                                    rays.push(ray.o.y);
-                                    rays.push(ray.strength);
-                                }
-                            }
-                            ray.o += ray.d * 100.;
-                            rays.push(ray.o.z);
-                            rays.push(ray.o.y);
-                            rays.push(ray.strength);
-                        }
-                    }
-                }
-            }
-            if draw_mode & 2 > 0 {
+            for path in &paths {
+                // make new ray
                 let mut pos = center_pos;
                 pos.y += ray_num as f64 / (num_rays as f64) * width - width / 2.;
                 let mut ray = Ray::new(pos, direction, [0., 0., 0., pos.y], wavelength);
                 rays.push(ray.o.z);
                 rays.push(ray.o.y);
                 rays.push(ray.strength);
-                old_strength = ray.strength;
-                for element in &self.elements {
-                    ray.propagate(element);
+                let mut old_strength = ray.strength;
+
+                self.walk_ghost(path, &mut ray, |ray| {
                     rays.push(ray.o.z);
                     rays.push(ray.o.y);
                     rays.push(old_strength);
@@ -1198,7 +1424,7 @@ impl Lens {
                     rays.push(ray.o.z);
                     rays.push(ray.o.y);
                     rays.push(ray.strength);
-                }
+                });
                 ray.o += ray.d * 100.;
                 rays.push(ray.o.z);
                 rays.push(ray.o.y);
@@ -1208,6 +1434,8 @@ impl Lens {
         rays.into_iter().map(|num| num as f32).collect()
     }
 
+    /// Trace a `num_rays`² grid of rays along the ghosts of `Lens::get_ghost_paths`,
+    /// returns the ray after every element for the rays that make it through.
     pub fn get_paths(
         &self,
         num_rays: u32,
@@ -1217,6 +1445,7 @@ impl Lens {
         which_ghost: u32,
     ) -> Vec<Vec<Ray>> {
         let mut rays = vec![];
+        let paths = self.get_ghost_paths(draw_mode as usize, which_ghost as usize, 2);
 
         let width = 2.0;
         for ray_num_x in 0..num_rays {
@@ -1228,71 +1457,14 @@ impl Lens {
                 let end_wavelen = 0.78;
                 let wavelength =
                     start_wavelen + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
-                if draw_mode & 1 > 0 {
-                    let mut ghost_num = 0;
-                    for i in 0..self.elements.len() - 1 {
-                        for j in i + 1..self.elements.len() {
-                            ghost_num += 1;
-                            if ghost_num == which_ghost || which_ghost == 0 {
-                                let mut ray_collection = vec![];
-                                // make new ray
-                                let mut pos = center_pos;
-                                pos.x += ray_num_x as f64 / (num_rays as f64) * width - width / 2.;
-                                pos.y += ray_num_y as f64 / (num_rays as f64) * width - width / 2.;
-                                let mut ray =
-                                    Ray::new(pos, direction, [0., 0., pos.x, pos.y], wavelength);
-                                ray_collection.push(ray);
-
-                                for (ele, element) in self.elements.iter().enumerate() {
-                                    // if we iterated through all elements up to
-                                    // the first reflection point
-
-                                    if ele == j {
-                                        // reflect at the first element,
-                                        // which is further down the optical path
-                                        ray.reflect(element);
-                                        ray_collection.push(ray);
-
-                                        // propagate backwards through system
-                                        // until the second reflection
-                                        for k in (i + 1..j).rev() {
-                                            ray.propagate(&self.elements[k]);
-                                            ray_collection.push(ray);
-                                        }
-                                        ray.reflect(&self.elements[i]);
-                                        ray_collection.push(ray);
-
-                                        for k in i + 1..=j {
-                                            ray.propagate(&self.elements[k]);
-                                            ray_collection.push(ray);
-                                        }
-                                        // println!("strength: {}", ray.strength);
-                                    } else {
-                                        ray.propagate(element);
-                                        ray_collection.push(ray);
-                                    }
-                                }
-                                ray.o += ray.d * 100.;
-                                ray_collection.push(ray);
-
-                                // only return rays that have made it through
-                                if ray.d.magnitude() > 0. {
-                                    rays.push(ray_collection);
-                                }
-                            }
-                        }
-                    }
-                }
-                if draw_mode & 2 > 0 {
+                for path in &paths {
+                    // make new ray
                     let mut pos = center_pos;
                     pos.x += ray_num_x as f64 / (num_rays as f64) * width - width / 2.;
                     pos.y += ray_num_y as f64 / (num_rays as f64) * width - width / 2.;
                     let mut ray = Ray::new(pos, direction, [0., 0., pos.x, pos.y], wavelength);
                     let mut ray_collection = vec![ray];
-                    for element in &self.elements {
-                        ray.propagate(element);
-                        ray_collection.push(ray);
-                    }
+                    self.walk_ghost(path, &mut ray, |ray| ray_collection.push(*ray));
                     ray.o += ray.d * 100.;
                     ray_collection.push(ray);
 
@@ -1306,37 +1478,59 @@ impl Lens {
         rays
     }
 
-    pub fn get_ghost_dot(&self, i: usize, j: usize, mut ray: Ray, sensor_pos: f64) -> Ray {
-        ray.d = ray.d.normalize();
-        // ray.init_pos = ray.intersect(self.elements[0].position);
-
-        for (ele, element) in self.elements.iter().enumerate() {
-            // if we iterated through all elements up to
-            // the first reflection point
-
-            if ele == j {
-                // reflect at the first element,
-                // which is further down the optical path
-                ray.reflect(element);
-
-                // propagate backwards through system
-                // until the second reflection
-                for k in (i + 1..j).rev() {
-                    ray.propagate(&self.elements[k]);
-                }
-                ray.reflect(&self.elements[i]);
+    /// trace `ray` along the path of the ghost reflected at element `j` and then at element `i`
+    /// and intersect it with the sensor plane at `sensor_pos`, fails unless `i < j`
+    pub fn get_ghost_dot(
+        &self,
+        i: usize,
+        j: usize,
+        ray: Ray,
+        sensor_pos: f64,
+    ) -> Result<(Ray, RayStatus), String> {
+        let path = GhostPath::two_bounce(i as u32, j as u32)?;
+        Ok(self.trace_ghost(&path, ray, sensor_pos))
+    }
 
-                for k in i + 1..=j {
-                    ray.propagate(&self.elements[k]);
+    /// move `ray` along `path` through all elements, `step` sees the ray after every element
+    fn walk_ghost(&self, path: &GhostPath, ray: &mut Ray, mut step: impl FnMut(&Ray)) {
+        // the element of the previous reflection
+        let mut last = None;
+        for (r, &surface) in path.reflections().iter().enumerate() {
+            let surface = surface as usize;
+            if r % 2 == 0 {
+                // travel forwards up to the reflection
+                for element in &self.elements[last.map_or(0, |last| last + 1)..surface] {
+                    ray.propagate(element);
+                    step(ray);
                 }
-                // println!("strength: {}", ray.strength);
             } else {
-                ray.propagate(element);
+                // travel backwards down to the reflection
+                for element in self.elements[surface + 1..last.unwrap()].iter().rev() {
+                    ray.propagate(element);
+                    step(ray);
+                }
             }
+            ray.reflect(&self.elements[surface]);
+            step(ray);
+            last = Some(surface);
+        }
+        for element in &self.elements[last.map_or(0, |last| last + 1)..] {
+            ray.propagate(element);
+            step(ray);
+        }
+    }
+
+    /// trace `ray` along `path` and intersect it with the sensor plane at `sensor_pos`
+    pub fn trace_ghost(&self, path: &GhostPath, mut ray: Ray, sensor_pos: f64) -> (Ray, RayStatus) {
+        ray.d = ray.d.normalize();
+        self.walk_ghost(path, &mut ray, |_| ());
+
+        if ray.status == RayStatus::Ok && !((sensor_pos - ray.o.z) / ray.d.z >= 0.) {
+            ray.terminate(RayStatus::BehindSensor);
         }
         ray.o = ray.intersect_vec(sensor_pos);
 
-        ray
+        (ray, ray.status)
     }
 
     pub fn get_at_pos(
@@ -1347,18 +1541,14 @@ impl Lens {
         sensor_pos: f64,
     ) -> Ray {
         let mut ray = Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], 0.5);
-        let mut ghost_num = 0;
-        for i in 0..self.elements.len() - 1 {
-            for j in i + 1..self.elements.len() {
-                ghost_num += 1;
-                if ghost_num == which_ghost {
-                    ray = self.get_ghost_dot(i, j, ray, sensor_pos);
-                }
-            }
+        if let Some(path) = self.get_ghost_path(which_ghost) {
+            ray = self.trace_ghost(&path, ray, sensor_pos).0;
         }
         ray
     }
 
+    /// Trace a `side_len`² grid of ray directions around `pos` along ghost `which_ghost`,
+    /// also returns why the rays stopped. With `filter` only the rays that hit the sensor are kept.
     pub fn get_dots_2dgrid(
         &self,
         side_len: u32,
@@ -1367,7 +1557,7 @@ impl Lens {
         sensor_pos: f64,
         width: [f64; 2],
         filter: bool,
-    ) -> Vec<DrawRay> {
+    ) -> (Vec<DrawRay>, RayStatusCounts) {
         let center_dir = self.get_center_dir(pos);
 
         let mut rays = vec![];
@@ -1375,57 +1565,48 @@ impl Lens {
         let width_d = width[0];
         let width_p = width[1];
         // if draw_mode & 1 > 0 {
-        let mut ghost_num = 0;
-        for i in 0..self.elements.len() - 1 {
-            for j in i + 1..self.elements.len() {
-                ghost_num += 1;
-                if ghost_num == which_ghost {
-                    rays.append(
-                        &mut iproduct!(0..side_len, 0..side_len)
-                            .into_iter()
-                            .par_bridge()
-                            .filter_map(|(z, w)| {
-                                // let wave_num = 1;
-                                // let ray_num = ray_num_x * num_rays + ray_num_y;
-                                // let wavelen = (ray_num % wave_num) as f64;
-                                // let start_wavelen = 0.38;
-                                // let end_wavelen = 0.78;
-                                // let wavelength = start_wavelen
-                                //     + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
-                                let (x, y, z, w) = (
-                                    0.5,
-                                    0.5,
-                                    z as f64 / side_len as f64,
-                                    w as f64 / side_len as f64,
-                                );
-                                let wavelength = 0.5;
-
-                                // make new ray
-                                let mut pos = pos;
-                                pos.x += x * width_p - width_p / 2.;
-                                pos.y += y * width_p - width_p / 2.;
-
-                                let mut dir = center_dir;
-                                dir.x += z * width_d - width_d / 2.;
-                                dir.y += w * width_d - width_d / 2.;
-
-                                let mut ray =
-                                    Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], wavelength);
-                                ray.ghost_num = ghost_num;
-                                let ray = self.get_ghost_dot(i, j, ray, sensor_pos);
-                                if !filter || ray.o.is_finite() {
-                                    Some(ray)
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<_>>(),
-                    );
-                }
-            }
+        if let Some(path) = self.get_ghost_path(which_ghost as usize) {
+            rays.append(
+                &mut iproduct!(0..side_len, 0..side_len)
+                    .into_iter()
+                    .par_bridge()
+                    .map(|(z, w)| {
+                        // let wave_num = 1;
+                        // let ray_num = ray_num_x * num_rays + ray_num_y;
+                        // let wavelen = (ray_num % wave_num) as f64;
+                        // let start_wavelen = 0.38;
+                        // let end_wavelen = 0.78;
+                        // let wavelength = start_wavelen
+                        //     + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
+                        let (x, y, z, w) = (
+                            0.5,
+                            0.5,
+                            z as f64 / side_len as f64,
+                            w as f64 / side_len as f64,
+                        );
+                        let wavelength = 0.5;
+
+                        // make new ray
+                        let mut pos = pos;
+                        pos.x += x * width_p - width_p / 2.;
+                        pos.y += y * width_p - width_p / 2.;
+
+                        let mut dir = center_dir;
+                        dir.x += z * width_d - width_d / 2.;
+                        dir.y += w * width_d - width_d / 2.;
+
+                        let mut ray = Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], wavelength);
+                        ray.ghost_num = which_ghost;
+                        self.trace_ghost(&path, ray, sensor_pos)
+                    })
+                    .collect::<Vec<_>>(),
+            );
         }
-        rays.iter()
-            .map(|ray| DrawRay {
+        let counts = rays.iter().map(|(_, status)| *status).collect();
+        let dots = rays
+            .iter()
+            .filter(|(_, status)| !filter || *status == RayStatus::Ok)
+            .map(|(ray, _)| DrawRay {
                 pos: ray.o.xy().into(),
                 wavelength: ray.wavelength,
                 strength: ray.strength,
@@ -1434,9 +1615,11 @@ impl Lens {
                 aperture_pos: ray.aperture_pos,
                 entry_pos: ray.entry_pos,
             })
-            .collect()
+            .collect();
+        (dots, counts)
     }
 
+    /// like `Lens::get_dots_2dgrid`, with a `side_len`⁴ grid of ray positions and directions
     pub fn get_dots_grid(
         &self,
         side_len: u32,
@@ -1445,7 +1628,7 @@ impl Lens {
         sensor_pos: f64,
         width: [f64; 2],
         filter: bool,
-    ) -> Vec<DrawRay> {
+    ) -> (Vec<DrawRay>, RayStatusCounts) {
         let center_dir = self.get_center_dir(pos);
 
         let mut rays = vec![];
@@ -1453,57 +1636,48 @@ impl Lens {
         let width_d = width[0];
         let width_p = width[1];
         // if draw_mode & 1 > 0 {
-        let mut ghost_num = 0;
-        for i in 0..self.elements.len() - 1 {
-            for j in i + 1..self.elements.len() {
-                ghost_num += 1;
-                if ghost_num == which_ghost {
-                    rays.append(
-                        &mut iproduct!(0..side_len, 0..side_len, 0..side_len, 0..side_len)
-                            .into_iter()
-                            .par_bridge()
-                            .filter_map(|(x, y, z, w)| {
-                                // let wave_num = 1;
-                                // let ray_num = ray_num_x * num_rays + ray_num_y;
-                                // let wavelen = (ray_num % wave_num) as f64;
-                                // let start_wavelen = 0.38;
-                                // let end_wavelen = 0.78;
-                                // let wavelength = start_wavelen
-                                //     + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
-                                let (x, y, z, w) = (
-                                    x as f64 / side_len as f64,
-                                    y as f64 / side_len as f64,
-                                    z as f64 / side_len as f64,
-                                    w as f64 / side_len as f64,
-                                );
-                                let wavelength = 0.5;
-
-                                // make new ray
-                                let mut pos = pos;
-                                pos.x += x * width_p - width_p / 2.;
-                                pos.y += y * width_p - width_p / 2.;
-
-                                let mut dir = center_dir;
-                                dir.x += z * width_d - width_d / 2.;
-                                dir.y += w * width_d - width_d / 2.;
-
-                                let mut ray =
-                                    Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], wavelength);
-                                ray.ghost_num = ghost_num;
-                                let ray = self.get_ghost_dot(i, j, ray, sensor_pos);
-                                if !filter || ray.o.is_finite() {
-                                    Some(ray)
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<_>>(),
-                    );
-                }
-            }
+        if let Some(path) = self.get_ghost_path(which_ghost as usize) {
+            rays.append(
+                &mut iproduct!(0..side_len, 0..side_len, 0..side_len, 0..side_len)
+                    .into_iter()
+                    .par_bridge()
+                    .map(|(x, y, z, w)| {
+                        // let wave_num = 1;
+                        // let ray_num = ray_num_x * num_rays + ray_num_y;
+                        // let wavelen = (ray_num % wave_num) as f64;
+                        // let start_wavelen = 0.38;
+                        // let end_wavelen = 0.78;
+                        // let wavelength = start_wavelen
+                        //     + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
+                        let (x, y, z, w) = (
+                            x as f64 / side_len as f64,
+                            y as f64 / side_len as f64,
+                            z as f64 / side_len as f64,
+                            w as f64 / side_len as f64,
+                        );
+                        let wavelength = 0.5;
+
+                        // make new ray
+                        let mut pos = pos;
+                        pos.x += x * width_p - width_p / 2.;
+                        pos.y += y * width_p - width_p / 2.;
+
+                        let mut dir = center_dir;
+                        dir.x += z * width_d - width_d / 2.;
+                        dir.y += w * width_d - width_d / 2.;
+
+                        let mut ray = Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], wavelength);
+                        ray.ghost_num = which_ghost;
+                        self.trace_ghost(&path, ray, sensor_pos)
+                    })
+                    .collect::<Vec<_>>(),
+            );
         }
-        rays.iter()
-            .map(|ray| DrawRay {
+        let counts = rays.iter().map(|(_, status)| *status).collect();
+        let dots = rays
+            .iter()
+            .filter(|(_, status)| !filter || *status == RayStatus::Ok)
+            .map(|(ray, _)| DrawRay {
                 pos: ray.o.xy().into(),
                 wavelength: ray.wavelength,
                 strength: ray.strength,
@@ -1512,9 +1686,15 @@ impl Lens {
                 aperture_pos: ray.aperture_pos,
                 entry_pos: ray.entry_pos,
             })
-            .collect()
+            .collect();
+        (dots, counts)
     }
 
+    /// Trace random rays around `pos` along ghost `which_ghost` until `num_rays` of them
+    /// are collected, also returns why the rays stopped. With `filter` only the rays that hit
+    /// the sensor are kept, after `num_rays * MAX_TRIES_PER_DOT` traced rays it gives up.
+    /// The wavelengths in µm are uniformly distributed over the range `wavelengths`.
+    #[allow(clippy::too_many_arguments)]
     pub fn get_dots(
         &self,
         num_rays: u32,
@@ -1522,8 +1702,9 @@ impl Lens {
         which_ghost: u32,
         sensor_pos: f64,
         width: [f64; 2],
+        wavelengths: [f64; 2],
         filter: bool,
-    ) -> Vec<DrawRay> {
+    ) -> (Vec<DrawRay>, RayStatusCounts) {
         // Pick an arbitrary number as seed.
         fastrand::seed(7);
         // let rays = self.get_paths(
@@ -1537,42 +1718,42 @@ impl Lens {
         let center_dir = self.get_center_dir(pos);
 
         let mut rays = vec![];
+        let mut counts = RayStatusCounts::default();
 
         let width_d = width[0];
         let width_p = width[1];
         // if draw_mode & 1 > 0 {
-        let mut ghost_num = 0;
-        for i in 0..self.elements.len() - 1 {
-            for j in i + 1..self.elements.len() {
-                ghost_num += 1;
-                if ghost_num == which_ghost {
-                    while rays.len() < num_rays as usize {
-                        // let wave_num = 1;
-                        // let ray_num = ray_num_x * num_rays + ray_num_y;
-                        // let wavelen = (ray_num % wave_num) as f64;
-                        // let start_wavelen = 0.38;
-                        // let end_wavelen = 0.78;
-                        // let wavelength = start_wavelen
-                        //     + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
-                        let wavelength = 0.5;
-
-                        // make new ray
-                        let mut dir = center_dir;
-                        // dir.x += ray_num_x as f64 / (num_rays as f64) * width - width / 2.;
-                        // dir.y += ray_num_y as f64 / (num_rays as f64) * width - width / 2.;
-                        dir.x += fastrand::f64() * width_d - width_d / 2.;
-                        dir.y += fastrand::f64() * width_d - width_d / 2.;
-
-                        let mut pos = pos;
-                        pos.x += fastrand::f64() * width_p - width_p / 2.;
-                        pos.y += fastrand::f64() * width_p - width_p / 2.;
-                        let mut ray = Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], wavelength);
-                        ray.ghost_num = ghost_num;
-                        let ray = self.get_ghost_dot(i, j, ray, sensor_pos);
-                        if !filter || ray.o.is_finite() {
-                            rays.push(ray);
-                        }
-                    }
+        if let Some(path) = self.get_ghost_path(which_ghost as usize) {
+            // give up on ghosts that (almost) no ray makes it through
+            while rays.len() < num_rays as usize
+                && counts.total() < num_rays as usize * MAX_TRIES_PER_DOT
+            {
+                // let wave_num = 1;
+                // let ray_num = ray_num_x * num_rays + ray_num_y;
+                // let wavelen = (ray_num % wave_num) as f64;
+                // let start_wavelen = 0.38;
+                // let end_wavelen = 0.78;
+                // let wavelength = start_wavelen
+                //     + wavelen * ((end_wavelen - start_wavelen) / (wave_num as f64));
+                let wavelength =
+                    wavelengths[0] + fastrand::f64() * (wavelengths[1] - wavelengths[0]);
+
+                // make new ray
+                let mut dir = center_dir;
+                // dir.x += ray_num_x as f64 / (num_rays as f64) * width - width / 2.;
+                // dir.y += ray_num_y as f64 / (num_rays as f64) * width - width / 2.;
+                dir.x += fastrand::f64() * width_d - width_d / 2.;
+                dir.y += fastrand::f64() * width_d - width_d / 2.;
+
+                let mut pos = pos;
+                pos.x += fastrand::f64() * width_p - width_p / 2.;
+                pos.y += fastrand::f64() * width_p - width_p / 2.;
+                let mut ray = Ray::new(pos, dir, [pos.x, pos.y, dir.x, dir.y], wavelength);
+                ray.ghost_num = which_ghost;
+                let (ray, status) = self.trace_ghost(&path, ray, sensor_pos);
+                counts.add(status);
+                if !filter || status == RayStatus::Ok {
+                    rays.push(ray);
                 }
             }
         }
@@ -1622,7 +1803,8 @@ impl Lens {
         //     rays.len(),
         //     rays.len() as f64 / (&num_rays * 100) as f64 * 100.
         // );
-        rays.iter()
+        let dots = rays
+            .iter()
             .map(|ray| DrawRay {
                 pos: ray.o.xy().into(),
                 wavelength: ray.wavelength,
@@ -1632,7 +1814,8 @@ impl Lens {
                 aperture_pos: ray.aperture_pos,
                 entry_pos: ray.entry_pos,
             })
-            .collect()
+            .collect();
+        (dots, counts)
     }
 
     pub fn get_center_dir(&self, pos: Vector3<f64>) -> Vector3<f64> {
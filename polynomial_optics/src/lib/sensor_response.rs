@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// number of samples of the LUT the draw shaders get
+pub const SENSOR_SAMPLES: usize = 41;
+/// wavelength of the first sample of the LUT in µm
+pub const SENSOR_START: f64 = 0.38;
+/// distance between the samples of the LUT in µm
+pub const SENSOR_STEP: f64 = 0.01;
+/// measurements have to reach from at most this wavelength in µm...
+const COVERAGE_START: f64 = 0.4;
+/// ...to at least this one
+const COVERAGE_END: f64 = 0.7;
+
+/// ## How much light of each wavelength ends up in the r, g and b channels of a camera
+/// The response is constant outside of the measurements.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SensorResponse {
+    /// `(wavelength in µm, [r, g, b])`, sorted by wavelength
+    pub measurements: Vec<(f64, [f64; 3])>,
+}
+
+impl SensorResponse {
+    /// Cameras measured by Christian Mauer, from
+    /// https://www.image-engineering.de/content/library/diploma_thesis/christian_mauer_spectral_response.pdf
+    pub fn get_all_sensors() -> Vec<(String, SensorResponse)> {
+        [
+            ("Leica M8", include_str!("../../sensors/Leica M8.csv")),
+            ("Nikon D700", include_str!("../../sensors/Nikon D700.csv")),
+            (
+                "Canon EOS 40D",
+                include_str!("../../sensors/Canon EOS 40D.csv"),
+            ),
+            ("Fuji S5 Pro", include_str!("../../sensors/Fuji S5 Pro.csv")),
+            (
+                "Panasonic DMC-LX3",
+                include_str!("../../sensors/Panasonic DMC-LX3.csv"),
+            ),
+            (
+                "Arriflex D-21",
+                include_str!("../../sensors/Arriflex D-21.csv"),
+            ),
+            (
+                "Canon EOS 450D no IR filter",
+                include_str!("../../sensors/Canon EOS 450D no IR filter.csv"),
+            ),
+            (
+                "Hasselblad H3D",
+                include_str!("../../sensors/Hasselblad H3D.csv"),
+            ),
+            ("Nikon D200", include_str!("../../sensors/Nikon D200.csv")),
+        ]
+        .iter()
+        .map(|(name, csv)| (name.to_string(), Self::parse_csv(csv).unwrap()))
+        .collect()
+    }
+
+    /// Reads a sensor response, either a `.csv` file, see `SensorResponse::parse_csv`,
+    /// or a `.ron` file holding a `SensorResponse`.
+    pub fn read(path: &Path) -> Result<SensorResponse, String> {
+        let str = match std::fs::read_to_string(path) {
+            Ok(str) => str,
+            Err(err) => return Err(format!("problem reading file: {}", err)),
+        };
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Self::parse_csv(&str),
+            Some("ron") => match ron::de::from_str::<SensorResponse>(&str) {
+                Ok(response) => Self::from_measurements(response.measurements),
+                Err(err) => Err(format!("{}", err)),
+            },
+            _ => Err(format!("unknown sensor format {:?}", path)),
+        }
+    }
+
+    /// Parses lines of `wavelength in nm, r, g, b`, a header line is skipped.
+    /// Responses above 1 are scaled down to a peak of 1.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let sensor = SensorResponse::parse_csv(
+    ///     "wavelength,r,g,b
+    ///      400, 0, 10, 80
+    ///      550, 10, 100, 10
+    ///      700, 50, 0, 0",
+    /// )
+    /// .unwrap();
+    /// let rgb = sensor.rgb(0.55);
+    /// assert!((rgb[0] - 0.1).abs() < 1e-9 && (rgb[1] - 1.).abs() < 1e-9);
+    ///
+    /// let err = SensorResponse::parse_csv("450, 0, 1, 0\n650, 1, 0, 0").unwrap_err();
+    /// assert!(err.contains("cover"));
+    /// ```
+    pub fn parse_csv(str: &str) -> Result<SensorResponse, String> {
+        let mut measurements = vec![];
+        for (line_num, line) in str.lines().enumerate() {
+            let line_num = line_num + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields = line
+                .split(',')
+                .map(|field| field.trim().parse::<f64>())
+                .collect::<Vec<_>>();
+            match fields.as_slice() {
+                [Ok(wavelength), Ok(r), Ok(g), Ok(b)] => {
+                    measurements.push((wavelength / 1000., [*r, *g, *b]))
+                }
+                // the header
+                _ if measurements.is_empty() && line_num == 1 => continue,
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `wavelength, r, g, b`, got {:?}",
+                        line_num, line
+                    ))
+                }
+            }
+        }
+
+        let peak = measurements
+            .iter()
+            .flat_map(|(_, rgb)| rgb.iter())
+            .fold(0., |peak: f64, value| peak.max(*value));
+        if peak > 1. {
+            for (_, rgb) in &mut measurements {
+                for value in rgb {
+                    *value /= peak;
+                }
+            }
+        }
+        Self::from_measurements(measurements)
+    }
+
+    /// a sensor response from `(wavelength in µm, [r, g, b])`,
+    /// they have to cover 400nm to 700nm
+    pub fn from_measurements(measurements: Vec<(f64, [f64; 3])>) -> Result<Self, String> {
+        if measurements.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(String::from("measurements are not sorted by wavelength"));
+        }
+        if let Some((wavelength, rgb)) = measurements
+            .iter()
+            .find(|(_, rgb)| rgb.iter().any(|value| !value.is_finite() || *value < 0.))
+        {
+            return Err(format!(
+                "response {:?} at {}µm is negative",
+                rgb, wavelength
+            ));
+        }
+        match (measurements.first(), measurements.last()) {
+            (Some((first, _)), Some((last, _)))
+                if *first <= COVERAGE_START && *last >= COVERAGE_END =>
+            {
+                Ok(Self { measurements })
+            }
+            (Some((first, _)), Some((last, _))) => Err(format!(
+                "measurements cover {}nm to {}nm, they need to cover {}nm to {}nm",
+                first * 1000.,
+                last * 1000.,
+                COVERAGE_START * 1000.,
+                COVERAGE_END * 1000.
+            )),
+            _ => Err(String::from("no measurements")),
+        }
+    }
+
+    /// the response at `wavelength` in µm, interpolated linearly between the measurements
+    pub fn rgb(&self, wavelength: f64) -> [f64; 3] {
+        let next = self.measurements.partition_point(|(w, _)| *w < wavelength);
+        if next == 0 {
+            self.measurements[0].1
+        } else if next == self.measurements.len() {
+            self.measurements[next - 1].1
+        } else {
+            let (w0, rgb0) = self.measurements[next - 1];
+            let (w1, rgb1) = self.measurements[next];
+            let t = (wavelength - w0) / (w1 - w0);
+            [0, 1, 2].map(|i| rgb0[i] + (rgb1[i] - rgb0[i]) * t)
+        }
+    }
+
+    /// The response every 10nm from 380nm to 780nm in `r, g, b, wavelength in nm` format,
+    /// the LUT the draw shaders sample in `lookup_rgb`.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let (_name, sensor) = &SensorResponse::get_all_sensors()[0];
+    /// let lut = sensor.get_lut();
+    /// assert_eq!(lut.len(), 4 * SENSOR_SAMPLES);
+    /// assert_eq!(lut[3], 380.);
+    /// assert_eq!(lut[4 * 17 + 3], 550.);
+    /// ```
+    pub fn get_lut(&self) -> Vec<f32> {
+        let mut lut = Vec::with_capacity(4 * SENSOR_SAMPLES);
+        for i in 0..SENSOR_SAMPLES {
+            let wavelength = SENSOR_START + i as f64 * SENSOR_STEP;
+            let rgb = self.rgb(wavelength);
+            lut.extend(rgb.iter().map(|value| *value as f32));
+            lut.push((wavelength * 1000.).round() as f32);
+        }
+        lut
+    }
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Asphere, CoatingStack, Element, Glass, Lens, Properties, Sellmeier, Spectrum, FLAT_RADIUS,
+};
+
+/// ## Cover glass and sensor surface at the end of a lens
+/// Light reflected at them travels back into the lens and forms ghosts like any other surface.
+/// Distances are in the units of the lens.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SensorStack {
+    /// material of the cover glass / IR filter in front of the sensor
+    pub cover_glass: Sellmeier,
+    /// coating on both sides of the cover glass
+    pub cover_coating: CoatingStack,
+    /// thickness of the cover glass, 0 for a bare sensor
+    pub cover_thickness: f64,
+    /// distance from the back of the cover glass to the sensor
+    pub cover_gap: f64,
+    /// reflectance of the sensor surface
+    pub reflectance: Spectrum,
+}
+
+impl Default for SensorStack {
+    fn default() -> Self {
+        Self {
+            cover_glass: Sellmeier::bk7(),
+            cover_coating: CoatingStack::none(),
+            cover_thickness: 0.5,
+            cover_gap: 0.5,
+            // a rough guess for a sensor behind microlenses
+            reflectance: Spectrum::constant(0.1),
+        }
+    }
+}
+
+impl SensorStack {
+    /// the surfaces of the stack for a sensor at `sensor_dist`, the sensor itself is the last one
+    pub fn get_elements(&self, sensor_dist: f64) -> Vec<Element> {
+        let mut elements = vec![];
+        if self.cover_thickness > 0. {
+            let back = sensor_dist - self.cover_gap;
+            for (entry, position) in [(true, back - self.cover_thickness), (false, back)] {
+                elements.push(Element {
+                    radius: if entry { FLAT_RADIUS } else { -FLAT_RADIUS },
+                    properties: Properties::Glass(Glass {
+                        sellmeier: self.cover_glass,
+                        coating: self.cover_coating,
+                        entry,
+                        outer_ior: Sellmeier::air(),
+                        spherical: true,
+                        asphere: Asphere::sphere(),
+                        semi_diameter: None,
+                    }),
+                    position,
+                });
+            }
+        }
+        elements.push(Element {
+            radius: 0.,
+            properties: Properties::Sensor(self.reflectance),
+            position: sensor_dist,
+        });
+        elements
+    }
+
+    /// number of elements the stack adds to a lens
+    pub fn num_elements(&self) -> usize {
+        if self.cover_thickness > 0. {
+            3
+        } else {
+            1
+        }
+    }
+}
+
+impl Lens {
+    /// Add `sensor_stack` to the end of the lens, replacing the current one.
+    /// `None` removes it again.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let mut lens = Lens::read_prescription(
+    ///     std::path::Path::new("../lenses/AngenieuxDGauss.fx"),
+    ///     &Sellmeier::get_all_glasses(),
+    ///     1.,
+    /// )
+    /// .unwrap();
+    /// let num_elements = lens.elements.len();
+    /// let num_ghosts = lens.get_ghosts_indicies(1, 0).len();
+    ///
+    /// lens.set_sensor_stack(Some(SensorStack::default()));
+    /// assert_eq!(lens.elements.len(), num_elements + 3);
+    /// assert_eq!(lens.lens_elements().len(), num_elements);
+    /// // the cover glass and the sensor reflect light back into the lens
+    /// assert!(lens.get_ghosts_indicies(1, 0).len() > num_ghosts);
+    ///
+    /// lens.set_sensor_stack(None);
+    /// assert_eq!(lens.elements.len(), num_elements);
+    /// ```
+    pub fn set_sensor_stack(&mut self, sensor_stack: Option<SensorStack>) {
+        let num_lens_elements = self.lens_elements().len();
+        self.elements.truncate(num_lens_elements);
+        self.sensor_stack = sensor_stack;
+        if let Some(sensor_stack) = sensor_stack {
+            self.elements
+                .extend(sensor_stack.get_elements(self.sensor_dist));
+        }
+    }
+
+    /// move the sensor stack to the current `sensor_dist`
+    pub fn place_sensor_stack(&mut self) {
+        self.set_sensor_stack(self.sensor_stack);
+    }
+
+    /// the elements without the sensor stack
+    pub fn lens_elements(&self) -> &[Element] {
+        let num_stack = self.sensor_stack.map_or(0, |stack| stack.num_elements());
+        &self.elements[..self.elements.len() - num_stack]
+    }
+}
@@ -1,12 +1,6 @@
 use itertools::iproduct;
 use itertools::Itertools;
-use mathru::algebra::{
-    abstr::{AbsDiffEq, Field, Scalar},
-    linear::{
-        matrix::{Solve, Transpose},
-        Matrix, Vector,
-    },
-};
+use mathru::algebra::abstr::{AbsDiffEq, Field, Scalar};
 use num::{traits::Zero, One};
 use rand::Rng;
 use rayon::prelude::*;
@@ -19,8 +13,6 @@ use std::{
     ops::{Add, AddAssign, Div, Mul, MulAssign, Neg},
 };
 
-use crate::iexp;
-
 pub trait PowUsize {
     fn upow(self, exp: usize) -> Self;
 }
@@ -79,7 +71,14 @@ pub struct Monomial<N, const VARIABLES: usize> {
     pub exponents: [usize; VARIABLES],
 }
 
-const NAMED_VARS: &str = "xyzw";
+const NAMED_VARS: &str = "xyzwλ";
+
+/// regularisations on the path of `Polynomial::get_sparse_lasso`
+const LASSO_PATH_STEPS: usize = 100;
+/// smallest regularisation on the path relative to the one that leaves all terms at 0
+const LASSO_PATH_RATIO: f64 = 1e-4;
+/// coordinate descent sweeps per regularisation at most
+const LASSO_MAX_SWEEPS: usize = 1000;
 
 impl<N: PartialOrd, const VARIABLES: usize> PartialOrd for Monomial<N, VARIABLES> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
@@ -271,6 +270,305 @@ impl<const VARIABLES: usize> Polynomial<f64, VARIABLES> {
         let missing_len = (VARIABLES + 1) * len - v.len();
         [v, vec![0.0; missing_len]].concat()
     }
+
+    /// every monomial up to `degree` with a coefficient of 0, in the order of `crate::exponents`
+    pub fn dense(degree: usize) -> Self {
+        Polynomial {
+            terms: crate::exponents::<VARIABLES>(degree)
+                .into_iter()
+                .map(|exponents| Monomial {
+                    coefficient: 0.,
+                    exponents,
+                })
+                .collect(),
+        }
+    }
+
+    /// the exponents of every term evaluated at every point, one row per point
+    fn design_matrix<T>(&self, points: &[([f64; VARIABLES], T)]) -> Vec<f64> {
+        points
+            .iter()
+            .flat_map(|(input, _)| self.terms.iter().map(move |term| term.eval_exp(*input)))
+            .collect()
+    }
+
+    /// Fit the coefficients to `points` of an input and the value there with the QR solver.
+    /// Returns the estimated condition number of the system.
+    pub fn fit_points(
+        &mut self,
+        points: &[([f64; VARIABLES], f64)],
+        solver: &crate::LeastSquares,
+    ) -> Result<f64, String> {
+        let design = self.design_matrix(points);
+        let values = points.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        let solution = solver.solve(&design, self.terms.len(), &values)?;
+        for (term, c) in self.terms.iter_mut().zip(solution.coefficients) {
+            term.coefficient = c;
+        }
+        Ok(solution.condition)
+    }
+
+    /// root mean square error at `points`
+    pub fn error_points(&self, points: &[([f64; VARIABLES], f64)]) -> f64 {
+        (points
+            .par_iter()
+            .map(|(input, value)| (value - self.eval(*input)).powi(2))
+            .sum::<f64>()
+            / points.len() as f64)
+            .sqrt()
+    }
+
+    /// # Orthogonal Matching Pursuit
+    /// Picks up to `num_max_terms` of the terms of `self` one at a time, always the one that
+    /// correlates the most with the residual, and refits the picked terms after every step.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let f = |p: [f64; 2]| 2. * p[0] * p[1] - p[1].powi(3);
+    /// let points = (0..100)
+    ///     .map(|i| [(i % 10) as f64 / 10. - 0.5, (i / 10) as f64 / 10. - 0.5])
+    ///     .map(|p| (p, f(p)))
+    ///     .collect::<Vec<_>>();
+    /// let sparse = Polynomial::<f64, 2>::dense(4)
+    ///     .get_sparse_omp(&points, 2, &LeastSquares::default())
+    ///     .unwrap();
+    /// assert_eq!(sparse.terms.len(), 2);
+    /// assert!(sparse.error_points(&points) < 1e-12);
+    /// ```
+    pub fn get_sparse_omp(
+        &self,
+        points: &[([f64; VARIABLES], f64)],
+        num_max_terms: usize,
+        solver: &crate::LeastSquares,
+    ) -> Result<Self, String> {
+        let points = points
+            .iter()
+            .map(|(input, value)| (*input, [*value]))
+            .collect::<Vec<_>>();
+        let (mut sparse, _) = self.get_sparse_somp(&points, num_max_terms, solver)?;
+        Ok(sparse.remove(0))
+    }
+
+    /// # Simultaneous Orthogonal Matching Pursuit
+    /// `get_sparse_omp` for `OUTPUTS` values per point at once: every output gets the same terms,
+    /// picked by how much they correlate with all the residuals together.
+    /// Returns a polynomial per output, all with the same terms in the same order,
+    /// and the root mean square error over all outputs after every picked term.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let f = |p: [f64; 2]| [2. * p[0] * p[1] - p[1].powi(3), p[0] * p[1] + 0.5 * p[1].powi(3)];
+    /// let points = (0..100)
+    ///     .map(|i| [(i % 10) as f64 / 10. - 0.5, (i / 10) as f64 / 10. - 0.5])
+    ///     .map(|p| (p, f(p)))
+    ///     .collect::<Vec<_>>();
+    /// let (sparse, errors) = Polynomial::<f64, 2>::dense(4)
+    ///     .get_sparse_somp(&points, 3, &LeastSquares::default())
+    ///     .unwrap();
+    /// assert_eq!(sparse[0].terms.len(), 2);
+    /// let exponents = |p: &Polynomial<f64, 2>| p.terms.iter().map(|t| t.exponents).collect::<Vec<_>>();
+    /// assert_eq!(exponents(&sparse[0]), exponents(&sparse[1]));
+    /// // the third term doesn't improve anything anymore
+    /// assert_eq!(errors.len(), 2);
+    /// assert!(errors[1] < 1e-12);
+    /// ```
+    pub fn get_sparse_somp<const OUTPUTS: usize>(
+        &self,
+        points: &[([f64; VARIABLES], [f64; OUTPUTS])],
+        num_max_terms: usize,
+        solver: &crate::LeastSquares,
+    ) -> Result<(Vec<Self>, Vec<f64>), String> {
+        let (num_points, num_terms) = (points.len(), self.terms.len());
+        let design = self.design_matrix(points);
+        let column = |term: usize| (0..num_points).map(move |point| point * num_terms + term);
+        let norms = (0..num_terms)
+            .map(|term| column(term).map(|i| design[i].powi(2)).sum::<f64>().sqrt())
+            .collect::<Vec<_>>();
+        let outputs = (0..OUTPUTS)
+            .map(|output| {
+                points
+                    .iter()
+                    .map(|(input, values)| (*input, values[output]))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut sparse = vec![Polynomial { terms: vec![] }; OUTPUTS];
+        let mut errors = vec![];
+        let mut picked = vec![false; num_terms];
+        let mut residuals = outputs
+            .iter()
+            .map(|points| points.iter().map(|(_, value)| *value).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        // stop early once the residuals are numerically zero
+        let tolerance = f64::EPSILON * residuals.iter().flatten().map(|r| r * r).sum::<f64>();
+        while errors.len() < num_max_terms.min(num_terms) {
+            let best = (0..num_terms)
+                .filter(|&term| !picked[term] && norms[term] > 0.)
+                .map(|term| {
+                    let correlation = residuals
+                        .iter()
+                        .map(|residual| {
+                            column(term)
+                                .zip(residual)
+                                .map(|(i, r)| design[i] * r)
+                                .sum::<f64>()
+                                .powi(2)
+                        })
+                        .sum::<f64>();
+                    (term, correlation / norms[term].powi(2))
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+            let best = match best {
+                Some((best, correlation)) if correlation > tolerance => best,
+                _ => break,
+            };
+            picked[best] = true;
+
+            let mut error = 0.;
+            for ((polynomial, points), residual) in
+                sparse.iter_mut().zip(&outputs).zip(&mut residuals)
+            {
+                polynomial.terms.push(self.terms[best]);
+                polynomial.fit_points(points, solver)?;
+                for (r, (input, value)) in residual.iter_mut().zip(points) {
+                    *r = value - polynomial.eval(*input);
+                    error += *r * *r;
+                }
+            }
+            errors.push((error / (num_points * OUTPUTS) as f64).sqrt());
+        }
+        Ok((sparse, errors))
+    }
+
+    /// # LASSO with coordinate descent
+    /// Minimizes |y - Xc|² / 2n + λ|c|₁ over the terms of `self` with coordinate descent,
+    /// going down a path of regularisations λ and starting each from the previous solution.
+    /// The largest set of at most `num_max_terms` terms found on the path is refitted without
+    /// the regularisation.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let f = |p: [f64; 2]| 2. * p[0] * p[1] - p[1].powi(3);
+    /// let points = (0..100)
+    ///     .map(|i| [(i % 10) as f64 / 10. - 0.5, (i / 10) as f64 / 10. - 0.5])
+    ///     .map(|p| (p, f(p)))
+    ///     .collect::<Vec<_>>();
+    /// let sparse = Polynomial::<f64, 2>::dense(4)
+    ///     .get_sparse_lasso(&points, 2, &LeastSquares::default())
+    ///     .unwrap();
+    /// assert_eq!(sparse.terms.len(), 2);
+    /// assert!(sparse.error_points(&points) < 1e-12);
+    /// ```
+    pub fn get_sparse_lasso(
+        &self,
+        points: &[([f64; VARIABLES], f64)],
+        num_max_terms: usize,
+        solver: &crate::LeastSquares,
+    ) -> Result<Self, String> {
+        let (num_points, num_terms) = (points.len() as f64, self.terms.len());
+        let design = self.design_matrix(points);
+        // columns scaled to a mean square of 1
+        let columns = (0..num_terms)
+            .map(|term| {
+                let column = design
+                    .iter()
+                    .skip(term)
+                    .step_by(num_terms)
+                    .copied()
+                    .collect::<Vec<_>>();
+                let norm = (column.iter().map(|x| x * x).sum::<f64>() / num_points).sqrt();
+                if norm > 0. {
+                    column.iter().map(|x| x / norm).collect()
+                } else {
+                    column
+                }
+            })
+            .collect::<Vec<Vec<f64>>>();
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+
+        let mut residual = points.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        let lambda_max = columns
+            .iter()
+            .map(|column| dot(column, &residual).abs() / num_points)
+            .fold(0., f64::max);
+        let tolerance = 1e-7 * (dot(&residual, &residual) / num_points).sqrt();
+        let terms = (0..num_terms)
+            .filter(|&term| columns[term].iter().any(|x| *x != 0.))
+            .collect::<Vec<_>>();
+        let mut coefficients = vec![0.; num_terms];
+        let mut support = vec![];
+        for step in 0..LASSO_PATH_STEPS {
+            let lambda =
+                lambda_max * LASSO_PATH_RATIO.powf(step as f64 / (LASSO_PATH_STEPS - 1) as f64);
+            for _ in 0..LASSO_MAX_SWEEPS {
+                let sweep = lasso_sweep(&columns, &terms, &mut coefficients, &mut residual, lambda);
+                if sweep < tolerance {
+                    break;
+                }
+                // converge on the active terms before the next sweep over all of them
+                let active = terms
+                    .iter()
+                    .copied()
+                    .filter(|&term| coefficients[term] != 0.)
+                    .collect::<Vec<_>>();
+                for _ in 0..LASSO_MAX_SWEEPS {
+                    let sweep =
+                        lasso_sweep(&columns, &active, &mut coefficients, &mut residual, lambda);
+                    if sweep < tolerance {
+                        break;
+                    }
+                }
+            }
+            let active = (0..num_terms)
+                .filter(|&term| coefficients[term] != 0.)
+                .collect::<Vec<_>>();
+            if active.len() > num_max_terms {
+                break;
+            }
+            support = active;
+        }
+
+        let mut sparse = Polynomial {
+            terms: support.iter().map(|&term| self.terms[term]).collect(),
+        };
+        if !sparse.terms.is_empty() {
+            sparse.fit_points(points, solver)?;
+        }
+        Ok(sparse)
+    }
+}
+
+/// One coordinate descent sweep of `Polynomial::get_sparse_lasso` over `terms`,
+/// returns the largest change of a coefficient.
+fn lasso_sweep(
+    columns: &[Vec<f64>],
+    terms: &[usize],
+    coefficients: &mut [f64],
+    residual: &mut [f64],
+    lambda: f64,
+) -> f64 {
+    let num_points = residual.len() as f64;
+    let mut max_change = 0f64;
+    for &term in terms {
+        let column = &columns[term];
+        // the columns have a mean square of 1, so no division is needed
+        let rho = column
+            .iter()
+            .zip(residual.iter())
+            .map(|(x, r)| x * r)
+            .sum::<f64>()
+            / num_points
+            + coefficients[term];
+        let new = rho.signum() * (rho.abs() - lambda).max(0.);
+        let change = new - coefficients[term];
+        if change != 0. {
+            residual
+                .iter_mut()
+                .zip(column)
+                .for_each(|(r, x)| *r -= change * x);
+            coefficients[term] = new;
+            max_change = max_change.max(change.abs());
+        }
+    }
+    max_change
 }
 
 impl Polynomial<f64, 1> {
@@ -580,7 +878,9 @@ impl<
             + PowUsize
             + Field
             + Scalar
-            + AbsDiffEq,
+            + AbsDiffEq
+            + num::ToPrimitive
+            + num::FromPrimitive,
     > Polynomial<N, 2>
 {
     /// ```
@@ -594,44 +894,24 @@ impl<
     /// },];
     /// let mut pol = Polynomial::new(pol);
     /// println!("f(1, 1)={}", pol.eval([1.0, 1.0]));
-    /// pol.fit(&vec![(1., 1., 1.0), (1.5, 2., 2.0)]);
+    /// pol.fit(&vec![(1., 1., 1.0), (1.5, 2., 2.0)]).unwrap();
     /// println!("{}", pol);
     /// println!("f(1, 1)={}", pol.eval([1.0, 1.0]));
     /// println!("f(1, 2)={}", pol.eval([1.5, 2.0]));
     /// approx::abs_diff_eq!(pol.eval([1.0, 1.0]), 1.0, epsilon = f64::EPSILON);
     /// approx::abs_diff_eq!(pol.eval([1.5, 2.0]), 2.0, epsilon = f64::EPSILON);
     /// ```
-    pub fn fit(&mut self, points: &[(N, N, N)]) {
-        let tems_num = self.terms.len();
-        let mut m = vec![num::Zero::zero(); tems_num.pow(2_u32)];
-        // let mut k = vec![num::Zero::zero(); tems_num];
-        iexp!(self.terms.iter().enumerate(), 2)
-            .zip(m.iter_mut())
-            .into_iter()
-            .par_bridge()
-            .for_each(|([(i, a), (j, b)], m)| {
-                *m = points
-                    .iter()
-                    .map(|(x, y, _d)| a.combine_res(b, [*x, *y]))
-                    .sum::<N>();
-            });
-
-        let k = self
-            .terms
-            .par_iter_mut()
-            .map(|a| {
-                points
-                    .iter()
-                    .map(|(x, y, d)| *d * a.res([*x, *y]))
-                    .sum::<N>()
-            })
+    pub fn fit(&mut self, points: &[(N, N, N)]) -> Result<(), String> {
+        let design = points
+            .iter()
+            .flat_map(|&(x, y, _d)| self.terms.iter().map(move |a| a.res([x, y])))
             .collect();
-        let m = Matrix::new(tems_num, tems_num, m);
-        let k = Vector::new_column(k);
-        let c = m.solve(&k).unwrap();
-        for (term, c) in self.terms.iter_mut().zip(c.iter()) {
-            term.coefficient = *c;
+        let values = points.iter().map(|&(_x, _y, d)| d).collect();
+        let c = crate::solve_fit(design, self.terms.len(), values)?;
+        for (term, c) in self.terms.iter_mut().zip(c) {
+            term.coefficient = c;
         }
+        Ok(())
     }
 }
 
@@ -646,10 +926,12 @@ impl<
             + PowUsize
             + Field
             + Scalar
-            + AbsDiffEq,
+            + AbsDiffEq
+            + num::ToPrimitive
+            + num::FromPrimitive,
     > Polynomial<N, 4>
 {
-    pub fn fit(&mut self, points: &[(N, N, N, N, N)]) {
+    pub fn fit(&mut self, points: &[(N, N, N, N, N)]) -> Result<(), String> {
         let now = Instant::now();
         let tems_num = self.terms.len();
         let mut m = vec![num::Zero::zero(); tems_num * points.len()];
@@ -668,23 +950,34 @@ impl<
                 *m = b.eval_exp([point.0, point.1, point.2, point.3]);
             });
         assert_eq!(m, ma);
-        let x = Matrix::new(tems_num, points.len(), m);
-        let y = Vector::new_column(points.iter().map(|p| p.4).collect());
+        let values = points.iter().map(|p| p.4).collect();
 
-        let y = x.clone() * y;
-        let x = x.clone() * x.transpose();
-
-        let c = x.solve(&y).unwrap();
-        for (term, c) in self.terms.iter_mut().zip(c.iter()) {
-            term.coefficient = *c;
+        let c = crate::solve_fit(m, tems_num, values)?;
+        for (term, c) in self.terms.iter_mut().zip(c) {
+            term.coefficient = c;
         }
         if cfg!(debug_assertions) {
             println!("fit time: {:?}", now.elapsed());
         }
+        Ok(())
     }
 }
 
 impl Polynomial<f64, 4> {
+    /// Fit the coefficients with the QR solver, independent of the `intel-mkl` feature.
+    /// Returns the estimated condition number of the system.
+    pub fn fit_least_squares(
+        &mut self,
+        points: &[(f64, f64, f64, f64, f64)],
+        solver: &crate::LeastSquares,
+    ) -> Result<f64, String> {
+        let points = points
+            .iter()
+            .map(|p| ([p.0, p.1, p.2, p.3], p.4))
+            .collect::<Vec<_>>();
+        self.fit_points(&points, solver)
+    }
+
     pub fn approx_error(
         &self,
         points: &[(f64, f64, f64, f64, f64)],
@@ -721,7 +1014,7 @@ impl Polynomial<f64, 4> {
         &mut self,
         points: &[(f64, f64, f64, f64, f64)],
         num_iterations: usize,
-    ) {
+    ) -> Result<(), String> {
         let mut rng = rand::thread_rng();
         let num_samples = 10000;
         let momentum_multiplier = 0.9;
@@ -730,7 +1023,7 @@ impl Polynomial<f64, 4> {
         let mut grad = vec![0.0; self.terms.len()];
         let now = std::time::Instant::now();
         println!("error = {}", self.approx_error(points, num_samples, 0));
-        self.fit(points);
+        self.fit(points)?;
         println!("error = {}", self.approx_error(points, num_samples, 0));
         let mut file = std::fs::OpenOptions::new()
             .write(true)
@@ -771,5 +1064,6 @@ impl Polynomial<f64, 4> {
             //         c.0 = o.0;
             //     });
         }
+        Ok(())
     }
 }
@@ -0,0 +1,165 @@
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// number of samples of a `Spectrum`
+pub const SPECTRUM_SAMPLES: usize = 9;
+/// wavelength of the first sample of a `Spectrum` in µm
+pub const SPECTRUM_START: f64 = 0.38;
+/// distance between the samples of a `Spectrum` in µm
+pub const SPECTRUM_STEP: f64 = 0.05;
+
+/// ## A fraction of light over the visible spectrum, like a reflectance or transmittance
+/// Sampled every 50 nm from 380 nm to 780 nm, fixed size so `Properties` stays `Copy`
+/// and fits into the element buffer of the shaders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Spectrum {
+    pub samples: [f64; SPECTRUM_SAMPLES],
+}
+
+impl Hash for Spectrum {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for sample in self.samples {
+            sample.to_bits().hash(state);
+        }
+    }
+}
+
+impl Spectrum {
+    /// the same value at all wavelengths
+    pub fn constant(value: f64) -> Self {
+        Self {
+            samples: [value; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// resample measurements of `(wavelength in µm, value in 0..=1)`, sorted by wavelength
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let spectrum = Spectrum::from_measurements(&[(0.4, 0.2), (0.7, 0.05)]).unwrap();
+    /// assert!((spectrum.at(0.55) - 0.125).abs() < 1e-9);
+    /// // constant outside of the measurements
+    /// assert!((spectrum.at(0.38) - 0.2).abs() < 1e-9);
+    /// assert!(Spectrum::from_measurements(&[(0.7, 0.05), (0.4, 0.2)]).is_err());
+    /// ```
+    pub fn from_measurements(measurements: &[(f64, f64)]) -> Result<Self, String> {
+        if measurements.is_empty() {
+            return Err(String::from("no measurements"));
+        }
+        if measurements.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(String::from("measurements are not sorted by wavelength"));
+        }
+        if let Some((wavelength, value)) = measurements
+            .iter()
+            .find(|(_, value)| !(0. ..=1.).contains(value))
+        {
+            return Err(format!(
+                "value {} at {}µm is not in 0..=1",
+                value, wavelength
+            ));
+        }
+
+        let mut samples = [0.; SPECTRUM_SAMPLES];
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let wavelength = SPECTRUM_START + i as f64 * SPECTRUM_STEP;
+            let next = measurements.partition_point(|(w, _)| *w < wavelength);
+            *sample = if next == 0 {
+                measurements[0].1
+            } else if next == measurements.len() {
+                measurements[next - 1].1
+            } else {
+                let (w0, v0) = measurements[next - 1];
+                let (w1, v1) = measurements[next];
+                v0 + (v1 - v0) * (wavelength - w0) / (w1 - w0)
+            };
+        }
+        Ok(Self { samples })
+    }
+
+    /// Reads a spectrum from a CSV file, see `Spectrum::parse_csv`.
+    pub fn read_csv(path: &Path) -> Result<Spectrum, String> {
+        match std::fs::read_to_string(path) {
+            Ok(str) => Self::parse_csv(str.as_str()),
+            Err(err) => Err(format!("problem reading file: {}", err)),
+        }
+    }
+
+    /// Parses lines of `wavelength in nm, value`, like filter datasheets list them.
+    /// Values above 1 are read as percent, a header line is skipped.
+    /// ```
+    /// # use polynomial_optics::*;
+    /// let ir_cut = Spectrum::parse_csv(
+    ///     "wavelength,transmittance
+    ///      400, 90
+    ///      650, 90
+    ///      700, 1",
+    /// )
+    /// .unwrap();
+    /// assert!((ir_cut.at(0.55) - 0.9).abs() < 1e-9);
+    /// assert!((ir_cut.at(0.75) - 0.01).abs() < 1e-9);
+    ///
+    /// let err = Spectrum::parse_csv("400, 0.5\n450; 0.5").unwrap_err();
+    /// assert!(err.starts_with("line 2:"));
+    /// ```
+    pub fn parse_csv(str: &str) -> Result<Spectrum, String> {
+        let measurements = parse_csv_measurements(str)?;
+
+        let percent = measurements.iter().any(|(_, value)| *value > 1.);
+        let measurements = measurements
+            .into_iter()
+            .map(|(wavelength, value)| {
+                (
+                    wavelength / 1000.,
+                    if percent { value / 100. } else { value },
+                )
+            })
+            .collect::<Vec<_>>();
+        Self::from_measurements(&measurements)
+    }
+
+    /// the value at `wavelength` in µm, interpolated linearly between the samples
+    pub fn at(&self, wavelength: f64) -> f64 {
+        let x = ((wavelength - SPECTRUM_START) / SPECTRUM_STEP)
+            .clamp(0., (SPECTRUM_SAMPLES - 1) as f64);
+        let i = (x as usize).min(SPECTRUM_SAMPLES - 2);
+        let t = x - i as f64;
+        self.samples[i] * (1. - t) + self.samples[i + 1] * t
+    }
+}
+
+/// Parses lines of `wavelength, value`, skipping empty lines and a header line.
+pub(crate) fn parse_csv_measurements(str: &str) -> Result<Vec<(f64, f64)>, String> {
+    let mut measurements = vec![];
+    for (line_num, line) in str.lines().enumerate() {
+        let line_num = line_num + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        let parsed = match fields.as_slice() {
+            [wavelength, value] => wavelength
+                .parse::<f64>()
+                .and_then(|wavelength| value.parse::<f64>().map(|value| (wavelength, value))),
+            _ => {
+                return Err(format!(
+                    "line {}: expected `wavelength, value`, got {:?}",
+                    line_num, line
+                ))
+            }
+        };
+        match parsed {
+            Ok((wavelength, value)) => measurements.push((wavelength, value)),
+            // the header
+            Err(_) if measurements.is_empty() && line_num == 1 => continue,
+            Err(_) => {
+                return Err(format!(
+                    "line {}: could not parse numbers in {:?}",
+                    line_num, line
+                ))
+            }
+        }
+    }
+    Ok(measurements)
+}
@@ -0,0 +1,283 @@
+use std::f64::consts::TAU;
+
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+use crate::{
+    ColourPipeline, Lens, LightSource, Properties, Ray, RayStatus, SENSOR_SAMPLES, SENSOR_START,
+    SENSOR_STEP,
+};
+
+/// wavelength in µm a `Starburst` pattern is calculated for, other wavelengths are scaled from it
+pub const STARBURST_REFERENCE: f64 = 0.55;
+/// side length of the FFT grid over the diameter of the aperture, zero padding against aliasing
+pub const STARBURST_PADDING: f64 = 4.;
+/// samples per grid cell and axis when rasterizing the aperture
+const SUPERSAMPLING: usize = 4;
+
+/// ## Far-field diffraction pattern of the aperture
+/// The Fraunhofer pattern is the squared magnitude of the Fourier transform of the aperture,
+/// here a polygon like `Ray::clip_poly` clips with. Each bin of `pattern` is
+/// `λ z / (STARBURST_PADDING D)` wide on the sensor for an exit pupil of diameter `D` at
+/// distance `z`, see `Lens::starburst_bin_size`.
+/// ```
+/// # use polynomial_optics::*;
+/// let starburst = Starburst::new(6, 128);
+/// // the centre is the brightest
+/// assert_eq!(starburst.at([0., 0.], STARBURST_REFERENCE), 1.);
+/// // six blades make spikes perpendicular to the edges, at 0°, 60°, ...
+/// let along = |angle: f64, distance: f64| {
+///     let offset = [angle.cos() * distance, angle.sin() * distance];
+///     starburst.at(offset, STARBURST_REFERENCE)
+/// };
+/// let spikes: f64 = (10..40).map(|d| along(0., d as f64)).sum();
+/// let between: f64 = (10..40).map(|d| along(30f64.to_radians(), d as f64)).sum();
+/// assert!(spikes > 10. * between);
+/// // longer wavelengths spread further
+/// assert!(starburst.at([12., 0.], 0.7) > starburst.at([12., 0.], 0.4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Starburst {
+    /// side length of the pattern, a power of 2
+    pub size: usize,
+    /// blades of the aperture, a circle below 3
+    pub num_blades: u32,
+    /// row-major intensities with the peak of 1 at `[size / 2, size / 2]`
+    pub pattern: Vec<f64>,
+}
+
+impl Starburst {
+    /// calculate the pattern of an aperture with `num_blades` on a grid of `size`²
+    pub fn new(num_blades: u32, size: usize) -> Self {
+        assert!(
+            size.is_power_of_two() && size >= 2,
+            "size has to be a power of 2 of at least 2"
+        );
+        let apothem = size as f64 / (2. * STARBURST_PADDING);
+        let edges = (0..num_blades)
+            .map(|i| {
+                let part = i as f64 * TAU / num_blades as f64;
+                Vector2::new(part.cos(), part.sin())
+            })
+            .collect::<Vec<_>>();
+        let inside = |point: Vector2<f64>| {
+            if num_blades < 3 {
+                point.magnitude() <= apothem
+            } else {
+                edges.iter().all(|dir| dir.dot(point) <= apothem)
+            }
+        };
+
+        let mut re = vec![0.; size * size];
+        let mut im = vec![0.; size * size];
+        let half = (size / 2) as f64;
+        for y in 0..size {
+            for x in 0..size {
+                let mut covered = 0;
+                for sy in 0..SUPERSAMPLING {
+                    for sx in 0..SUPERSAMPLING {
+                        let point = Vector2::new(
+                            x as f64 + (sx as f64 + 0.5) / SUPERSAMPLING as f64 - 0.5 - half,
+                            y as f64 + (sy as f64 + 0.5) / SUPERSAMPLING as f64 - 0.5 - half,
+                        );
+                        if inside(point) {
+                            covered += 1;
+                        }
+                    }
+                }
+                re[y * size + x] = covered as f64 / (SUPERSAMPLING * SUPERSAMPLING) as f64;
+            }
+        }
+
+        fft2(&mut re, &mut im, size);
+
+        // |F|², shifted so the zero frequency is in the middle
+        let mut pattern = vec![0.; size * size];
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * size + x;
+                let shifted = ((y + size / 2) % size) * size + (x + size / 2) % size;
+                pattern[shifted] = re[i] * re[i] + im[i] * im[i];
+            }
+        }
+        let peak = pattern.iter().cloned().fold(0., f64::max);
+        if peak > 0. {
+            pattern.iter_mut().for_each(|value| *value /= peak);
+        }
+
+        Self {
+            size,
+            num_blades,
+            pattern,
+        }
+    }
+
+    /// Intensity at `offset` bins of `STARBURST_REFERENCE` from the centre for light of
+    /// `wavelength` in µm. The pattern grows with the wavelength and keeps its energy,
+    /// 0 outside of the grid.
+    pub fn at(&self, offset: [f64; 2], wavelength: f64) -> f64 {
+        let scale = STARBURST_REFERENCE / wavelength;
+        let half = (self.size / 2) as f64;
+        let x = offset[0] * scale + half;
+        let y = offset[1] * scale + half;
+        let max = (self.size - 1) as f64;
+        if !(0. ..=max).contains(&x) || !(0. ..=max).contains(&y) {
+            return 0.;
+        }
+        let (x0, y0) = (
+            (x as usize).min(self.size - 2),
+            (y as usize).min(self.size - 2),
+        );
+        let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+        let value = |x: usize, y: usize| self.pattern[y * self.size + x];
+        let top = value(x0, y0) * (1. - tx) + value(x0 + 1, y0) * tx;
+        let bottom = value(x0, y0 + 1) * (1. - tx) + value(x0 + 1, y0 + 1) * tx;
+        (top * (1. - ty) + bottom * ty) * scale * scale
+    }
+
+    /// Render the pattern of `light` seen through `colour` into an image of `width` x `height`,
+    /// row-major and linear in `colour.output`. The light is at `center` in pixels and
+    /// one bin of `STARBURST_REFERENCE` is `pixels_per_bin` wide.
+    pub fn render(
+        &self,
+        width: usize,
+        height: usize,
+        center: [f64; 2],
+        pixels_per_bin: f64,
+        light: &LightSource,
+        colour: &ColourPipeline,
+    ) -> Vec<[f64; 3]> {
+        let spectrum = (0..SENSOR_SAMPLES)
+            .map(|i| {
+                let wavelength = SENSOR_START + i as f64 * SENSOR_STEP;
+                let power = light.power(wavelength);
+                (
+                    wavelength,
+                    colour.response(wavelength).map(|value| value * power),
+                )
+            })
+            .collect::<Vec<_>>();
+        let matrix = colour.get_matrix();
+
+        let mut image = vec![[0.; 3]; width * height];
+        for (i, pixel) in image.iter_mut().enumerate() {
+            let offset = [
+                ((i % width) as f64 + 0.5 - center[0]) / pixels_per_bin,
+                ((i / width) as f64 + 0.5 - center[1]) / pixels_per_bin,
+            ];
+            let mut response = [0.; 3];
+            for (wavelength, weight) in &spectrum {
+                let intensity = self.at(offset, *wavelength);
+                if intensity > 0. {
+                    for (response, weight) in response.iter_mut().zip(weight) {
+                        *response += weight * intensity;
+                    }
+                }
+            }
+            *pixel = matrix.map(|row| {
+                (0..3).map(|c| row[c] * response[c]).sum::<f64>() / SENSOR_SAMPLES as f64
+            });
+        }
+        image
+    }
+}
+
+impl Lens {
+    /// blades of the first aperture, `None` if the lens has no aperture
+    pub fn num_blades(&self) -> Option<u32> {
+        self.elements
+            .iter()
+            .find_map(|element| match element.properties {
+                Properties::Aperture(num_blades) => Some(num_blades),
+                _ => None,
+            })
+    }
+
+    /// Where a light far away in `direction` is imaged on the sensor, following the chief ray
+    /// of `wavelength` in µm through the centre of the entrance pupil.
+    /// `None` if the lens has no aperture or the ray doesn't reach the sensor.
+    pub fn light_position(&self, direction: Vector3<f64>, wavelength: f64) -> Option<[f64; 2]> {
+        let pupil = self.paraxial(wavelength).ok()?.entrance_pupil?;
+        let d = direction.normalize();
+        if d.z <= 0. {
+            return None;
+        }
+        let start = self.elements[0].position - 1.;
+        let o = Vector3::new(0., 0., pupil.position) - d * ((pupil.position - start) / d.z);
+        let mut ray = Ray::new(o, d, [0.; 4], wavelength);
+        for element in &self.elements {
+            ray.propagate(element);
+        }
+        if ray.status != RayStatus::Ok || ray.d.z.abs() < f64::EPSILON {
+            return None;
+        }
+        let hit = ray.o + ray.d * ((self.sensor_dist - ray.o.z) / ray.d.z);
+        Some([hit.x, hit.y])
+    }
+
+    /// Width on the sensor of one bin of a `Starburst` at `wavelength` in µm, in the units of
+    /// the lens that are `unit` µm long, 1e3 for mm. `None` if the lens has no aperture.
+    pub fn starburst_bin_size(&self, wavelength: f64, unit: f64) -> Option<f64> {
+        let pupil = self.paraxial(wavelength).ok()?.exit_pupil?;
+        let distance = (self.sensor_dist - pupil.position).abs();
+        Some(wavelength / unit * distance / (STARBURST_PADDING * 2. * pupil.radius))
+    }
+}
+
+/// in place 2D FFT of the `size`² grid in `re` and `im`
+fn fft2(re: &mut [f64], im: &mut [f64], size: usize) {
+    let mut row_re = vec![0.; size];
+    let mut row_im = vec![0.; size];
+    for y in 0..size {
+        fft(
+            &mut re[y * size..(y + 1) * size],
+            &mut im[y * size..(y + 1) * size],
+        );
+    }
+    for x in 0..size {
+        for y in 0..size {
+            row_re[y] = re[y * size + x];
+            row_im[y] = im[y * size + x];
+        }
+        fft(&mut row_re, &mut row_im);
+        for y in 0..size {
+            re[y * size + x] = row_re[y];
+            im[y * size + x] = row_im[y];
+        }
+    }
+}
+
+/// in place radix-2 FFT, the length has to be a power of 2
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -TAU / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}